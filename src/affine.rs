@@ -0,0 +1,461 @@
+// Points in affine coordinates, for compact storage.
+//
+// An AffinePoint holds the same coordinates as the affine points of c-xs233,
+// (X, S) with x = sqrt(b)*X and s = sqrt(b)*S, fully reduced. This takes 64
+// bytes instead of the 128 bytes of the projective point types. Converting a
+// projective point to affine coordinates needs an inversion, and
+// batch_normalize shares a single inversion across a slice of points.
+//
+// Both backends expose the extended coordinates (X:S:Z:T) of their points, so
+// the conversions and the mixed addition work on them directly. For the FFI
+// backend, see Extended::from_opaque.
+
+use crate::gf233::Gf;
+use crate::pure::{CurveParams, Extended};
+use crate::{Error, Point};
+use core::marker::PhantomData;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+/// A point in affine coordinates, e.g. `AffinePoint<Xsk233Point>`. It is half the
+/// size of the projective point types, and can be added to them directly.
+pub struct AffinePoint<P> {
+    x: Gf,
+    s: Gf,
+    point: PhantomData<P>,
+}
+
+// The conversions between the point types of a backend and extended
+// coordinates, on which the affine operations are built.
+pub(crate) trait Backend: Point<EncodedPoint = [u8; 30]> + 'static {
+    type Params: CurveParams;
+
+    fn to_extended(&self) -> Extended;
+    fn from_extended(point: &Extended) -> Self;
+
+    fn to_affine(&self) -> AffinePoint<Self> {
+        AffinePoint::from_extended(&self.to_extended())
+    }
+
+    fn from_affine(point: &AffinePoint<Self>) -> Self {
+        Self::from_extended(&point.to_extended())
+    }
+
+    fn add_affine(&self, rhs: &AffinePoint<Self>) -> Self {
+        let sum = self
+            .to_extended()
+            .add_affine::<Self::Params>(&rhs.x, &rhs.s);
+        Self::from_extended(&sum)
+    }
+}
+
+impl<P> AffinePoint<P> {
+    fn new(x: Gf, s: Gf) -> Self {
+        AffinePoint {
+            x,
+            s,
+            point: PhantomData,
+        }
+    }
+
+    /// Returns whether this is the neutral element, which is the only point with
+    /// x = 0.
+    pub fn is_neutral(&self) -> Choice {
+        self.x.is_zero()
+    }
+
+    fn from_extended(point: &Extended) -> Self {
+        let inv_z = point.z.invert();
+        AffinePoint::new(point.x * inv_z, point.s * inv_z.square())
+    }
+
+    fn to_extended(self) -> Extended {
+        Extended::from_affine(self.x, self.s)
+    }
+}
+
+// Normalizes points with Montgomery's trick: we invert the product of all Z
+// and recover the individual inverses from the prefix products, which are kept
+// in out. Z is never zero, not even for the neutral.
+fn batch_normalize<P: Backend>(points: &[P], out: &mut [AffinePoint<P>]) -> Result<(), Error> {
+    if points.len() != out.len() {
+        return Err(Error::InvalidLength {
+            expected: points.len(),
+            actual: out.len(),
+        });
+    }
+
+    let mut acc = Gf::ONE;
+    for (point, prefix) in points.iter().zip(out.iter_mut()) {
+        prefix.x = acc;
+        acc *= point.to_extended().z;
+    }
+
+    let mut inv = acc.invert();
+    for (point, out) in points.iter().zip(out.iter_mut()).rev() {
+        let point = point.to_extended();
+        let inv_z = inv * out.x;
+        inv *= point.z;
+        *out = AffinePoint::new(point.x * inv_z, point.s * inv_z.square());
+    }
+    Ok(())
+}
+
+impl<P> Clone for AffinePoint<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for AffinePoint<P> {}
+
+// the coordinates are fully reduced, so we can compare them directly
+impl<P> ConstantTimeEq for AffinePoint<P> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.x.ct_eq(&other.x) & self.s.ct_eq(&other.s)
+    }
+}
+
+impl<P> PartialEq for AffinePoint<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<P> Eq for AffinePoint<P> {}
+
+impl<P> ConditionallySelectable for AffinePoint<P> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        AffinePoint::new(
+            Gf::conditional_select(&a.x, &b.x, choice),
+            Gf::conditional_select(&a.s, &b.s, choice),
+        )
+    }
+}
+
+// the negation of (X, S) is (X, S + X). this also provides ConditionallyNegatable.
+impl<P> core::ops::Neg for &AffinePoint<P> {
+    type Output = AffinePoint<P>;
+
+    fn neg(self) -> AffinePoint<P> {
+        AffinePoint::new(self.x, self.s + self.x)
+    }
+}
+
+impl<P> core::ops::Neg for AffinePoint<P> {
+    type Output = AffinePoint<P>;
+
+    fn neg(self) -> AffinePoint<P> {
+        -&self
+    }
+}
+
+// Implements Backend for a point type of the FFI backend.
+#[cfg(feature = "ffi")]
+macro_rules! impl_ffi_backend {
+    ($point:ty, $params:ty) => {
+        impl Backend for $point {
+            type Params = $params;
+
+            fn to_extended(&self) -> Extended {
+                <$point>::to_extended(*self)
+            }
+
+            fn from_extended(point: &Extended) -> Self {
+                <$point>::from_extended(point)
+            }
+        }
+    };
+}
+
+// Implements Backend for a point type of the pure Rust backend.
+#[cfg(feature = "pure-rust")]
+macro_rules! impl_pure_backend {
+    ($point:ident, $params:ty) => {
+        impl Backend for $point {
+            type Params = $params;
+
+            fn to_extended(&self) -> Extended {
+                self.0
+            }
+
+            fn from_extended(point: &Extended) -> Self {
+                $point(*point)
+            }
+        }
+    };
+}
+
+// Implements the public API of AffinePoint<P> for a point type P that
+// implements Backend. The name is that of the public type alias, which Debug
+// prints.
+macro_rules! impl_affine {
+    ($point:ty, $name:literal) => {
+        impl AffinePoint<$point> {
+            pub fn neutral() -> Self {
+                Backend::to_affine(<$point as Point>::neutral())
+            }
+
+            pub fn generator() -> Self {
+                Backend::to_affine(<$point as Point>::generator())
+            }
+
+            pub fn from_point(point: &$point) -> Self {
+                Backend::to_affine(point)
+            }
+
+            pub fn to_point(&self) -> $point {
+                <$point as Backend>::from_affine(self)
+            }
+
+            pub fn encode(&self) -> [u8; 30] {
+                self.to_extended().encode()
+            }
+
+            /// Decodes a point. On failure, this returns the neutral together with a
+            /// false `Choice`, like [`Point::decode`].
+            pub fn decode(repr: &[u8; 30]) -> (Self, Choice) {
+                let (point, is_valid) = Extended::decode::<<$point as Backend>::Params>(repr);
+                (AffinePoint::new(point.x, point.s), is_valid)
+            }
+        }
+
+        impl $point {
+            /// Converts all points to affine coordinates, sharing a single field
+            /// inversion. Fails with [`Error::InvalidLength`] if `out` does not have
+            /// the same length as `points`, without writing to it.
+            pub fn batch_normalize(
+                points: &[Self],
+                out: &mut [AffinePoint<Self>],
+            ) -> Result<(), Error> {
+                batch_normalize(points, out)
+            }
+        }
+
+        impl Default for AffinePoint<$point> {
+            fn default() -> Self {
+                Self::neutral()
+            }
+        }
+
+        impl core::fmt::Debug for AffinePoint<$point> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                let mut hex = [0u8; 60];
+                let hex = crate::__hex_encode(&self.encode(), &mut hex)?;
+                f.debug_tuple($name).field(&format_args!("{hex}")).finish()
+            }
+        }
+
+        impl From<&$point> for AffinePoint<$point> {
+            fn from(point: &$point) -> Self {
+                Self::from_point(point)
+            }
+        }
+
+        impl From<$point> for AffinePoint<$point> {
+            fn from(point: $point) -> Self {
+                Self::from_point(&point)
+            }
+        }
+
+        impl From<&AffinePoint<$point>> for $point {
+            fn from(point: &AffinePoint<$point>) -> Self {
+                point.to_point()
+            }
+        }
+
+        impl From<AffinePoint<$point>> for $point {
+            fn from(point: AffinePoint<$point>) -> Self {
+                point.to_point()
+            }
+        }
+
+        impl core::ops::Add<&AffinePoint<$point>> for &$point {
+            type Output = $point;
+
+            fn add(self, rhs: &AffinePoint<$point>) -> $point {
+                self.add_affine(rhs)
+            }
+        }
+
+        impl core::ops::Add<&AffinePoint<$point>> for $point {
+            type Output = $point;
+
+            fn add(self, rhs: &AffinePoint<$point>) -> $point {
+                self.add_affine(rhs)
+            }
+        }
+
+        impl core::ops::AddAssign<&AffinePoint<$point>> for $point {
+            fn add_assign(&mut self, rhs: &AffinePoint<$point>) {
+                *self = self.add_affine(rhs);
+            }
+        }
+
+        impl core::ops::Sub<&AffinePoint<$point>> for &$point {
+            type Output = $point;
+
+            fn sub(self, rhs: &AffinePoint<$point>) -> $point {
+                self.add_affine(&-rhs)
+            }
+        }
+
+        impl core::ops::Sub<&AffinePoint<$point>> for $point {
+            type Output = $point;
+
+            fn sub(self, rhs: &AffinePoint<$point>) -> $point {
+                self.add_affine(&-rhs)
+            }
+        }
+
+        impl core::ops::SubAssign<&AffinePoint<$point>> for $point {
+            fn sub_assign(&mut self, rhs: &AffinePoint<$point>) {
+                *self = self.add_affine(&-rhs);
+            }
+        }
+    };
+}
+
+#[cfg(feature = "ffi")]
+impl_ffi_backend!(crate::xsk233::Xsk233Point, crate::pure::xsk233::Params);
+#[cfg(feature = "ffi")]
+impl_ffi_backend!(crate::xsb233::Xsb233Point, crate::pure::xsb233::Params);
+#[cfg(feature = "pure-rust")]
+use crate::pure::{Xsb233Point as PureXsb233Point, Xsk233Point as PureXsk233Point};
+#[cfg(feature = "pure-rust")]
+impl_pure_backend!(PureXsk233Point, crate::pure::xsk233::Params);
+#[cfg(feature = "pure-rust")]
+impl_pure_backend!(PureXsb233Point, crate::pure::xsb233::Params);
+
+#[cfg(feature = "ffi")]
+impl_affine!(crate::xsk233::Xsk233Point, "Xsk233AffinePoint");
+#[cfg(feature = "ffi")]
+impl_affine!(crate::xsb233::Xsb233Point, "Xsb233AffinePoint");
+#[cfg(feature = "pure-rust")]
+impl_affine!(crate::pure::Xsk233Point, "Xsk233AffinePoint");
+#[cfg(feature = "pure-rust")]
+impl_affine!(crate::pure::Xsb233Point, "Xsb233AffinePoint");
+
+#[cfg(test)]
+mod tests {
+    use super::{AffinePoint, Backend};
+    use crate::scalar::Scalar;
+    use crate::test_utils::curve_tests;
+    use crate::{Curve, Point};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    // the neutral and seven random points
+    fn random_points<P: Backend>(seed: u8) -> [P; 8] {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let mut points = [*P::neutral(); 8];
+        for point in points.iter_mut().skip(1) {
+            let mut buf = [0u8; 30];
+            rng.fill(&mut buf);
+            *point = P::mulgen(&Scalar::new(buf));
+        }
+        points
+    }
+
+    fn check_conversions<P: Backend + core::fmt::Debug>(seed: u8)
+    where
+        AffinePoint<P>: core::fmt::Debug,
+    {
+        assert!(core::mem::size_of::<AffinePoint<P>>() <= 64);
+
+        for point in random_points::<P>(seed).iter() {
+            let affine = point.to_affine();
+            assert!(bool::from(P::from_affine(&affine).ct_eq(point)));
+            assert_eq!(
+                bool::from(affine.is_neutral()),
+                bool::from(point.is_neutral())
+            );
+
+            let mut negated = *point;
+            negated.neg_inplace();
+            assert_eq!(-&affine, negated.to_affine());
+
+            // named after the public alias, e.g. Xsk233AffinePoint, on both backends
+            assert_eq!(
+                format!("{affine:?}"),
+                format!("{point:?}").replacen("Point(", "AffinePoint(", 1)
+            );
+        }
+    }
+
+    fn check_batch_normalize<P: Backend + core::fmt::Debug>(seed: u8)
+    where
+        AffinePoint<P>: core::fmt::Debug,
+    {
+        let points = random_points::<P>(seed);
+        let mut batch = [P::neutral().to_affine(); 8];
+        super::batch_normalize(&points, &mut batch).unwrap();
+        for (point, affine) in points.iter().zip(batch.iter()) {
+            assert_eq!(*affine, point.to_affine());
+        }
+
+        assert_eq!(
+            super::batch_normalize(&points, &mut batch[1..]),
+            Err(crate::Error::InvalidLength {
+                expected: 8,
+                actual: 7
+            })
+        );
+    }
+
+    fn check_add_affine<P: Backend>(seed: u8) {
+        let points = random_points::<P>(seed);
+        for point in points.iter() {
+            let affine = point.to_affine();
+            for other in points.iter() {
+                let mut sum = *other;
+                sum.add_assign(point);
+                assert!(bool::from(other.add_affine(&affine).ct_eq(&sum)));
+
+                let mut diff = *other;
+                diff.sub_assign(point);
+                assert!(bool::from(other.add_affine(&-&affine).ct_eq(&diff)));
+            }
+        }
+
+        // adding the affine version of a point to itself doubles it
+        let mut doubled = points[1];
+        doubled.double_inplace();
+        assert!(bool::from(
+            points[1].add_affine(&points[1].to_affine()).ct_eq(&doubled)
+        ));
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn conversions() { check_conversions::<<C as Curve>::Point>(seed) }
+        fn batch_normalize() { check_batch_normalize::<<C as Curve>::Point>(seed) }
+        fn add_affine() { check_add_affine::<<C as Curve>::Point>(seed) }
+        fn point_api() {
+            type P = <C as Curve>::Point;
+            let point = P::mulgen(&Scalar::new([42u8]));
+            let affine = AffinePoint::<P>::from_point(&point);
+            let (decoded, is_valid) = AffinePoint::<P>::decode(&affine.encode());
+            assert!(bool::from(is_valid));
+            assert_eq!(decoded, affine);
+            assert_eq!(point + &affine, point + point);
+            assert_eq!(point - &affine, *P::neutral());
+            assert_eq!(AffinePoint::<P>::default(), AffinePoint::<P>::neutral());
+            assert_eq!(
+                P::from(AffinePoint::<P>::generator()),
+                *P::generator()
+            );
+        }
+    }
+
+    #[cfg(all(feature = "ffi", feature = "pure-rust"))]
+    #[test]
+    fn pure_backend() {
+        use crate::pure::{Xsb233Point, Xsk233Point};
+        check_conversions::<Xsk233Point>(3);
+        check_batch_normalize::<Xsk233Point>(3);
+        check_add_affine::<Xsk233Point>(3);
+        check_conversions::<Xsb233Point>(4);
+        check_batch_normalize::<Xsb233Point>(4);
+        check_add_affine::<Xsb233Point>(4);
+    }
+}
@@ -0,0 +1,228 @@
+//! Batch operations on many points at once.
+//!
+//! With the `rayon` feature, the `par_*` functions spread the work over the rayon
+//! thread pool. Their results are identical to the sequential versions.
+
+use crate::lookup::{recode_signed_4, LookupTable};
+use crate::scalar::Scalar;
+use crate::Point;
+use alloc::vec::Vec;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+// the number of terms of a multi-scalar multiplication handled by one task
+#[cfg(feature = "rayon")]
+const MSM_CHUNK: usize = 64;
+
+/// Computes `k*G` for each scalar `k`.
+pub fn mulgen<P: Point, const N: usize>(scalars: &[Scalar<N>]) -> Vec<P> {
+    scalars.iter().map(P::mulgen).collect()
+}
+
+/// Computes `k*P` for each pair `(P, k)`.
+pub fn mul<P: Point, const N: usize>(pairs: &[(P, Scalar<N>)]) -> Vec<P> {
+    pairs
+        .iter()
+        .map(|(point, scalar)| {
+            let mut out = P::default();
+            out.mul(point, scalar);
+            out
+        })
+        .collect()
+}
+
+/// Computes the sum of `k*P` over all pairs `(P, k)`, in constant time.
+///
+/// The doublings are shared between all terms, which makes this considerably
+/// faster than adding up the results of [`Point::mul`].
+pub fn msm<P: Point + 'static, const N: usize>(pairs: &[(P, Scalar<N>)]) -> P {
+    let tables: Vec<LookupTable<P, 8>> = pairs
+        .iter()
+        .map(|(point, _)| LookupTable::new(point))
+        .collect();
+    let digits: Vec<_> = pairs
+        .iter()
+        .map(|(_, scalar)| recode_signed_4(scalar))
+        .collect();
+
+    let mut acc = *P::neutral();
+    for (table, (_, carry)) in tables.iter().zip(digits.iter()) {
+        acc.add_assign(&table.select_signed(*carry));
+    }
+    for i in (0..N).rev() {
+        for j in [1, 0] {
+            acc.xdouble_inplace(4);
+            for (table, (digits, _)) in tables.iter().zip(digits.iter()) {
+                acc.add_assign(&table.select_signed(digits[i][j]));
+            }
+        }
+    }
+    acc
+}
+
+/// Encodes all points.
+pub fn encode<P: Point>(points: &[P]) -> Vec<P::EncodedPoint>
+where
+    P::EncodedPoint: Default,
+{
+    points
+        .iter()
+        .map(|point| {
+            let mut buf = P::EncodedPoint::default();
+            point.encode(&mut buf);
+            buf
+        })
+        .collect()
+}
+
+/// Like [`mulgen`], but in parallel.
+#[cfg(feature = "rayon")]
+pub fn par_mulgen<P: Point + Send, const N: usize>(scalars: &[Scalar<N>]) -> Vec<P> {
+    scalars.par_iter().map(P::mulgen).collect()
+}
+
+/// Like [`mul`], but in parallel.
+#[cfg(feature = "rayon")]
+pub fn par_mul<P: Point + Send + Sync, const N: usize>(pairs: &[(P, Scalar<N>)]) -> Vec<P> {
+    pairs
+        .par_iter()
+        .map(|(point, scalar)| {
+            let mut out = P::default();
+            out.mul(point, scalar);
+            out
+        })
+        .collect()
+}
+
+/// Like [`msm`], but in parallel. The terms are split into chunks whose sums are
+/// computed independently and then added up.
+#[cfg(feature = "rayon")]
+pub fn par_msm<P: Point + Send + Sync + 'static, const N: usize>(pairs: &[(P, Scalar<N>)]) -> P {
+    pairs.par_chunks(MSM_CHUNK).map(msm).reduce(
+        || *P::neutral(),
+        |mut lhs, rhs| {
+            lhs.add_assign(&rhs);
+            lhs
+        },
+    )
+}
+
+/// Like [`encode`], but in parallel.
+#[cfg(feature = "rayon")]
+pub fn par_encode<P: Point + Sync>(points: &[P]) -> Vec<P::EncodedPoint>
+where
+    P::EncodedPoint: Default + Send,
+{
+    points
+        .par_iter()
+        .map(|point| {
+            let mut buf = P::EncodedPoint::default();
+            point.encode(&mut buf);
+            buf
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use crate::Curve;
+    use alloc::vec;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    fn random_scalars(rng: &mut ChaCha8Rng, n: usize) -> Vec<Scalar<30>> {
+        (0..n)
+            .map(|_| {
+                let mut buf = [0u8; 30];
+                rng.fill(&mut buf);
+                Scalar::new(buf)
+            })
+            .collect()
+    }
+
+    fn check_mulgen<P: Point + 'static>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let scalars = random_scalars(&mut rng, 150);
+
+        let points: Vec<P> = mulgen(&scalars);
+        for (point, scalar) in points.iter().zip(scalars.iter()) {
+            assert!(bool::from(point.ct_eq(&P::mulgen(scalar))));
+        }
+    }
+
+    fn random_pairs<P: Point + 'static>(seed: u8) -> Vec<(P, Scalar<30>)> {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let scalars = random_scalars(&mut rng, 150);
+        let points: Vec<P> = mulgen(&scalars);
+        points
+            .into_iter()
+            .zip(random_scalars(&mut rng, 150))
+            .collect()
+    }
+
+    fn check_msm<P: Point + 'static>(seed: u8) {
+        let pairs = random_pairs::<P>(seed);
+        let products = mul(&pairs);
+        for (product, (point, scalar)) in products.iter().zip(pairs.iter()) {
+            let mut expected = P::default();
+            expected.mul(point, scalar);
+            assert!(bool::from(product.ct_eq(&expected)));
+        }
+
+        let mut sum = *P::neutral();
+        for product in products.iter() {
+            sum.add_assign(product);
+        }
+        assert!(bool::from(msm(&pairs).ct_eq(&sum)));
+        assert!(bool::from(msm(&pairs[..1]).ct_eq(&products[0])));
+        assert!(bool::from(msm::<P, 30>(&[]).is_neutral()));
+
+        let short = vec![(pairs[0].0, Scalar::new([0xff; 3]))];
+        let mut expected = P::default();
+        expected.mul(&pairs[0].0, &short[0].1);
+        assert!(bool::from(msm(&short).ct_eq(&expected)));
+    }
+
+    fn check_encode<P>(seed: u8)
+    where
+        P: Point + 'static,
+        P::EncodedPoint: Default + PartialEq + core::fmt::Debug,
+    {
+        let points: Vec<P> = random_pairs(seed).into_iter().map(|(p, _)| p).collect();
+        let encoded = encode(&points);
+        assert_eq!(encoded.len(), points.len());
+        for (repr, point) in encoded.iter().zip(points.iter()) {
+            assert_eq!(*repr, point.to_encoded());
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    fn check_parallel<P>(seed: u8)
+    where
+        P: Point + Send + Sync + 'static,
+        P::EncodedPoint: Default + Send + PartialEq + core::fmt::Debug,
+    {
+        let pairs = random_pairs::<P>(seed);
+        let points: Vec<P> = pairs.iter().map(|(p, _)| *p).collect();
+        let scalars: Vec<Scalar<30>> = pairs.iter().map(|(_, s)| *s).collect();
+        assert_eq!(
+            encode(&par_mulgen::<P, 30>(&scalars)),
+            encode(&mulgen::<P, 30>(&scalars))
+        );
+        assert_eq!(par_encode(&points), encode(&points));
+        assert_eq!(par_encode(&par_mul(&pairs)), encode(&mul(&pairs)));
+        assert_eq!(par_msm(&pairs).to_encoded(), msm(&pairs).to_encoded());
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn mulgen_each() { check_mulgen::<<C as Curve>::Point>(seed) }
+        fn mul_and_msm() { check_msm::<<C as Curve>::Point>(seed) }
+        fn encode_each() { check_encode::<<C as Curve>::Point>(seed) }
+        #[cfg(feature = "rayon")]
+        fn parallel_matches_serial() { check_parallel::<<C as Curve>::Point>(seed) }
+    }
+}
@@ -0,0 +1,370 @@
+// COSE_Key objects (RFC 9052, section 7) for the keys in crate::keys.
+//
+// The keys are CBOR maps with integer labels, written in the deterministic order of
+// RFC 8949, section 4.2.1. As for JWKs, the KeyFormat selects the key type:
+//
+// - Sec1 keys have the kty EC2 (2) and the crv "K-233" or "B-233", with the
+//   big-endian coordinates x (-2) and y (-3) and the big-endian scalar d (-4), as in
+//   RFC 9053, section 7.1.1. When decoding, y may also be the boolean sign bit of a
+//   compressed point, which is the tag bit of the SEC1 compressed encoding.
+// - Native keys have the kty OKP (1) and the crv "xsk233" or "xsb233", with the
+//   30-byte point encoding x (-2) and the 30-byte scalar d (-4).
+//
+// There are no registered curve identifiers for these curves, so crv is a text
+// string. Labels other than these, such as kid, are ignored.
+
+use crate::keys::{KeyCurve, KeyFormat, PublicKey, SecretKey};
+use crate::Error;
+use alloc::vec;
+use alloc::vec::Vec;
+use ciborium::Value;
+use zeroize::{Zeroize, Zeroizing};
+
+const LABEL_KTY: i64 = 1;
+const LABEL_CRV: i64 = -1;
+const LABEL_X: i64 = -2;
+const LABEL_Y: i64 = -3;
+const LABEL_D: i64 = -4;
+
+const KTY_OKP: i64 = 1;
+const KTY_EC2: i64 = 2;
+
+// The members of a parsed COSE_Key, see parse.
+#[derive(Default)]
+struct CoseKey {
+    kty: Option<Value>,
+    crv: Option<Value>,
+    x: Option<Value>,
+    y: Option<Value>,
+    d: Option<Value>,
+}
+
+impl CoseKey {
+    fn format<C: KeyCurve>(&self) -> Result<KeyFormat, Error> {
+        let kty = self.kty.as_ref().and_then(Value::as_integer);
+        let crv = self.crv.as_ref().and_then(Value::as_text);
+        match (kty.and_then(|kty| i64::try_from(kty).ok()), crv) {
+            (Some(KTY_EC2), Some(crv)) if crv == C::STANDARD_NAME => Ok(KeyFormat::Sec1),
+            (Some(KTY_OKP), Some(crv)) if crv == C::NAME => Ok(KeyFormat::Native),
+            _ => Err(Error::InvalidKey),
+        }
+    }
+
+    fn public_key<C: KeyCurve>(&self) -> Result<PublicKey<C>, Error> {
+        let format = self.format::<C>()?;
+        let x = byte_string(&self.x)?;
+        match (format, &self.y) {
+            (KeyFormat::Sec1, Some(Value::Bytes(y))) => {
+                // the coordinates must have the full length
+                if x.len() != 30 || y.len() != 30 {
+                    return Err(Error::InvalidLength {
+                        expected: 30,
+                        actual: if x.len() != 30 { x.len() } else { y.len() },
+                    });
+                }
+                let mut sec1 = [0u8; 61];
+                sec1[0] = 0x04;
+                sec1[1..31].copy_from_slice(x);
+                sec1[31..].copy_from_slice(y);
+                PublicKey::decode(format, &sec1)
+            }
+            (KeyFormat::Sec1, Some(Value::Bool(sign))) => {
+                if x.len() != 30 {
+                    return Err(Error::InvalidLength {
+                        expected: 30,
+                        actual: x.len(),
+                    });
+                }
+                let mut sec1 = [0u8; 31];
+                sec1[0] = 0x02 | u8::from(*sign);
+                sec1[1..].copy_from_slice(x);
+                PublicKey::decode(format, &sec1)
+            }
+            (KeyFormat::Native, None) => PublicKey::decode(format, x),
+            _ => Err(Error::InvalidKey),
+        }
+    }
+}
+
+impl Drop for CoseKey {
+    fn drop(&mut self) {
+        if let Some(Value::Bytes(d)) = &mut self.d {
+            d.zeroize();
+        }
+    }
+}
+
+fn byte_string(value: &Option<Value>) -> Result<&[u8], Error> {
+    match value {
+        Some(Value::Bytes(bytes)) => Ok(bytes),
+        _ => Err(Error::InvalidKey),
+    }
+}
+
+fn parse(bytes: &[u8]) -> Result<CoseKey, Error> {
+    let value: Value = ciborium::from_reader(bytes).map_err(|_| Error::InvalidKey)?;
+    let Value::Map(entries) = value else {
+        return Err(Error::InvalidKey);
+    };
+
+    let mut key = CoseKey::default();
+    for (label, value) in entries {
+        let Some(label) = label.as_integer() else {
+            continue;
+        };
+        let slot = match i64::try_from(label) {
+            Ok(LABEL_KTY) => &mut key.kty,
+            Ok(LABEL_CRV) => &mut key.crv,
+            Ok(LABEL_X) => &mut key.x,
+            Ok(LABEL_Y) => &mut key.y,
+            Ok(LABEL_D) => &mut key.d,
+            _ => continue,
+        };
+        // labels must not be repeated
+        if slot.replace(value).is_some() {
+            return Err(Error::InvalidKey);
+        }
+    }
+    Ok(key)
+}
+
+fn serialize(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out).expect("writing to a Vec does not fail");
+    out
+}
+
+impl<C: KeyCurve> PublicKey<C> {
+    /// Encodes the key as a COSE_Key object of the given format.
+    ///
+    /// [`KeyFormat::Sec1`] keys have the `kty` EC2 and the `crv` `"K-233"` or
+    /// `"B-233"`, with the big-endian coordinates `x` and `y`. [`KeyFormat::Native`]
+    /// keys have the `kty` OKP and the `crv` `"xsk233"` or `"xsb233"`, with the
+    /// 30-byte point encoding `x`. The curve names are not registered with IANA, so
+    /// they are text strings.
+    pub fn to_cose_key(&self, format: KeyFormat) -> Vec<u8> {
+        serialize(&Value::Map(self.cose_entries(format)))
+    }
+
+    fn cose_entries(&self, format: KeyFormat) -> Vec<(Value, Value)> {
+        match format {
+            KeyFormat::Sec1 => {
+                let sec1 = self.to_sec1_bytes();
+                vec![
+                    (LABEL_KTY.into(), KTY_EC2.into()),
+                    (LABEL_CRV.into(), C::STANDARD_NAME.into()),
+                    (LABEL_X.into(), sec1[1..31].into()),
+                    (LABEL_Y.into(), sec1[31..].into()),
+                ]
+            }
+            KeyFormat::Native => vec![
+                (LABEL_KTY.into(), KTY_OKP.into()),
+                (LABEL_CRV.into(), C::NAME.into()),
+                (LABEL_X.into(), self.to_bytes()[..].into()),
+            ],
+        }
+    }
+
+    /// Decodes the public part of a public or private COSE_Key object. `EC2` keys
+    /// may also hold the sign bit of a compressed point as `y`.
+    pub fn from_cose_key(bytes: &[u8]) -> Result<Self, Error> {
+        parse(bytes)?.public_key()
+    }
+}
+
+impl<C: KeyCurve> SecretKey<C> {
+    /// Encodes the key as a private COSE_Key object of the given format, which
+    /// includes the public key, see [`PublicKey::to_cose_key`].
+    pub fn to_cose_key(&self, format: KeyFormat) -> Zeroizing<Vec<u8>> {
+        let d = match format {
+            KeyFormat::Sec1 => {
+                let mut scalar = self.to_sec1_bytes();
+                let d = scalar.to_vec();
+                scalar.as_mut_slice().zeroize();
+                d
+            }
+            KeyFormat::Native => Zeroizing::new(self.to_bytes()).to_vec(),
+        };
+        let mut entries = self.public_key().cose_entries(format);
+        entries.push((LABEL_D.into(), Value::Bytes(d)));
+        let mut key = Value::Map(entries);
+        let out = Zeroizing::new(serialize(&key));
+        if let Value::Map(entries) = &mut key {
+            for (_, value) in entries.iter_mut() {
+                if let Value::Bytes(bytes) = value {
+                    bytes.zeroize();
+                }
+            }
+        }
+        out
+    }
+
+    /// Decodes a private COSE_Key object. The public key must match the secret
+    /// scalar.
+    pub fn from_cose_key(bytes: &[u8]) -> Result<Self, Error> {
+        let key = parse(bytes)?;
+        let format = key.format::<C>()?;
+        let secret_key = SecretKey::decode(format, byte_string(&key.d)?)?;
+        if key.public_key::<C>()? != *secret_key.public_key() {
+            return Err(Error::InvalidKey);
+        }
+        Ok(secret_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn check_roundtrip<C: KeyCurve, Other: KeyCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret_key = SecretKey::<C>::random_from_rng(&mut rng);
+        let public_key = *secret_key.public_key();
+
+        for format in [KeyFormat::Sec1, KeyFormat::Native] {
+            let encoded = secret_key.to_cose_key(format);
+            let decoded = SecretKey::<C>::from_cose_key(&encoded).unwrap();
+            assert_eq!(decoded.to_bytes(), secret_key.to_bytes());
+            assert_eq!(PublicKey::<C>::from_cose_key(&encoded), Ok(public_key));
+
+            let encoded = public_key.to_cose_key(format);
+            assert_eq!(PublicKey::<C>::from_cose_key(&encoded), Ok(public_key));
+            assert_eq!(
+                SecretKey::<C>::from_cose_key(&encoded).err(),
+                Some(Error::InvalidKey)
+            );
+            assert_eq!(
+                PublicKey::<Other>::from_cose_key(&encoded),
+                Err(Error::InvalidKey)
+            );
+        }
+    }
+
+    fn check_deterministic_encoding<C: KeyCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret_key = SecretKey::<C>::random_from_rng(&mut rng);
+        let public_key = *secret_key.public_key();
+
+        // {1: 1, -1: name, -2: x}, in the deterministic encoding
+        let mut expected = vec![0xa3, 0x01, 0x01, 0x20, 0x66];
+        expected.extend_from_slice(C::NAME.as_bytes());
+        expected.extend_from_slice(&[0x21, 0x58, 30]);
+        expected.extend_from_slice(&public_key.to_bytes());
+        assert_eq!(public_key.to_cose_key(KeyFormat::Native), expected);
+    }
+
+    fn check_ec2_members<C: KeyCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret_key = SecretKey::<C>::random_from_rng(&mut rng);
+        let public_key = *secret_key.public_key();
+
+        // y may be the sign bit of a compressed point
+        let sec1 = public_key.to_sec1_bytes();
+        let compressed = crate::ecdsa::VerifyingKey::from(&public_key).to_sec1_compressed();
+        let entries = |y: Value| {
+            vec![
+                (LABEL_KTY.into(), KTY_EC2.into()),
+                (LABEL_CRV.into(), C::STANDARD_NAME.into()),
+                (LABEL_X.into(), sec1[1..31].into()),
+                (LABEL_Y.into(), y),
+            ]
+        };
+        let sign = compressed[0] & 1 == 1;
+        let encoded = serialize(&Value::Map(entries(sign.into())));
+        assert_eq!(PublicKey::<C>::from_cose_key(&encoded), Ok(public_key));
+        let encoded = serialize(&Value::Map(entries((!sign).into())));
+        assert_ne!(PublicKey::<C>::from_cose_key(&encoded), Ok(public_key));
+
+        // unknown labels are ignored, repeated labels are rejected
+        let mut map = entries(sec1[31..].into());
+        map.push((2.into(), b"key-1"[..].into()));
+        map.push(("name".into(), "value".into()));
+        assert_eq!(
+            PublicKey::<C>::from_cose_key(&serialize(&Value::Map(map.clone()))),
+            Ok(public_key)
+        );
+        map.push((LABEL_X.into(), sec1[1..31].into()));
+        assert_eq!(
+            PublicKey::<C>::from_cose_key(&serialize(&Value::Map(map))),
+            Err(Error::InvalidKey)
+        );
+        assert_eq!(
+            PublicKey::<C>::from_cose_key(&serialize(&Value::Array(vec![]))),
+            Err(Error::InvalidKey)
+        );
+        assert_eq!(
+            PublicKey::<C>::from_cose_key(&[0xa3]),
+            Err(Error::InvalidKey)
+        );
+    }
+
+    fn check_invalid_points<C: KeyCurve>() {
+        // invalid points are rejected through Point::decode
+        let native = |x: &[u8]| {
+            serialize(&Value::Map(vec![
+                (LABEL_KTY.into(), KTY_OKP.into()),
+                (LABEL_CRV.into(), C::NAME.into()),
+                (LABEL_X.into(), x.into()),
+            ]))
+        };
+        assert_eq!(
+            PublicKey::<C>::from_cose_key(&native(&[0; 30])),
+            Err(Error::IdentityPoint)
+        );
+        let mut w_is_one = [0u8; 30];
+        w_is_one[0] = 1;
+        assert_eq!(
+            PublicKey::<C>::from_cose_key(&native(&w_is_one)),
+            Err(Error::InvalidEncoding)
+        );
+        assert_eq!(
+            PublicKey::<C>::from_cose_key(&native(&[0; 31])),
+            Err(Error::InvalidLength {
+                expected: 30,
+                actual: 31
+            })
+        );
+    }
+
+    fn check_invalid_scalars<C: KeyCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret_key = SecretKey::<C>::random_from_rng(&mut rng);
+        let public_key = *secret_key.public_key();
+
+        // invalid scalars, and the public key of another key
+        let private = |d: &[u8], x: &[u8]| {
+            serialize(&Value::Map(vec![
+                (LABEL_KTY.into(), KTY_OKP.into()),
+                (LABEL_CRV.into(), C::NAME.into()),
+                (LABEL_X.into(), x.into()),
+                (LABEL_D.into(), d.into()),
+            ]))
+        };
+        let x = public_key.to_bytes();
+        assert_eq!(
+            SecretKey::<C>::from_cose_key(&private(&[0; 30], &x)).err(),
+            Some(Error::ZeroScalar)
+        );
+        assert_eq!(
+            SecretKey::<C>::from_cose_key(&private(&[0xff; 30], &x)).err(),
+            Some(Error::NonCanonicalScalar)
+        );
+        let other = SecretKey::<C>::random_from_rng(&mut rng);
+        assert_eq!(
+            SecretKey::<C>::from_cose_key(&private(&other.to_bytes(), &x)).err(),
+            Some(Error::InvalidKey)
+        );
+    }
+
+    curve_tests! {
+        |C, Other, seed|
+        fn roundtrip() { check_roundtrip::<C, Other>(seed) }
+        fn deterministic_encoding() { check_deterministic_encoding::<C>(seed) }
+        fn ec2_members() { check_ec2_members::<C>(seed) }
+        fn invalid_points() { check_invalid_points::<C>() }
+        fn invalid_scalars() { check_invalid_scalars::<C>(seed) }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::scalar::Scalar;
+use crate::Point;
+use core::ops::{Add, Mul, Neg, Sub};
+use subtle::{ConditionallySelectable, ConstantTimeEq};
+
+/// The parameters of a prime order group, so that protocol code can be written once
+/// for both curves.
+///
+/// The groups in this crate are described by the marker types
+/// [`Xsk233`](crate::xsk233::Xsk233) and [`Xsb233`](crate::xsb233::Xsb233).
+pub trait Curve:
+    Clone + Copy + core::fmt::Debug + Default + PartialEq + Eq + core::hash::Hash + 'static
+{
+    /// The name of the group, e.g. `"xsk233"`.
+    const NAME: &'static str;
+
+    /// The (prime) order of the group, as little-endian 64-bit limbs.
+    const ORDER: [u64; 4];
+
+    /// The cofactor of the standard curve the group is derived from. The group itself
+    /// has prime order, so this only matters when interoperating with the standard
+    /// curve, e.g. for ECDSA.
+    const COFACTOR: u32;
+
+    /// The length of an encoded point in bytes.
+    const ENCODED_LEN: usize;
+
+    /// The point type. Both groups encode points in 30 bytes.
+    type Point: Point<EncodedPoint = [u8; 30]> + 'static;
+
+    /// Integers modulo [`Curve::ORDER`]. They are converted to a byte [`Scalar`] for
+    /// use with [`Point::mul`] and [`Point::mulgen`].
+    type Scalar: Copy
+        + core::fmt::Debug
+        + Default
+        + ConstantTimeEq
+        + ConditionallySelectable
+        + Add<Output = Self::Scalar>
+        + Sub<Output = Self::Scalar>
+        + Mul<Output = Self::Scalar>
+        + Neg<Output = Self::Scalar>
+        + Into<Scalar<30>>;
+}
@@ -0,0 +1,241 @@
+//! Diffie-Hellman key agreement, in the style of x25519-dalek.
+//!
+//! Both parties generate a secret and send the corresponding [`PublicKey`] to each
+//! other. Calling `diffie_hellman` with the public key of the other party yields the
+//! same [`SharedSecret`] on both sides, which is the encoding of the shared point.
+//! The shared secret is not uniformly random and should be passed through a key
+//! derivation function before use.
+//!
+//! Public keys are never the neutral, and secrets are never zero, so the shared point
+//! is never the neutral either. Secrets are zeroized on drop.
+
+use crate::curve::Curve;
+use crate::scalar::ModScalar;
+use crate::{Error, Point};
+use core::marker::PhantomData;
+use rand_core::CryptoRngCore;
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A secret that can only be used for a single key agreement.
+pub struct EphemeralSecret<C: Curve>(ModScalar<C>);
+
+/// A secret that can be used for many key agreements, and be stored.
+#[derive(Clone)]
+pub struct StaticSecret<C: Curve>(ModScalar<C>);
+
+/// The public key of a party, which is never the neutral.
+#[derive(Clone, Copy, Debug)]
+pub struct PublicKey<C: Curve>(C::Point);
+
+/// The result of a key agreement: the encoding of the shared point.
+pub struct SharedSecret<C: Curve>([u8; 30], PhantomData<C>);
+
+fn diffie_hellman<C: Curve>(secret: &ModScalar<C>, public: &PublicKey<C>) -> SharedSecret<C> {
+    let mut scalar = secret.to_scalar();
+    let mut shared = C::Point::default();
+    shared.mul(&public.0, &scalar);
+    scalar.zeroize();
+
+    SharedSecret(shared.to_encoded(), PhantomData)
+}
+
+impl<C: Curve> EphemeralSecret<C> {
+    pub fn random_from_rng(rng: &mut impl CryptoRngCore) -> Self {
+        EphemeralSecret(ModScalar::random(rng))
+    }
+
+    /// Performs the key agreement, consuming the secret.
+    pub fn diffie_hellman(self, their_public: &PublicKey<C>) -> SharedSecret<C> {
+        diffie_hellman(&self.0, their_public)
+    }
+}
+
+impl<C: Curve> StaticSecret<C> {
+    pub fn random_from_rng(rng: &mut impl CryptoRngCore) -> Self {
+        StaticSecret(ModScalar::random(rng))
+    }
+
+    /// Decodes a secret from its canonical 30-byte encoding. Zero is rejected.
+    pub fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+        let scalar = ModScalar::from_bytes_non_zero(bytes)?;
+        Ok(StaticSecret(scalar))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        self.0.to_bytes()
+    }
+
+    pub fn diffie_hellman(&self, their_public: &PublicKey<C>) -> SharedSecret<C> {
+        diffie_hellman(&self.0, their_public)
+    }
+}
+
+impl<C: Curve> PublicKey<C> {
+    /// Decodes a public key, rejecting invalid encodings and the neutral.
+    pub fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+        C::Point::try_decode_non_neutral(bytes).map(PublicKey)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        self.0.to_encoded()
+    }
+
+    pub fn as_point(&self) -> &C::Point {
+        &self.0
+    }
+}
+
+impl<C: Curve> SharedSecret<C> {
+    pub fn as_bytes(&self) -> &[u8; 30] {
+        &self.0
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        self.0
+    }
+}
+
+fn public_key<C: Curve>(secret: &ModScalar<C>) -> PublicKey<C> {
+    let mut scalar = secret.to_scalar();
+    let point = C::Point::mulgen(&scalar);
+    scalar.zeroize();
+    PublicKey(point)
+}
+
+impl<C: Curve> From<&EphemeralSecret<C>> for PublicKey<C> {
+    fn from(secret: &EphemeralSecret<C>) -> Self {
+        public_key(&secret.0)
+    }
+}
+
+impl<C: Curve> From<&StaticSecret<C>> for PublicKey<C> {
+    fn from(secret: &StaticSecret<C>) -> Self {
+        public_key(&secret.0)
+    }
+}
+
+impl<C: Curve> TryFrom<&[u8]> for PublicKey<C> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: &[u8; 30] = bytes.try_into().map_err(|_| Error::InvalidLength {
+            expected: 30,
+            actual: bytes.len(),
+        })?;
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<C: Curve> PartialEq for PublicKey<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl<C: Curve> Eq for PublicKey<C> {}
+
+impl<C: Curve> ConstantTimeEq for SharedSecret<C> {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl<C: Curve> Drop for EphemeralSecret<C> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<C: Curve> Drop for StaticSecret<C> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<C: Curve> Drop for SharedSecret<C> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<C: Curve> ZeroizeOnDrop for EphemeralSecret<C> {}
+impl<C: Curve> ZeroizeOnDrop for StaticSecret<C> {}
+impl<C: Curve> ZeroizeOnDrop for SharedSecret<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn check_agreement<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+
+        let alice = EphemeralSecret::<C>::random_from_rng(&mut rng);
+        let bob = StaticSecret::<C>::random_from_rng(&mut rng);
+        let alice_public = PublicKey::from(&alice);
+        let bob_public = PublicKey::from(&bob);
+
+        let bob_shared = bob.diffie_hellman(&alice_public);
+        let alice_shared = alice.diffie_hellman(&bob_public);
+        assert_eq!(alice_shared.as_bytes(), bob_shared.as_bytes());
+
+        // the shared secret is the encoding of the shared point
+        let mut expected = C::Point::default();
+        expected.mul(
+            alice_public.as_point(),
+            &ModScalar::<C>::from_canonical_bytes(&bob.to_bytes())
+                .unwrap()
+                .to_scalar(),
+        );
+        assert_eq!(bob_shared.to_bytes(), expected.to_encoded());
+    }
+
+    fn check_roundtrip<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret = StaticSecret::<C>::random_from_rng(&mut rng);
+        let public = PublicKey::from(&secret);
+
+        let decoded = StaticSecret::<C>::from_bytes(&secret.to_bytes()).unwrap();
+        assert_eq!(PublicKey::from(&decoded), public);
+        assert_eq!(PublicKey::<C>::from_bytes(&public.to_bytes()), Ok(public));
+        assert_eq!(PublicKey::<C>::try_from(&public.to_bytes()[..]), Ok(public));
+    }
+
+    fn check_invalid_inputs<C: Curve>() {
+        assert_eq!(
+            PublicKey::<C>::from_bytes(&[0; 30]).unwrap_err(),
+            Error::IdentityPoint
+        );
+        let mut w_is_one = [0u8; 30];
+        w_is_one[0] = 1;
+        assert_eq!(
+            PublicKey::<C>::from_bytes(&w_is_one).unwrap_err(),
+            Error::InvalidEncoding
+        );
+        assert_eq!(
+            PublicKey::<C>::try_from(&[0u8; 29][..]).unwrap_err(),
+            Error::InvalidLength {
+                expected: 30,
+                actual: 29
+            }
+        );
+        assert!(matches!(
+            StaticSecret::<C>::from_bytes(&[0; 30]),
+            Err(Error::ZeroScalar)
+        ));
+        assert!(matches!(
+            StaticSecret::<C>::from_bytes(&[0xff; 30]),
+            Err(Error::NonCanonicalScalar)
+        ));
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn agreement() { check_agreement::<C>(seed) }
+        fn roundtrip() { check_roundtrip::<C>(seed) }
+        fn invalid_inputs() { check_invalid_inputs::<C>() }
+    }
+}
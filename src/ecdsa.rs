@@ -0,0 +1,1027 @@
+//! ECDSA over the standard curves K-233 (sect233k1) and B-233 (sect233r1), as
+//! specified in SEC1 and X9.62.
+//!
+//! The xs233 groups are isomorphic to the prime order subgroups of these curves, so
+//! keys and signatures interoperate with other implementations of the standard
+//! curves: public keys are converted from and to SEC1 point encodings, and
+//! signatures are encoded in DER or as the fixed-width concatenation of `r` and `s`.
+//! The arithmetic uses [`Point::mulgen`] and [`Point::mul`] of the xs233 point types,
+//! and only the x coordinate of `R` is converted to the standard curve.
+//!
+//! Nonces are derived deterministically as specified in RFC 6979, with HMAC over the
+//! hash function of the message. [`SigningKey::sign`] and [`VerifyingKey::verify`]
+//! hash the message with SHA-256, the `*_digest` methods accept any hash function.
+
+use crate::curve::Curve;
+use crate::scalar::ModScalar;
+use crate::xsb233::Xsb233;
+use crate::xsk233::Xsk233;
+use crate::{sec1, Error, Point};
+use digest::core_api::BlockSizeUser;
+use digest::generic_array::{ArrayLength, GenericArray};
+use digest::typenum::{Unsigned, U29, U30, U58, U60};
+use digest::{Digest, KeyInit, Mac, Output};
+use hmac::SimpleHmac;
+use rand_core::CryptoRngCore;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A scalar, such as a secret key, in the big-endian SEC1 encoding.
+pub type ScalarBytes<C> = GenericArray<u8, <C as EcdsaCurve>::ScalarSize>;
+
+/// A signature in the fixed-width encoding `r || s`.
+pub type SignatureBytes<C> = GenericArray<u8, <C as EcdsaCurve>::SignatureSize>;
+
+/// The groups that ECDSA is implemented for: [`Xsk233`] for K-233 and [`Xsb233`] for
+/// B-233.
+pub trait EcdsaCurve: Curve + sealed::Sealed {
+    /// The length of scalars in bytes, which is the byte length of the order: 29 for
+    /// K-233 and 30 for B-233.
+    type ScalarSize: ArrayLength<u8>;
+
+    /// The length of a fixed-width signature in bytes, twice the scalar length.
+    type SignatureSize: ArrayLength<u8>;
+}
+
+mod sealed {
+    use crate::sec1::{COMPRESSED_LEN, UNCOMPRESSED_LEN};
+    use crate::Error;
+
+    // The conversions between encoded group elements and the standard curve, see
+    // crate::sec1.
+    pub trait Sealed {
+        fn decode_sec1(bytes: &[u8]) -> Result<[u8; 30], Error>;
+        fn encode_sec1_uncompressed(repr: &[u8; 30]) -> [u8; UNCOMPRESSED_LEN];
+        fn encode_sec1_compressed(repr: &[u8; 30]) -> [u8; COMPRESSED_LEN];
+        fn x_coordinate(repr: &[u8; 30]) -> [u8; 30];
+    }
+}
+
+macro_rules! impl_ecdsa_curve {
+    ($curve:ty, $params:ty, $scalar_size:ty, $signature_size:ty) => {
+        impl sealed::Sealed for $curve {
+            fn decode_sec1(bytes: &[u8]) -> Result<[u8; 30], Error> {
+                sec1::decode::<$params>(bytes)
+            }
+
+            fn encode_sec1_uncompressed(repr: &[u8; 30]) -> [u8; sec1::UNCOMPRESSED_LEN] {
+                sec1::encode_uncompressed::<$params>(repr)
+            }
+
+            fn encode_sec1_compressed(repr: &[u8; 30]) -> [u8; sec1::COMPRESSED_LEN] {
+                sec1::encode_compressed::<$params>(repr)
+            }
+
+            fn x_coordinate(repr: &[u8; 30]) -> [u8; 30] {
+                sec1::x_coordinate::<$params>(repr)
+            }
+        }
+
+        impl EcdsaCurve for $curve {
+            type ScalarSize = $scalar_size;
+            type SignatureSize = $signature_size;
+        }
+    };
+}
+
+impl_ecdsa_curve!(Xsk233, crate::pure::xsk233::Params, U29, U58);
+impl_ecdsa_curve!(Xsb233, crate::pure::xsb233::Params, U30, U60);
+
+fn scalar_len<C: EcdsaCurve>() -> usize {
+    C::ScalarSize::USIZE
+}
+
+// bits2int of RFC 6979, section 2.3.2: the leftmost bits of the input, as many as
+// the order has, as a little-endian integer.
+fn bits2int<C: EcdsaCurve>(bytes: &[u8]) -> [u8; 30] {
+    let len = bytes.len().min(scalar_len::<C>());
+    let mut out = [0u8; 30];
+    for (i, byte) in bytes[..len].iter().enumerate() {
+        out[len - 1 - i] = *byte;
+    }
+
+    let order_bits = 256 - C::ORDER[3].leading_zeros() as usize;
+    let shift = (8 * len).saturating_sub(order_bits);
+    if shift > 0 {
+        for i in 0..out.len() {
+            let next = out.get(i + 1).copied().unwrap_or(0);
+            out[i] = out[i] >> shift | next << (8 - shift);
+        }
+    }
+    out
+}
+
+// Decodes a big-endian scalar of the SEC1 length, which must not be zero.
+pub(crate) fn decode_scalar<C: EcdsaCurve>(bytes: &[u8]) -> Result<ModScalar<C>, Error> {
+    if bytes.len() != scalar_len::<C>() {
+        return Err(Error::InvalidLength {
+            expected: scalar_len::<C>(),
+            actual: bytes.len(),
+        });
+    }
+    let mut le = [0u8; 30];
+    for (out, byte) in le.iter_mut().zip(bytes.iter().rev()) {
+        *out = *byte;
+    }
+
+    let scalar = ModScalar::from_bytes_non_zero(&le);
+    le.zeroize();
+    scalar
+}
+
+// Encodes a scalar as big-endian bytes of the SEC1 length.
+pub(crate) fn encode_scalar<C: EcdsaCurve>(scalar: &ModScalar<C>, out: &mut [u8]) {
+    debug_assert_eq!(out.len(), scalar_len::<C>());
+    let mut le = scalar.to_bytes();
+    for (out, byte) in out.iter_mut().rev().zip(le.iter()) {
+        *out = *byte;
+    }
+    le.zeroize();
+}
+
+fn hmac<D: Digest + BlockSizeUser>(key: &[u8], parts: &[&[u8]]) -> Output<D> {
+    let mut mac =
+        <SimpleHmac<D> as KeyInit>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes()
+}
+
+// The HMAC_DRBG of RFC 6979, section 3.2, which generates the nonce candidates from
+// the encoded secret key and the encoded hash of the message. The extra input of
+// section 3.6 is empty for deterministic signatures.
+struct NonceGenerator<D: Digest + BlockSizeUser> {
+    k: Output<D>,
+    v: Output<D>,
+}
+
+impl<D: Digest + BlockSizeUser> NonceGenerator<D> {
+    fn new(secret: &[u8], hash: &[u8], extra: &[u8]) -> Self {
+        let mut k = Output::<D>::default();
+        let mut v = Output::<D>::default();
+        v.iter_mut().for_each(|byte| *byte = 0x01);
+
+        k = hmac::<D>(&k, &[&v, &[0x00], secret, hash, extra]);
+        v = hmac::<D>(&k, &[&v]);
+        k = hmac::<D>(&k, &[&v, &[0x01], secret, hash, extra]);
+        v = hmac::<D>(&k, &[&v]);
+        NonceGenerator { k, v }
+    }
+
+    // Fills out with the next candidate T.
+    fn fill(&mut self, out: &mut [u8]) {
+        for chunk in out.chunks_mut(self.v.len()) {
+            self.v = hmac::<D>(&self.k, &[&self.v]);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+    }
+
+    // Updates the state after a candidate was rejected.
+    fn reseed(&mut self) {
+        self.k = hmac::<D>(&self.k, &[&self.v, &[0x00]]);
+        self.v = hmac::<D>(&self.k, &[&self.v]);
+    }
+}
+
+impl<D: Digest + BlockSizeUser> Drop for NonceGenerator<D> {
+    fn drop(&mut self) {
+        self.k.as_mut_slice().zeroize();
+        self.v.as_mut_slice().zeroize();
+    }
+}
+
+/// A secret key for signing messages.
+#[derive(Clone)]
+pub struct SigningKey<C: EcdsaCurve> {
+    secret: ModScalar<C>,
+    verifying_key: VerifyingKey<C>,
+}
+
+/// A public key for verifying signatures, which is never the neutral.
+#[derive(Clone, Copy, Debug)]
+pub struct VerifyingKey<C: EcdsaCurve> {
+    point: C::Point,
+}
+
+/// A signature, the pair of non-zero scalars `(r, s)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature<C: EcdsaCurve> {
+    r: ModScalar<C>,
+    s: ModScalar<C>,
+}
+
+// the length of a DER encoded signature, a sequence of two integers of up to 31
+// bytes, all with short-form lengths
+const MAX_DER_LEN: usize = 2 + 2 * (2 + 31);
+
+/// A DER encoded signature, i.e. the `ECDSA-Sig-Value` of SEC1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DerSignature {
+    bytes: [u8; MAX_DER_LEN],
+    len: usize,
+}
+
+impl<C: EcdsaCurve> SigningKey<C> {
+    pub fn random_from_rng(rng: &mut impl CryptoRngCore) -> Self {
+        Self::from_scalar(ModScalar::random(rng))
+    }
+
+    /// Decodes a secret key from its big-endian SEC1 encoding of
+    /// [`EcdsaCurve::ScalarSize`] bytes. Zero is rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        decode_scalar(bytes).map(Self::from_scalar)
+    }
+
+    pub(crate) fn from_scalar(secret: ModScalar<C>) -> Self {
+        let mut scalar = secret.to_scalar();
+        let point = C::Point::mulgen(&scalar);
+        scalar.zeroize();
+        SigningKey {
+            secret,
+            verifying_key: VerifyingKey { point },
+        }
+    }
+
+    pub fn to_bytes(&self) -> ScalarBytes<C> {
+        let mut out = ScalarBytes::<C>::default();
+        encode_scalar(&self.secret, &mut out);
+        out
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey<C> {
+        &self.verifying_key
+    }
+
+    #[cfg(feature = "pkcs8")]
+    pub(crate) fn secret(&self) -> &ModScalar<C> {
+        &self.secret
+    }
+
+    /// Signs the SHA-256 hash of the message.
+    pub fn sign(&self, msg: &[u8]) -> Signature<C> {
+        self.sign_digest(Sha256::new_with_prefix(msg))
+    }
+
+    /// Signs the hash of the message in `digest`. The nonce is derived with HMAC
+    /// over the same hash function.
+    pub fn sign_digest<D: Digest + BlockSizeUser>(&self, digest: D) -> Signature<C> {
+        self.sign_prehash::<D>(&digest.finalize(), &[])
+    }
+
+    /// Signs the SHA-256 hash of the message, with fresh randomness mixed into the
+    /// nonce as in section 3.6 of RFC 6979. The signatures are not deterministic,
+    /// but the nonces stay secret even if the randomness is bad.
+    pub fn sign_with_rng(&self, rng: &mut impl CryptoRngCore, msg: &[u8]) -> Signature<C> {
+        let mut extra = [0u8; 32];
+        rng.fill_bytes(&mut extra);
+        let signature = self.sign_prehash::<Sha256>(&Sha256::digest(msg), &extra);
+        extra.zeroize();
+        signature
+    }
+
+    fn sign_prehash<D: Digest + BlockSizeUser>(
+        &self,
+        prehash: &[u8],
+        extra: &[u8],
+    ) -> Signature<C> {
+        let len = scalar_len::<C>();
+        let e = ModScalar::<C>::from_bytes_mod_order(&bits2int::<C>(prehash));
+
+        // the generator is seeded with int2octets(x) and bits2octets(h)
+        let mut secret_bytes = [0u8; 30];
+        encode_scalar(&self.secret, &mut secret_bytes[..len]);
+        let mut hash_bytes = [0u8; 30];
+        encode_scalar(&e, &mut hash_bytes[..len]);
+        let mut generator =
+            NonceGenerator::<D>::new(&secret_bytes[..len], &hash_bytes[..len], extra);
+        secret_bytes.zeroize();
+
+        loop {
+            let mut candidate = [0u8; 30];
+            generator.fill(&mut candidate[..len]);
+            let mut k_bytes = bits2int::<C>(&candidate[..len]);
+            let k: Option<ModScalar<C>> = ModScalar::from_canonical_bytes(&k_bytes).into();
+            candidate.zeroize();
+            k_bytes.zeroize();
+
+            if let Some(mut k) = k {
+                let signature = self.sign_with_nonce(&k, &e);
+                k.zeroize();
+                if let Some(signature) = signature {
+                    return signature;
+                }
+            }
+            generator.reseed();
+        }
+    }
+
+    // Returns None if the nonce is zero, or r or s are.
+    fn sign_with_nonce(&self, k: &ModScalar<C>, e: &ModScalar<C>) -> Option<Signature<C>> {
+        if k.is_zero().into() {
+            return None;
+        }
+        let mut k_scalar = k.to_scalar();
+        let point = C::Point::mulgen(&k_scalar);
+        k_scalar.zeroize();
+
+        let repr = point.to_encoded();
+        let r = ModScalar::from_bytes_mod_order(&C::x_coordinate(&repr));
+        let s = k.invert() * (*e + r * self.secret);
+        Signature::from_scalars(r, s).ok()
+    }
+}
+
+impl<C: EcdsaCurve> VerifyingKey<C> {
+    /// Decodes a compressed or uncompressed SEC1 point encoding. Points that are not
+    /// on the curve or not in the subgroup of prime order are rejected, as is the
+    /// point at infinity.
+    pub fn from_sec1_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let repr = C::decode_sec1(bytes)?;
+        let point = C::Point::try_decode_non_neutral(&repr)?;
+        Ok(VerifyingKey { point })
+    }
+
+    /// Wraps a point of the xs233 group, which must not be the neutral.
+    pub fn from_point(point: &C::Point) -> Result<Self, Error> {
+        if point.is_neutral().into() {
+            return Err(Error::IdentityPoint);
+        }
+        Ok(VerifyingKey { point: *point })
+    }
+
+    pub fn as_point(&self) -> &C::Point {
+        &self.point
+    }
+
+    /// Returns the uncompressed SEC1 encoding `0x04 || x || y`.
+    pub fn to_sec1_uncompressed(&self) -> [u8; 61] {
+        C::encode_sec1_uncompressed(&self.encode())
+    }
+
+    /// Returns the compressed SEC1 encoding, `0x02 || x` or `0x03 || x`.
+    pub fn to_sec1_compressed(&self) -> [u8; 31] {
+        C::encode_sec1_compressed(&self.encode())
+    }
+
+    fn encode(&self) -> [u8; 30] {
+        self.point.to_encoded()
+    }
+
+    /// Verifies a signature of the SHA-256 hash of the message.
+    pub fn verify(&self, msg: &[u8], signature: &Signature<C>) -> Result<(), Error> {
+        self.verify_prehash(&Sha256::digest(msg), signature)
+    }
+
+    /// Verifies a signature of the hash of the message in `digest`.
+    pub fn verify_digest<D: Digest>(
+        &self,
+        digest: D,
+        signature: &Signature<C>,
+    ) -> Result<(), Error> {
+        self.verify_prehash(&digest.finalize(), signature)
+    }
+
+    /// Verifies a signature of an already computed hash.
+    pub fn verify_prehash(&self, prehash: &[u8], signature: &Signature<C>) -> Result<(), Error> {
+        let e = ModScalar::<C>::from_bytes_mod_order(&bits2int::<C>(prehash));
+        let w = signature.s.invert();
+
+        // R = (e/s)*G + (r/s)*Q, which must not be the neutral
+        let mut point = C::Point::default();
+        point.mul(&self.point, &(signature.r * w).to_scalar());
+        point.add_assign(&C::Point::mulgen(&(e * w).to_scalar()));
+        if point.is_neutral().into() {
+            return Err(Error::VerificationFailed);
+        }
+
+        let repr = point.to_encoded();
+        if ModScalar::<C>::from_bytes_mod_order(&C::x_coordinate(&repr)) == signature.r {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
+
+impl<C: EcdsaCurve> Signature<C> {
+    /// Builds a signature from its scalars, which must not be zero.
+    pub fn from_scalars(r: ModScalar<C>, s: ModScalar<C>) -> Result<Self, Error> {
+        if bool::from(r.is_zero() | s.is_zero()) {
+            return Err(Error::ZeroScalar);
+        }
+        Ok(Signature { r, s })
+    }
+
+    pub fn r(&self) -> &ModScalar<C> {
+        &self.r
+    }
+
+    pub fn s(&self) -> &ModScalar<C> {
+        &self.s
+    }
+
+    /// Decodes the fixed-width encoding `r || s`, with both scalars in big-endian.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        let len = scalar_len::<C>();
+        if bytes.len() != 2 * len {
+            return Err(Error::InvalidLength {
+                expected: 2 * len,
+                actual: bytes.len(),
+            });
+        }
+        let (r, s) = bytes.split_at(len);
+        Ok(Signature {
+            r: decode_scalar(r)?,
+            s: decode_scalar(s)?,
+        })
+    }
+
+    /// Returns the fixed-width encoding `r || s`.
+    pub fn to_bytes(&self) -> SignatureBytes<C> {
+        let len = scalar_len::<C>();
+        let mut out = SignatureBytes::<C>::default();
+        let (r, s) = out.split_at_mut(len);
+        encode_scalar(&self.r, r);
+        encode_scalar(&self.s, s);
+        out
+    }
+
+    /// Decodes a DER encoded `ECDSA-Sig-Value`. Only the unique DER encoding is
+    /// accepted.
+    pub fn from_der(bytes: &[u8]) -> Result<Self, Error> {
+        let body = match bytes {
+            [0x30, len, body @ ..] if *len < 0x80 && *len as usize == body.len() => body,
+            _ => return Err(Error::InvalidDer),
+        };
+        let (r, rest) = read_der_integer(body)?;
+        let (s, rest) = read_der_integer(rest)?;
+        if !rest.is_empty() {
+            return Err(Error::InvalidDer);
+        }
+        Ok(Signature {
+            r: decode_der_scalar(r)?,
+            s: decode_der_scalar(s)?,
+        })
+    }
+
+    /// Returns the DER encoded `ECDSA-Sig-Value`.
+    pub fn to_der(&self) -> DerSignature {
+        let mut bytes = [0u8; MAX_DER_LEN];
+        let r_len = write_der_integer(&mut bytes[2..], &self.r.to_bytes());
+        let s_len = write_der_integer(&mut bytes[2 + r_len..], &self.s.to_bytes());
+        bytes[0] = 0x30;
+        bytes[1] = (r_len + s_len) as u8;
+        DerSignature {
+            bytes,
+            len: 2 + r_len + s_len,
+        }
+    }
+}
+
+// Splits a DER INTEGER from the input, and returns its big-endian value without the
+// sign byte. Negative and non-minimal encodings are rejected.
+fn read_der_integer(input: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    let (value, rest) = match input {
+        [0x02, len, rest @ ..] if *len < 0x80 && *len as usize <= rest.len() => {
+            rest.split_at(*len as usize)
+        }
+        _ => return Err(Error::InvalidDer),
+    };
+    match value {
+        [] => Err(Error::InvalidDer),
+        [first, ..] if first & 0x80 != 0 => Err(Error::InvalidDer),
+        [0, second, ..] if second & 0x80 == 0 => Err(Error::InvalidDer),
+        [0, value @ ..] if !value.is_empty() => Ok((value, rest)),
+        _ => Ok((value, rest)),
+    }
+}
+
+fn decode_der_scalar<C: EcdsaCurve>(value: &[u8]) -> Result<ModScalar<C>, Error> {
+    let len = scalar_len::<C>();
+    if value.len() > len {
+        return Err(Error::NonCanonicalScalar);
+    }
+    let mut padded = [0u8; 30];
+    padded[len - value.len()..len].copy_from_slice(value);
+    decode_scalar(&padded[..len])
+}
+
+// Writes the little-endian integer as a DER INTEGER, and returns its length.
+fn write_der_integer(out: &mut [u8], le: &[u8; 30]) -> usize {
+    let len = le.iter().rposition(|byte| *byte != 0).map_or(1, |i| i + 1);
+    let pad = le[len - 1] & 0x80 != 0;
+    let value_len = len + pad as usize;
+
+    out[0] = 0x02;
+    out[1] = value_len as u8;
+    for (out, byte) in out[2 + pad as usize..2 + value_len]
+        .iter_mut()
+        .zip(le[..len].iter().rev())
+    {
+        *out = *byte;
+    }
+    2 + value_len
+}
+
+impl DerSignature {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl AsRef<[u8]> for DerSignature {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<C: EcdsaCurve> TryFrom<&[u8]> for Signature<C> {
+    type Error = Error;
+
+    /// Decodes the fixed-width encoding, like [`Signature::from_slice`].
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_slice(bytes)
+    }
+}
+
+impl<C: EcdsaCurve> PartialEq for VerifyingKey<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.point.ct_eq(&other.point).into()
+    }
+}
+
+impl<C: EcdsaCurve> Eq for VerifyingKey<C> {}
+
+#[cfg(feature = "signature")]
+impl<C: EcdsaCurve> signature::Signer<Signature<C>> for SigningKey<C> {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature<C>, signature::Error> {
+        Ok(self.sign(msg))
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: EcdsaCurve> signature::RandomizedSigner<Signature<C>> for SigningKey<C> {
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> Result<Signature<C>, signature::Error> {
+        Ok(self.sign_with_rng(rng, msg))
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: EcdsaCurve, D: Digest + BlockSizeUser> signature::DigestSigner<D, Signature<C>>
+    for SigningKey<C>
+{
+    fn try_sign_digest(&self, digest: D) -> Result<Signature<C>, signature::Error> {
+        Ok(self.sign_digest(digest))
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: EcdsaCurve> signature::Keypair for SigningKey<C> {
+    type VerifyingKey = VerifyingKey<C>;
+
+    fn verifying_key(&self) -> VerifyingKey<C> {
+        self.verifying_key
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: EcdsaCurve> signature::Verifier<Signature<C>> for VerifyingKey<C> {
+    fn verify(&self, msg: &[u8], signature: &Signature<C>) -> Result<(), signature::Error> {
+        Ok(VerifyingKey::verify(self, msg, signature)?)
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: EcdsaCurve, D: Digest> signature::DigestVerifier<D, Signature<C>> for VerifyingKey<C> {
+    fn verify_digest(&self, digest: D, signature: &Signature<C>) -> Result<(), signature::Error> {
+        Ok(VerifyingKey::verify_digest(self, digest, signature)?)
+    }
+}
+
+// the encoding of the trait is the fixed-width one
+#[cfg(feature = "signature")]
+impl<C: EcdsaCurve> signature::SignatureEncoding for Signature<C> {
+    type Repr = SignatureBytes<C>;
+}
+
+impl<C: EcdsaCurve> From<Signature<C>> for SignatureBytes<C> {
+    fn from(signature: Signature<C>) -> Self {
+        signature.to_bytes()
+    }
+}
+
+impl<C: EcdsaCurve> Drop for SigningKey<C> {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl<C: EcdsaCurve> ZeroizeOnDrop for SigningKey<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use sha1::Sha1;
+    use sha2::{Sha224, Sha512};
+
+    struct Vectors {
+        secret: &'static str,
+        public: &'static str,
+        public_compressed: &'static str,
+        // (message, hash, r, s)
+        signatures: &'static [(&'static str, &'static str, &'static str, &'static str)],
+        // the DER encoding of the first signature
+        der: &'static str,
+        // the sum of the generator and the point of order 2, which is on the curve
+        // but not in the subgroup
+        not_in_subgroup: &'static str,
+    }
+
+    // generated with OpenSSL 3.5, with deterministic nonces (nonce-type:1)
+    const K233: Vectors = Vectors {
+        secret: "001c0ffee0ddba11c0ffee0ddba11c0ffee0ddba11c0ffee0ddba11c0f",
+        public_compressed: "0201a531e4aa52ab6e6162fdad45ee23e95e7892cecefe6f358a6f035ba8a1",
+        public: "0401a531e4aa52ab6e6162fdad45ee23e95e7892cecefe6f358a6f035ba8a1003525b9b1ed2ea3526b6c211ed57f15c68d420ea72ece9fd4c6dd64f69b",
+        signatures: &[
+            (
+                "sample",
+                "sha1",
+                "1890113b154202e427b2dc86dee5351b324b0c3f047b24f9b821134ea8",
+                "0de972a203e80d5851bf799737d5fb053f56b55a04d55b10d444c22f4b",
+            ),
+            (
+                "sample",
+                "sha224",
+                "7b183bee03d86070e7393b80145cb9e79d988fae158df44e1deee87362",
+                "2c976c7e9774aa488b0f0f8b24f60d3bf206ce554b04ba0057cb6eb26e",
+            ),
+            (
+                "sample",
+                "sha256",
+                "4f0e4216500168a025b1f79869d1ed80963357b0bc3a3be97efa0948d8",
+                "36f57b688c0723bc52aac259223a2fd30a353b2cf476e99ca367aa970b",
+            ),
+            (
+                "sample",
+                "sha512",
+                "3e6639a5b5cb2eb0912f06f00076dc2551283729f0761bfcc930134ae6",
+                "1a6d86f14f22bf66faac6c3ba0fd4342cdd15d7859ab35006bbc27cbe3",
+            ),
+            (
+                "test",
+                "sha1",
+                "20bd0edcab81ee2f0801c9c76ded34af6f12b38480d48ea1c439877d4f",
+                "29fb026a2d1b327c69a772ec6d14184a8de57926089e8cdadbef80299a",
+            ),
+            (
+                "test",
+                "sha224",
+                "37b3099508d6b0570ce9854dff0c67549fdc6a31c41e91d544d6940133",
+                "332c356fa52afe6b7bd9d15c8ec5653e692fbc30be1ec1ec0de2b2d700",
+            ),
+            (
+                "test",
+                "sha256",
+                "6efa359b6fd495bf9ab2e6ca8bd4b4555a6331d3a7ebc0375840ad6c5d",
+                "7ac28714eec25680f32428489e7e34b7ab4750baa57f055198d04395be",
+            ),
+            (
+                "test",
+                "sha512",
+                "147ba319bdcd74eb9db5879cda00dbf1c07ca8c045c7e5cf8c2916fc00",
+                "7982d90134d09a66a9e25b3f0f4191ac42491f997b95ad96a3e3275a49",
+            ),
+        ],
+        der: "303e021d1890113b154202e427b2dc86dee5351b324b0c3f047b24f9b821134ea8021d0de972a203e80d5851bf799737d5fb053f56b55a04d55b10d444c22f4b",
+        not_in_subgroup: "0401ecb92776d0fb3dec476585b9065724ef7e1966bf54a850e5cbddaa1be6005729c6f23af8c1f9ea10ab046c84751b242f8f83706f4f457f2825505e",
+    };
+
+    const B233: Vectors = Vectors {
+        secret: "00e6b5d2a9c8f1e07a3b2c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f",
+        public_compressed: "0200d7b01b45a8a3971f91999b06cb919b6fdd85026356b4e4967bf8094d30",
+        public: "0400d7b01b45a8a3971f91999b06cb919b6fdd85026356b4e4967bf8094d30014bf28e5bbf8f9e6169816a6f1af22052072b9c3473317098bb8eafcfd1",
+        signatures: &[
+            (
+                "sample",
+                "sha1",
+                "00f1dcac10cc2b1272d75b6bd9a3efb3c3b4c3b6a879db222a725f7cd14b",
+                "00ab4b7ca8c9afb5565c320c9ac81e0b6af2be9ffb34d2d1033cd5950e29",
+            ),
+            (
+                "sample",
+                "sha224",
+                "002c3f061a3c71d6c0c296d148882bddd3f43e0070bde0938ae42d489d6d",
+                "00a2216a4910105741eb5446fadb074af584d0b62d3e5228273b058129c7",
+            ),
+            (
+                "sample",
+                "sha256",
+                "00775dae89a1dbac4ae6c8ead282e5483970942d82db6c43ba48dfec97ae",
+                "00b180f4ae9cec6587c68978a1dd3c9986ec87e9daa10dbfbca348432dae",
+            ),
+            (
+                "sample",
+                "sha512",
+                "0055f38b5c65ada51565be6becf697713f716a3e6f3f92a21b4049aab5ad",
+                "004f5c881a7b96b8594cd13aa1171cf5fb71d0e742cda13422df56cd4165",
+            ),
+            (
+                "test",
+                "sha1",
+                "001bc7530b5470323d1086e09bf6e2eaaf4c08f4602ef9938d0e9247ac94",
+                "00c17e98cf2709be2d4f29bbf0d7299c50a206be0ff079c7910779ad5419",
+            ),
+            (
+                "test",
+                "sha224",
+                "000f4e9387444817610d916b42af4d4c160350ef40d18cdad2e46153f68a",
+                "0039c59f28320f4e6a7b5ef80690e06ed6f2ac556ccf5a3061dd3673405e",
+            ),
+            (
+                "test",
+                "sha256",
+                "007768b04ae7d3ddfadf88f16f1f918d63f96ab2620956ef94e5de4cc3be",
+                "00d56d3d8224e1fe4842335066fad77bec486e19b6ce9dbb7013e2e8377e",
+            ),
+            (
+                "test",
+                "sha512",
+                "006cfc463ce793f43bb37060fdafadc80f834a1790fc34202a7ec9cca3fb",
+                "00621029383c2927b2f582829c36886a60046315a7f56c06dfb417707d25",
+            ),
+        ],
+        der: "3040021e00f1dcac10cc2b1272d75b6bd9a3efb3c3b4c3b6a879db222a725f7cd14b021e00ab4b7ca8c9afb5565c320c9ac81e0b6af2be9ffb34d2d1033cd5950e29",
+        not_in_subgroup: "0400bde52fa1a68362c1dd44817101102d9bd872c6997f6afbecf72b5bbe2800aea0853a1f48246e026286b1e652cd9573e370a242848a7eab53895919",
+    };
+
+    fn sign_with<C: EcdsaCurve>(key: &SigningKey<C>, hash: &str, msg: &[u8]) -> Signature<C> {
+        match hash {
+            "sha1" => key.sign_digest(Sha1::new_with_prefix(msg)),
+            "sha224" => key.sign_digest(Sha224::new_with_prefix(msg)),
+            "sha256" => key.sign_digest(Sha256::new_with_prefix(msg)),
+            "sha512" => key.sign_digest(Sha512::new_with_prefix(msg)),
+            _ => unreachable!(),
+        }
+    }
+
+    fn verify_with<C: EcdsaCurve>(
+        key: &VerifyingKey<C>,
+        hash: &str,
+        msg: &[u8],
+        signature: &Signature<C>,
+    ) -> Result<(), Error> {
+        match hash {
+            "sha1" => key.verify_digest(Sha1::new_with_prefix(msg), signature),
+            "sha224" => key.verify_digest(Sha224::new_with_prefix(msg), signature),
+            "sha256" => key.verify_digest(Sha256::new_with_prefix(msg), signature),
+            "sha512" => key.verify_digest(Sha512::new_with_prefix(msg), signature),
+            _ => unreachable!(),
+        }
+    }
+
+    fn check_vectors<C: EcdsaCurve>(vectors: &Vectors) {
+        let key = SigningKey::<C>::from_bytes(&hex::decode(vectors.secret).unwrap()).unwrap();
+        assert_eq!(hex::encode(key.to_bytes()), vectors.secret);
+
+        let public = VerifyingKey::<C>::from_sec1_bytes(&hex::decode(vectors.public).unwrap());
+        assert_eq!(public, Ok(*key.verifying_key()));
+        assert_eq!(
+            hex::encode(key.verifying_key().to_sec1_uncompressed()),
+            vectors.public
+        );
+        let compressed = key.verifying_key().to_sec1_compressed();
+        assert_eq!(hex::encode(compressed), vectors.public_compressed);
+        assert_eq!(
+            VerifyingKey::<C>::from_sec1_bytes(&compressed),
+            Ok(*key.verifying_key())
+        );
+
+        for (msg, hash, r, s) in vectors.signatures {
+            let signature = sign_with(&key, hash, msg.as_bytes());
+            let expected = format!("{r}{s}");
+            assert_eq!(hex::encode(signature.to_bytes()), expected, "{msg} {hash}");
+
+            let decoded = Signature::<C>::from_slice(&hex::decode(&expected).unwrap()).unwrap();
+            assert_eq!(decoded, signature);
+            assert_eq!(
+                verify_with(key.verifying_key(), hash, msg.as_bytes(), &decoded),
+                Ok(())
+            );
+            assert_eq!(
+                verify_with(key.verifying_key(), hash, b"other", &decoded),
+                Err(Error::VerificationFailed)
+            );
+        }
+
+        let der = hex::decode(vectors.der).unwrap();
+        let signature = Signature::<C>::from_der(&der).unwrap();
+        assert_eq!(signature.to_der().as_bytes(), der);
+        let (_, hash, r, s) = vectors.signatures[0];
+        assert_eq!(hex::encode(signature.to_bytes()), format!("{r}{s}"));
+        assert_eq!(
+            verify_with(key.verifying_key(), hash, b"sample", &signature),
+            Ok(())
+        );
+
+        assert_eq!(
+            VerifyingKey::<C>::from_sec1_bytes(&hex::decode(vectors.not_in_subgroup).unwrap()),
+            Err(Error::InvalidEncoding)
+        );
+    }
+
+    fn check_sign_verify<C: EcdsaCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SigningKey::<C>::random_from_rng(&mut rng);
+        let public = *key.verifying_key();
+        let msg = b"xs233 ecdsa";
+
+        let signature = key.sign(msg);
+        assert_eq!(key.sign(msg), signature);
+        assert_eq!(public.verify(msg, &signature), Ok(()));
+        assert_eq!(
+            public.verify(b"xs233 ecdsA", &signature),
+            Err(Error::VerificationFailed)
+        );
+        let swapped = Signature::from_scalars(*signature.s(), *signature.r()).unwrap();
+        assert_eq!(public.verify(msg, &swapped), Err(Error::VerificationFailed));
+        assert_eq!(
+            SigningKey::<C>::from_bytes(&key.to_bytes()).map(|key| *key.verifying_key()),
+            Ok(public)
+        );
+    }
+
+    fn check_fixed_width_encoding<C: EcdsaCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SigningKey::<C>::random_from_rng(&mut rng);
+        let signature = key.sign(b"xs233 ecdsa");
+        let len = scalar_len::<C>();
+
+        // fixed-width encoding
+        let bytes = signature.to_bytes();
+        assert_eq!(bytes.len(), 2 * len);
+        assert_eq!(Signature::<C>::try_from(&bytes[..]), Ok(signature));
+        assert_eq!(
+            Signature::<C>::from_slice(&bytes[1..]),
+            Err(Error::InvalidLength {
+                expected: 2 * len,
+                actual: 2 * len - 1
+            })
+        );
+        let mut zero = bytes.clone();
+        zero[..len].fill(0);
+        assert_eq!(Signature::<C>::from_slice(&zero), Err(Error::ZeroScalar));
+        let mut large = bytes;
+        large[len..].fill(0xff);
+        assert_eq!(
+            Signature::<C>::from_slice(&large),
+            Err(Error::NonCanonicalScalar)
+        );
+    }
+
+    fn check_der_encoding<C: EcdsaCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SigningKey::<C>::random_from_rng(&mut rng);
+        let signature = key.sign(b"xs233 ecdsa");
+
+        // DER encoding
+        let der = signature.to_der();
+        assert_eq!(Signature::<C>::from_der(der.as_bytes()), Ok(signature));
+        let mut trailing = der.as_bytes().to_vec();
+        trailing.push(0);
+        assert_eq!(Signature::<C>::from_der(&trailing), Err(Error::InvalidDer));
+        let one = [0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01];
+        assert!(Signature::<C>::from_der(&one).is_ok());
+        let padded = [0x30, 0x07, 0x02, 0x02, 0x00, 0x01, 0x02, 0x01, 0x01];
+        assert_eq!(Signature::<C>::from_der(&padded), Err(Error::InvalidDer));
+        let negative = [0x30, 0x06, 0x02, 0x01, 0x81, 0x02, 0x01, 0x01];
+        assert_eq!(Signature::<C>::from_der(&negative), Err(Error::InvalidDer));
+        let zero = [0x30, 0x06, 0x02, 0x01, 0x00, 0x02, 0x01, 0x01];
+        assert_eq!(Signature::<C>::from_der(&zero), Err(Error::ZeroScalar));
+    }
+
+    fn check_sec1_encoding<C: EcdsaCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SigningKey::<C>::random_from_rng(&mut rng);
+        let public = *key.verifying_key();
+
+        // SEC1 point encodings
+        let uncompressed = public.to_sec1_uncompressed();
+        assert_eq!(
+            VerifyingKey::<C>::from_sec1_bytes(&uncompressed),
+            Ok(public)
+        );
+        let compressed = public.to_sec1_compressed();
+        assert_eq!(VerifyingKey::<C>::from_sec1_bytes(&compressed), Ok(public));
+        let mut flipped = compressed;
+        flipped[0] ^= 1;
+        let mut negated = C::Point::default();
+        negated.neg(public.as_point());
+        assert_eq!(
+            VerifyingKey::<C>::from_sec1_bytes(&flipped),
+            VerifyingKey::from_point(&negated)
+        );
+        assert_eq!(
+            VerifyingKey::<C>::from_sec1_bytes(&[0x00]),
+            Err(Error::IdentityPoint)
+        );
+        assert_eq!(
+            VerifyingKey::<C>::from_sec1_bytes(&uncompressed[..60]),
+            Err(Error::InvalidLength {
+                expected: 61,
+                actual: 60
+            })
+        );
+        let mut off_curve = uncompressed;
+        off_curve[60] ^= 1;
+        assert_eq!(
+            VerifyingKey::<C>::from_sec1_bytes(&off_curve),
+            Err(Error::InvalidEncoding)
+        );
+        let mut order_two = [0u8; 61];
+        order_two[0] = 0x04;
+        assert_eq!(
+            VerifyingKey::<C>::from_sec1_bytes(&order_two),
+            Err(Error::InvalidEncoding)
+        );
+        assert_eq!(
+            VerifyingKey::<C>::from_point(C::Point::neutral()),
+            Err(Error::IdentityPoint)
+        );
+    }
+
+    // uses the keys only through the traits of the signature crate
+    #[cfg(feature = "signature")]
+    fn check_signature_traits<C: EcdsaCurve>(seed: u8) {
+        use sha2::Sha384;
+        use signature::{
+            DigestSigner, DigestVerifier, Keypair, RandomizedSigner, SignatureEncoding, Signer,
+            Verifier,
+        };
+
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SigningKey::<C>::random_from_rng(&mut rng);
+        let public = Keypair::verifying_key(&key);
+        let msg = b"message";
+
+        let signature: Signature<C> = Signer::sign(&key, msg);
+        assert_eq!(signature, key.sign(msg));
+        assert!(Verifier::verify(&public, msg, &signature).is_ok());
+        assert!(Verifier::verify(&public, b"other", &signature).is_err());
+
+        // the signatures with extra entropy are fresh, and still valid
+        let first: Signature<C> = RandomizedSigner::sign_with_rng(&key, &mut rng, msg);
+        let second: Signature<C> = RandomizedSigner::sign_with_rng(&key, &mut rng, msg);
+        assert_ne!(first, second);
+        assert_ne!(first, signature);
+        assert!(Verifier::verify(&public, msg, &first).is_ok());
+        assert!(Verifier::verify(&public, msg, &second).is_ok());
+
+        let digest_signature: Signature<C> =
+            DigestSigner::sign_digest(&key, Sha384::new_with_prefix(msg));
+        assert_eq!(
+            digest_signature,
+            key.sign_digest(Sha384::new_with_prefix(msg))
+        );
+        assert!(DigestVerifier::verify_digest(
+            &public,
+            Sha384::new_with_prefix(msg),
+            &digest_signature
+        )
+        .is_ok());
+        assert!(DigestVerifier::verify_digest(
+            &public,
+            Sha256::new_with_prefix(msg),
+            &digest_signature
+        )
+        .is_err());
+
+        let bytes = SignatureEncoding::to_bytes(&signature);
+        assert_eq!(bytes, signature.to_bytes());
+        assert_eq!(Signature::<C>::try_from(&bytes[..]), Ok(signature));
+    }
+
+    #[test]
+    fn vectors() {
+        check_vectors::<Xsk233>(&K233);
+        check_vectors::<Xsb233>(&B233);
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn sign_verify() { check_sign_verify::<C>(seed) }
+        fn fixed_width_encoding() { check_fixed_width_encoding::<C>(seed) }
+        fn der_encoding() { check_der_encoding::<C>(seed) }
+        fn sec1_encoding() { check_sec1_encoding::<C>(seed) }
+        #[cfg(feature = "signature")]
+        fn signature_traits() { check_signature_traits::<C>(seed) }
+    }
+}
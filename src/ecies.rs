@@ -0,0 +1,441 @@
+//! Public key encryption of blobs and streams to a static point, in the style of
+//! ECIES.
+//!
+//! The sender generates an ephemeral secret, computes the shared point with
+//! [`Point::mul`], and derives a ChaCha20-Poly1305 key from its encoding with
+//! HKDF-SHA256, bound to the ephemeral and the recipient points. The recipient
+//! decrypts with the [`StaticSecret`] of the point.
+//!
+//! A ciphertext is the version byte [`VERSION`], the 30-byte encoding of the ephemeral
+//! point, a random 12-byte nonce, and the encrypted chunks of the plaintext. Each
+//! chunk is the ciphertext followed by the 16-byte tag. All chunks but the last hold
+//! exactly [`CHUNK_LEN`] bytes of plaintext, so a short message is a single chunk:
+//!
+//! ```text
+//! version (1) || ephemeral (30) || nonce (12) || ciphertext || tag (16)
+//! ```
+//!
+//! Like in the STREAM construction, the nonce of a chunk is the nonce of the header
+//! xor the big-endian index of the chunk and a flag for the last chunk, so chunks
+//! cannot be reordered, dropped or appended. [`encrypt`] and [`decrypt`] process
+//! whole messages, and [`Encryptor`] and [`Decryptor`] one chunk at a time, for
+//! large files.
+
+use crate::curve::Curve;
+use crate::ecdh::{PublicKey, StaticSecret};
+use crate::scalar::ModScalar;
+use crate::{Error, Point};
+use alloc::vec::Vec;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use rand_core::CryptoRngCore;
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+/// The version of the ciphertext format.
+pub const VERSION: u8 = 1;
+
+/// The length of the header: the version, the ephemeral point and the nonce.
+pub const HEADER_LEN: usize = 1 + 30 + NONCE_LEN;
+
+/// The length of the nonce in the header.
+pub const NONCE_LEN: usize = 12;
+
+/// The length of the tag of each chunk.
+pub const TAG_LEN: usize = 16;
+
+/// The length of the plaintext of all chunks but the last.
+pub const CHUNK_LEN: usize = 64 * 1024;
+
+const KDF_LABEL: &[u8] = b"xs233-ecies-v1";
+
+// The state shared by the encryptor and the decryptor: the cipher, the nonce from the
+// header, and the index of the next chunk.
+struct Stream {
+    cipher: ChaCha20Poly1305,
+    nonce: [u8; NONCE_LEN],
+    index: u32,
+}
+
+impl Stream {
+    fn new<C: Curve>(shared: &[u8; 30], header: &[u8; HEADER_LEN], recipient: &[u8; 30]) -> Self {
+        let mut key = Key::default();
+        Hkdf::<Sha256>::new(None, shared)
+            .expand_multi_info(
+                &[KDF_LABEL, C::NAME.as_bytes(), &header[1..31], recipient],
+                &mut key,
+            )
+            .expect("the key is shorter than the maximum");
+        let cipher = ChaCha20Poly1305::new(&key);
+        key.zeroize();
+
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&header[31..]);
+        Stream {
+            cipher,
+            nonce,
+            index: 0,
+        }
+    }
+
+    // The nonce of the next chunk. The last index is never used, so that incrementing
+    // it cannot overflow.
+    fn nonce(&self, last: bool) -> Result<Nonce, Error> {
+        if self.index == u32::MAX {
+            return Err(Error::MessageLimitReached);
+        }
+        let mut nonce = Nonce::from(self.nonce);
+        for (n, i) in nonce[7..11].iter_mut().zip(self.index.to_be_bytes()) {
+            *n ^= i;
+        }
+        nonce[11] ^= u8::from(last);
+        Ok(nonce)
+    }
+
+    fn encrypt(&mut self, chunk: &[u8], last: bool) -> Result<Vec<u8>, Error> {
+        let nonce = self.nonce(last)?;
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, chunk)
+            .map_err(|_| Error::MessageLimitReached)?;
+        self.index += 1;
+        Ok(ciphertext)
+    }
+
+    fn decrypt(&mut self, chunk: &[u8], last: bool) -> Result<Vec<u8>, Error> {
+        let nonce = self.nonce(last)?;
+        let plaintext = self
+            .cipher
+            .decrypt(&nonce, chunk)
+            .map_err(|_| Error::DecryptionFailed)?;
+        self.index += 1;
+        Ok(plaintext)
+    }
+}
+
+/// Encrypts a message chunk by chunk.
+pub struct Encryptor(Stream);
+
+/// Decrypts a message chunk by chunk.
+pub struct Decryptor(Stream);
+
+impl Encryptor {
+    /// Starts a message to `recipient`, and returns the encryptor with the header that
+    /// precedes the chunks.
+    ///
+    /// Fails with [`Error::IdentityPoint`] if `recipient` is the neutral.
+    pub fn new<C: Curve>(
+        rng: &mut impl CryptoRngCore,
+        recipient: &C::Point,
+    ) -> Result<(Self, [u8; HEADER_LEN]), Error> {
+        if recipient.is_neutral().into() {
+            return Err(Error::IdentityPoint);
+        }
+
+        let mut header = [0u8; HEADER_LEN];
+        header[0] = VERSION;
+        rng.fill_bytes(&mut header[31..]);
+
+        let mut secret = ModScalar::<C>::random(rng).to_scalar();
+        let ephemeral = C::Point::mulgen(&secret);
+        let mut shared = C::Point::default();
+        shared.mul(recipient, &secret);
+        secret.zeroize();
+
+        let mut shared_bytes = shared.to_encoded();
+        header[1..31].copy_from_slice(&ephemeral.to_encoded());
+
+        let stream = Stream::new::<C>(&shared_bytes, &header, &recipient.to_encoded());
+        shared_bytes.zeroize();
+        Ok((Encryptor(stream), header))
+    }
+
+    /// Encrypts a chunk that is not the last one, which must be [`CHUNK_LEN`] bytes
+    /// long.
+    pub fn encrypt_next(&mut self, chunk: &[u8]) -> Result<Vec<u8>, Error> {
+        if chunk.len() != CHUNK_LEN {
+            return Err(Error::InvalidLength {
+                expected: CHUNK_LEN,
+                actual: chunk.len(),
+            });
+        }
+        self.0.encrypt(chunk, false)
+    }
+
+    /// Encrypts the last chunk, which may be shorter than [`CHUNK_LEN`] and empty.
+    pub fn encrypt_last(mut self, chunk: &[u8]) -> Result<Vec<u8>, Error> {
+        if chunk.len() > CHUNK_LEN {
+            return Err(Error::InvalidLength {
+                expected: CHUNK_LEN,
+                actual: chunk.len(),
+            });
+        }
+        self.0.encrypt(chunk, true)
+    }
+}
+
+impl Decryptor {
+    /// Starts decrypting a message with its `header`.
+    ///
+    /// Fails with [`Error::UnsupportedVersion`] for other versions of the format, and
+    /// with [`Error::InvalidEncoding`] or [`Error::IdentityPoint`] if the ephemeral
+    /// point is invalid.
+    pub fn new<C: Curve>(
+        secret: &StaticSecret<C>,
+        header: &[u8; HEADER_LEN],
+    ) -> Result<Self, Error> {
+        if header[0] != VERSION {
+            return Err(Error::UnsupportedVersion { version: header[0] });
+        }
+        let ephemeral = PublicKey::<C>::try_from(&header[1..31])?;
+        let shared = secret.diffie_hellman(&ephemeral);
+        let recipient = PublicKey::from(secret).to_bytes();
+        Ok(Decryptor(Stream::new::<C>(
+            shared.as_bytes(),
+            header,
+            &recipient,
+        )))
+    }
+
+    /// Decrypts a chunk that is not the last one, which must be [`CHUNK_LEN`] plus
+    /// [`TAG_LEN`] bytes long.
+    ///
+    /// Fails with [`Error::DecryptionFailed`] if the chunk was modified, or is not the
+    /// next chunk of the message.
+    pub fn decrypt_next(&mut self, chunk: &[u8]) -> Result<Vec<u8>, Error> {
+        if chunk.len() != CHUNK_LEN + TAG_LEN {
+            return Err(Error::InvalidLength {
+                expected: CHUNK_LEN + TAG_LEN,
+                actual: chunk.len(),
+            });
+        }
+        self.0.decrypt(chunk, false)
+    }
+
+    /// Decrypts the last chunk. Fails with [`Error::DecryptionFailed`] if the chunk
+    /// was modified, or is not the last chunk of the message, so a truncated message
+    /// is detected.
+    pub fn decrypt_last(mut self, chunk: &[u8]) -> Result<Vec<u8>, Error> {
+        if chunk.len() > CHUNK_LEN + TAG_LEN {
+            return Err(Error::InvalidLength {
+                expected: CHUNK_LEN + TAG_LEN,
+                actual: chunk.len(),
+            });
+        }
+        self.0.decrypt(chunk, true)
+    }
+}
+
+/// Encrypts `plaintext` to `recipient`.
+pub fn encrypt<C: Curve>(
+    rng: &mut impl CryptoRngCore,
+    recipient: &C::Point,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let (mut encryptor, header) = Encryptor::new::<C>(rng, recipient)?;
+    let chunks = plaintext.len() / CHUNK_LEN + 1;
+    let mut ciphertext = Vec::with_capacity(HEADER_LEN + plaintext.len() + chunks * TAG_LEN);
+    ciphertext.extend_from_slice(&header);
+
+    let mut rest = plaintext;
+    while rest.len() > CHUNK_LEN {
+        let (chunk, tail) = rest.split_at(CHUNK_LEN);
+        ciphertext.extend_from_slice(&encryptor.encrypt_next(chunk)?);
+        rest = tail;
+    }
+    ciphertext.extend_from_slice(&encryptor.encrypt_last(rest)?);
+    Ok(ciphertext)
+}
+
+/// Decrypts a message that was encrypted to the public key of `secret`.
+///
+/// Fails with [`Error::InvalidLength`] if the ciphertext is shorter than a header and
+/// a tag, and otherwise like [`Decryptor`].
+pub fn decrypt<C: Curve>(secret: &StaticSecret<C>, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+    if ciphertext.len() < HEADER_LEN + TAG_LEN {
+        return Err(Error::InvalidLength {
+            expected: HEADER_LEN + TAG_LEN,
+            actual: ciphertext.len(),
+        });
+    }
+    let (header, mut rest) = ciphertext.split_at(HEADER_LEN);
+    let mut decryptor = Decryptor::new(secret, header.try_into().unwrap())?;
+    let mut plaintext = Vec::with_capacity(rest.len());
+
+    while rest.len() > CHUNK_LEN + TAG_LEN {
+        let (chunk, tail) = rest.split_at(CHUNK_LEN + TAG_LEN);
+        plaintext.extend_from_slice(&decryptor.decrypt_next(chunk)?);
+        rest = tail;
+    }
+    plaintext.extend_from_slice(&decryptor.decrypt_last(rest)?);
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn check_roundtrip<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret = StaticSecret::<C>::random_from_rng(&mut rng);
+        let recipient = *PublicKey::from(&secret).as_point();
+
+        for len in [
+            0,
+            1,
+            CHUNK_LEN - 1,
+            CHUNK_LEN,
+            CHUNK_LEN + 1,
+            2 * CHUNK_LEN + 7,
+        ] {
+            let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let ciphertext = encrypt::<C>(&mut rng, &recipient, &plaintext).unwrap();
+            let chunks = len.div_ceil(CHUNK_LEN).max(1);
+            assert_eq!(ciphertext.len(), HEADER_LEN + len + chunks * TAG_LEN);
+            assert_eq!(ciphertext[0], VERSION);
+            assert_eq!(decrypt(&secret, &ciphertext).unwrap(), plaintext);
+
+            // the same message, one chunk at a time
+            let header: &[u8; HEADER_LEN] = ciphertext[..HEADER_LEN].try_into().unwrap();
+            let mut decryptor = Decryptor::new(&secret, header).unwrap();
+            let mut chunks = ciphertext[HEADER_LEN..]
+                .chunks(CHUNK_LEN + TAG_LEN)
+                .peekable();
+            let mut decrypted = Vec::new();
+            while let Some(chunk) = chunks.next() {
+                if chunks.peek().is_some() {
+                    decrypted.extend(decryptor.decrypt_next(chunk).unwrap());
+                } else {
+                    decrypted.extend(decryptor.decrypt_last(chunk).unwrap());
+                    break;
+                }
+            }
+            assert_eq!(decrypted, plaintext);
+        }
+
+        // a message that ends with an empty chunk
+        let (mut encryptor, header) = Encryptor::new::<C>(&mut rng, &recipient).unwrap();
+        let mut ciphertext = header.to_vec();
+        ciphertext.extend(encryptor.encrypt_next(&[7; CHUNK_LEN]).unwrap());
+        ciphertext.extend(encryptor.encrypt_last(&[]).unwrap());
+        assert_eq!(decrypt(&secret, &ciphertext).unwrap(), [7; CHUNK_LEN]);
+
+        // the ephemeral point is fresh, so equal messages have unrelated ciphertexts
+        let first = encrypt::<C>(&mut rng, &recipient, b"message").unwrap();
+        let second = encrypt::<C>(&mut rng, &recipient, b"message").unwrap();
+        assert_ne!(first[1..31], second[1..31]);
+        assert_ne!(first[HEADER_LEN..], second[HEADER_LEN..]);
+
+        // another recipient
+        let other = StaticSecret::<C>::random_from_rng(&mut rng);
+        assert_eq!(decrypt(&other, &first), Err(Error::DecryptionFailed));
+    }
+
+    fn check_tampering<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret = StaticSecret::<C>::random_from_rng(&mut rng);
+        let recipient = *PublicKey::from(&secret).as_point();
+
+        // every modified bit is detected
+        let ciphertext = encrypt::<C>(&mut rng, &recipient, b"a short message").unwrap();
+        for i in 0..ciphertext.len() {
+            let mut modified = ciphertext.clone();
+            modified[i] ^= 1;
+            let err = decrypt(&secret, &modified).unwrap_err();
+            if i == 0 {
+                assert_eq!(
+                    err,
+                    Error::UnsupportedVersion {
+                        version: VERSION ^ 1
+                    }
+                );
+            } else if i < 31 {
+                assert!(matches!(
+                    err,
+                    Error::DecryptionFailed | Error::InvalidEncoding
+                ));
+            } else {
+                assert_eq!(err, Error::DecryptionFailed);
+            }
+        }
+
+        // truncated, extended and reordered messages
+        let plaintext = [3u8; 2 * CHUNK_LEN + 1];
+        let ciphertext = encrypt::<C>(&mut rng, &recipient, &plaintext).unwrap();
+        let full = CHUNK_LEN + TAG_LEN;
+        let truncated = &ciphertext[..HEADER_LEN + 2 * full];
+        assert_eq!(decrypt(&secret, truncated), Err(Error::DecryptionFailed));
+        let mut extended = ciphertext.clone();
+        extended.extend_from_slice(&ciphertext[HEADER_LEN..HEADER_LEN + full]);
+        assert_eq!(decrypt(&secret, &extended), Err(Error::DecryptionFailed));
+        let mut reordered = ciphertext[..HEADER_LEN].to_vec();
+        reordered.extend_from_slice(&ciphertext[HEADER_LEN + full..HEADER_LEN + 2 * full]);
+        reordered.extend_from_slice(&ciphertext[HEADER_LEN..HEADER_LEN + full]);
+        reordered.extend_from_slice(&ciphertext[HEADER_LEN + 2 * full..]);
+        assert_eq!(decrypt(&secret, &reordered), Err(Error::DecryptionFailed));
+
+        // a last chunk that is shorter than a tag
+        let mut short = ciphertext[..HEADER_LEN + full].to_vec();
+        short.extend_from_slice(&[0; TAG_LEN - 1]);
+        assert_eq!(decrypt(&secret, &short), Err(Error::DecryptionFailed));
+
+        assert_eq!(
+            decrypt(&secret, &ciphertext[..HEADER_LEN + TAG_LEN - 1]),
+            Err(Error::InvalidLength {
+                expected: HEADER_LEN + TAG_LEN,
+                actual: HEADER_LEN + TAG_LEN - 1
+            })
+        );
+    }
+
+    fn check_invalid_points<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret = StaticSecret::<C>::random_from_rng(&mut rng);
+        let recipient = *PublicKey::from(&secret).as_point();
+        let ciphertext = encrypt::<C>(&mut rng, &recipient, b"message").unwrap();
+
+        // the neutral as the ephemeral point
+        let mut modified = ciphertext.clone();
+        modified[1..31].fill(0);
+        assert_eq!(decrypt(&secret, &modified), Err(Error::IdentityPoint));
+
+        // an invalid encoding of the ephemeral point
+        modified[1] = 1;
+        assert_eq!(decrypt(&secret, &modified), Err(Error::InvalidEncoding));
+
+        // the neutral as the recipient
+        assert!(matches!(
+            Encryptor::new::<C>(&mut rng, C::Point::neutral()),
+            Err(Error::IdentityPoint)
+        ));
+
+        // chunks of the wrong length
+        let (mut encryptor, header) = Encryptor::new::<C>(&mut rng, &recipient).unwrap();
+        assert_eq!(
+            encryptor.encrypt_next(&[0; CHUNK_LEN - 1]),
+            Err(Error::InvalidLength {
+                expected: CHUNK_LEN,
+                actual: CHUNK_LEN - 1
+            })
+        );
+        let mut decryptor = Decryptor::new(&secret, &header).unwrap();
+        assert_eq!(
+            decryptor.decrypt_next(&[0; TAG_LEN]),
+            Err(Error::InvalidLength {
+                expected: CHUNK_LEN + TAG_LEN,
+                actual: TAG_LEN
+            })
+        );
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn roundtrip() { check_roundtrip::<C>(seed) }
+        fn tampering() { check_tampering::<C>(seed) }
+        fn invalid_points() { check_invalid_points::<C>(seed) }
+    }
+}
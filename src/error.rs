@@ -0,0 +1,105 @@
+/// The error type of the fallible high-level APIs.
+///
+/// The constant-time primitives, such as [`Point::decode`](crate::Point::decode),
+/// report failure as a `subtle::Choice` instead. The fallible APIs branch on the
+/// result, so they only leak whether the operation succeeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The bytes are not a valid point encoding.
+    InvalidEncoding,
+    /// The bytes encode an integer that is not smaller than the group order.
+    NonCanonicalScalar,
+    /// The input does not have the expected length.
+    InvalidLength {
+        /// The expected length in bytes.
+        expected: usize,
+        /// The actual length in bytes.
+        actual: usize,
+    },
+    /// The scalar is zero, which is not allowed here, e.g. for secret keys.
+    ZeroScalar,
+    /// The point is the neutral element of the group, which is not allowed here.
+    IdentityPoint,
+    /// A signature or proof is invalid.
+    VerificationFailed,
+    /// The signature at `index` of a batch is invalid.
+    BatchVerificationFailed {
+        /// The index of the first invalid signature.
+        index: usize,
+    },
+    /// The bytes are not valid DER, or do not have the expected structure.
+    InvalidDer,
+    /// No point could be derived from the input.
+    MappingFailed,
+    /// A key object, such as a JWK, is malformed or holds a key of another type or
+    /// curve.
+    InvalidKey,
+    /// A ciphertext or its associated data was modified, or was encrypted with
+    /// another key.
+    DecryptionFailed,
+    /// An encryption context has used up its sequence numbers.
+    MessageLimitReached,
+    /// The key or the id of a pre-shared key is empty.
+    InvalidPsk,
+    /// The ciphertext has a format version that is not supported.
+    UnsupportedVersion {
+        /// The version byte of the ciphertext.
+        version: u8,
+    },
+    /// The string is not a hex encoding of the expected length.
+    InvalidHex,
+    /// The protocol cannot process the input, e.g. because it hashes to the neutral,
+    /// or because it is too long to be length-prefixed.
+    InvalidInput,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::InvalidEncoding => f.write_str("invalid point encoding"),
+            Error::NonCanonicalScalar => f.write_str("non-canonical scalar encoding"),
+            Error::InvalidLength { expected, actual } => {
+                write!(f, "invalid length: expected {expected} bytes, got {actual}")
+            }
+            Error::ZeroScalar => f.write_str("unexpected zero scalar"),
+            Error::IdentityPoint => f.write_str("unexpected identity point"),
+            Error::VerificationFailed => f.write_str("verification failed"),
+            Error::BatchVerificationFailed { index } => {
+                write!(f, "batch verification failed at index {index}")
+            }
+            Error::InvalidDer => f.write_str("invalid DER encoding"),
+            Error::MappingFailed => f.write_str("no point could be derived from the input"),
+            Error::InvalidKey => f.write_str("malformed or unsupported key"),
+            Error::DecryptionFailed => f.write_str("decryption failed"),
+            Error::MessageLimitReached => f.write_str("message limit reached"),
+            Error::InvalidPsk => f.write_str("invalid pre-shared key"),
+            Error::UnsupportedVersion { version } => {
+                write!(f, "unsupported ciphertext version {version}")
+            }
+            Error::InvalidHex => f.write_str("invalid hex encoding"),
+            Error::InvalidInput => f.write_str("invalid protocol input"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl From<crate::ParsePointError> for Error {
+    fn from(err: crate::ParsePointError) -> Self {
+        match err {
+            crate::ParsePointError::InvalidLength | crate::ParsePointError::InvalidHexDigit => {
+                Error::InvalidHex
+            }
+            crate::ParsePointError::InvalidEncoding => Error::InvalidEncoding,
+        }
+    }
+}
+
+#[cfg(feature = "signature")]
+impl From<Error> for signature::Error {
+    // the signature crate only carries the source with the std feature
+    fn from(_: Error) -> Self {
+        signature::Error::new()
+    }
+}
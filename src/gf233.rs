@@ -0,0 +1,1122 @@
+// Arithmetic in GF(2^233), with modulus z^233 + z^74 + 1. This is the field
+// underlying both xsk233 and xsb233. The implementation follows gf233.h from
+// c-xs233, but is written for portability rather than speed. All operations
+// are constant time.
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+// Field elements are stored as four little-endian 64-bit limbs and are always
+// fully reduced, i.e. the top 23 bits of the last limb are zero.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub(crate) struct Gf([u64; 4]);
+
+// sqrt(z) = z^32 + z^69 + z^117 + z^154 + z^191 + z^228. None of the powers
+// are multiples of 64.
+const SQRT_Z_POWERS: [usize; 6] = [32, 69, 117, 154, 191, 228];
+
+// H(z^(2*k + 1)) for k = 0 to 115, for Gf::halftrace. This is the table of
+// the portable implementation in c-xs233.
+static HALFTRACE: [Gf; 116] = [
+    Gf::from_be([
+        0x00000197FD722105,
+        0x5AABAEF4E0DC1096,
+        0x20B7FC13C12E8D69,
+        0xFD01D0208E170521,
+    ]),
+    Gf::from_be([
+        0x0000019395A41BE5,
+        0x109128B2E0851116,
+        0x9795725B45048071,
+        0x5C8585D4DA648BAD,
+    ]),
+    Gf::from_be([
+        0x0000010C2A9B2FEA,
+        0x1A2BEE86B8843672,
+        0x38D2053841A24074,
+        0x1902CF5E9217F50D,
+    ]),
+    Gf::from_be([
+        0x000000338A8713C4,
+        0x254C7AE360522FC4,
+        0x61A5035A3AC98C5D,
+        0x86126EAC1C0A1166,
+    ]),
+    Gf::from_be([
+        0x000001725848920E,
+        0xB0518410CE9A5281,
+        0xA2BCC8424D24A625,
+        0xB619963440E20137,
+    ]),
+    Gf::from_be([
+        0x0000010C68C348AA,
+        0x0A692C21421930C9,
+        0x2E92898009218751,
+        0xF596806D30877774,
+    ]),
+    Gf::from_be([
+        0x000001D6F5179967,
+        0x760DEE3DF1DD38A2,
+        0x323EF75BE3A7DCED,
+        0xACD2A1364347E213,
+    ]),
+    Gf::from_be([
+        0x000001825B64FFE5,
+        0x262772A288061B2A,
+        0x4C50DAFB23C00154,
+        0x1A04E7C6670CFD0D,
+    ]),
+    Gf::from_be([
+        0x0000012B062F72CE,
+        0x4F6A4CB8300C3A3E,
+        0x2D9123CAB8A44010,
+        0x3946A716EA5479F0,
+    ]),
+    Gf::from_be([
+        0x00000034C83C8D45,
+        0x00E1E8839B1E46D3,
+        0x34C6863B098156C4,
+        0xFA5C0B69829ACC79,
+    ]),
+    Gf::from_be([
+        0x0000006DC7D31CA5,
+        0xF71249CE72F775AB,
+        0xB82BBD63F49ACF2A,
+        0xCEDFDDF67383E042,
+    ]),
+    Gf::from_be([
+        0x000000B0F1628609,
+        0x42190624AC380C95,
+        0xA024D82185222546,
+        0xE0043C318D731517,
+    ]),
+    Gf::from_be([
+        0x000000B0B1608609,
+        0x41190624AC280C15,
+        0xA024582195222146,
+        0x60007C118BF31517,
+    ]),
+    Gf::from_be([
+        0x000001130E386A29,
+        0xAE073201BA7A15D8,
+        0x5DF5248123414E5A,
+        0xE351CDFDAD5CB8EB,
+    ]),
+    Gf::from_be([
+        0x000000C9AB45864C,
+        0xBDC8C6AC5AAF7402,
+        0x0F491B2A78A2C273,
+        0x6FDBCC0127156FD8,
+    ]),
+    Gf::from_be([
+        0x000000FBB1A6E508,
+        0x104F346FBA9D43BF,
+        0x4ACD52B04B6B4660,
+        0xE88953A73AC92769,
+    ]),
+    Gf::from_be([
+        0x000001E13FBF06E3,
+        0x76E0A4F906E37771,
+        0x2E597729E82D2A3F,
+        0x528F8ED9D0D63E0C,
+    ]),
+    Gf::from_be([
+        0x00000016CDB3CB21,
+        0x1BD9A49184B028EC,
+        0xA866B5915D252427,
+        0x911265623863754B,
+    ]),
+    Gf::from_be([
+        0x00000165AD0EFACF,
+        0xCE93CE63CB721128,
+        0xD0FB32CBA5A99E9B,
+        0x9754C4F3247DC02F,
+    ]),
+    Gf::from_be([
+        0x00000123A6B0E0EC,
+        0xC1FB0822A02C180A,
+        0xB511248A9D000007,
+        0x3841B1D17336D8C5,
+    ]),
+    Gf::from_be([
+        0x000000E85F8DE429,
+        0x783A080144BB6E9C,
+        0x8AE8F3A1C401A476,
+        0xB7CA6960FD60266A,
+    ]),
+    Gf::from_be([
+        0x000000B3181963A8,
+        0xBA9E305E3ABF298A,
+        0xE2C54590464E4676,
+        0xBB866DE5633A023C,
+    ]),
+    Gf::from_be([
+        0x000001E47516C9C3,
+        0x6201E697FC3F58EE,
+        0x323AF69981A7E402,
+        0xAEC8803E6F57D347,
+    ]),
+    Gf::from_be([
+        0x000000826248024D,
+        0x410040135A1512A3,
+        0x1240880B9085C645,
+        0x8C91472303959758,
+    ]),
+    Gf::from_be([
+        0x0000013BA26A572E,
+        0x0A0462E535277F3A,
+        0x32F508F2028B7183,
+        0x0BD8AF82A616836A,
+    ]),
+    Gf::from_be([
+        0x0000016C0E09236D,
+        0x52265C3D20DC75A2,
+        0x743A211BC2E70C28,
+        0xF807CDF6035E8C12,
+    ]),
+    Gf::from_be([
+        0x000001C279C85167,
+        0x79CB29E68E72CBE4,
+        0x9718D8DBD91A2F1E,
+        0x875D76EA1874CB85,
+    ]),
+    Gf::from_be([
+        0x00000069B4970E05,
+        0x849430341E5C6BD4,
+        0xF189672326466E4D,
+        0xB89F36ADDC2A90A0,
+    ]),
+    Gf::from_be([
+        0x000000578EC33383,
+        0x052F1EA0F8516F95,
+        0x536F29513360CD18,
+        0xC69B3FB1DD99C69F,
+    ]),
+    Gf::from_be([
+        0x000000F0FE1C6ACB,
+        0x758BA8B8C6B7439F,
+        0x06ECE689F104A676,
+        0xD69907A1EAD04A2F,
+    ]),
+    Gf::from_be([
+        0x0000010C68C348AA,
+        0x0A692C21C21930C9,
+        0x2A92898009218751,
+        0xD596806D30877775,
+    ]),
+    Gf::from_be([
+        0x000000E4DDDE14CB,
+        0x8DE55C8CAF4E0912,
+        0x180AFE693BE23BDD,
+        0xFB5571D19200A151,
+    ]),
+    Gf::from_be([
+        0x000001C39BCAF3A7,
+        0xA703860E8E0A5A19,
+        0x313958D331222342,
+        0x6258B744B5C6D5C3,
+    ]),
+    Gf::from_be([
+        0x000000981724FB4E,
+        0xD44AA4184E85097B,
+        0x26A472DAE824A22D,
+        0x0C85748AA2924B26,
+    ]),
+    Gf::from_be([
+        0x0000003B63169906,
+        0x8AB90AF4E65C00C6,
+        0xA4C59652050EAE7C,
+        0xED41002D0F325D69,
+    ]),
+    Gf::from_be([
+        0x00000121CD9AFF4D,
+        0xA59E1CD35A2A5029,
+        0xCC51B4FB366DC282,
+        0x3658951270AD6C58,
+    ]),
+    Gf::from_be([
+        0x000001786534E22D,
+        0x87FF3875DE0C5F1A,
+        0xDD3CB6833F4FE004,
+        0x3C08BFD5E62CEC83,
+    ]),
+    Gf::from_be([
+        0x0000011F867DDBAB,
+        0x6A8C945E3C3F3DDA,
+        0x66972FD1826E6543,
+        0xABC6BDE8E21F5A74,
+    ]),
+    Gf::from_be([
+        0x000001636BB8712E,
+        0x651A800A26325289,
+        0x913994D2B4002607,
+        0x821C827530B4C182,
+    ]),
+    Gf::from_be([
+        0x000001572982BF0B,
+        0xD0BF9A4BD49C53D6,
+        0xCCFF1071C749E470,
+        0xFC48C7B99A2D687F,
+    ]),
+    Gf::from_be([
+        0x000001B1D1E3EC21,
+        0x036B0841E85434EE,
+        0x2CF5D9A119098D09,
+        0x8C16896A3B066C6B,
+    ]),
+    Gf::from_be([
+        0x000001E6F7B35B62,
+        0xEA886092103F78CE,
+        0x1A9AF5D880844407,
+        0xAB9AB47D7B14E324,
+    ]),
+    Gf::from_be([
+        0x0000000000000000,
+        0x0000000080000000,
+        0x0000000000000000,
+        0x0000000000000001,
+    ]),
+    Gf::from_be([
+        0x0000016AC624EEAC,
+        0xC2CFBC9EB6005339,
+        0x48F8A2A28B666200,
+        0x0049C7D6A5DC702B,
+    ]),
+    Gf::from_be([
+        0x00000179EAC21AED,
+        0x366846CD329B5895,
+        0x169D884B68AB4734,
+        0xB28DB461DDC5DA64,
+    ]),
+    Gf::from_be([
+        0x00000166C5BCAEEE,
+        0xB8D20C564AFA5FB5,
+        0xB5FAB62A4C2E8E3A,
+        0xF719FFF299A799BA,
+    ]),
+    Gf::from_be([
+        0x000000D015E2998E,
+        0x8D04B40F34504FC1,
+        0x6CCC785232636D19,
+        0x81097AB8109A6D28,
+    ]),
+    Gf::from_be([
+        0x000001F7B967ADA2,
+        0x5826184E789C73F6,
+        0x605F5B30C24AC531,
+        0xFD1BD3EA8E4E501C,
+    ]),
+    Gf::from_be([
+        0x000001270C10A70C,
+        0x18B2A8D04CE41C02,
+        0x80932432440CA82E,
+        0x1D05EC1003651120,
+    ]),
+    Gf::from_be([
+        0x000001AB5DBF1A02,
+        0x75DCB2007CEF3542,
+        0xF311F740FE40E82E,
+        0x6E86DC98173F8680,
+    ]),
+    Gf::from_be([
+        0x000000D095F2998E,
+        0x0904B40F34704FC5,
+        0x6CCC7C5212636D1A,
+        0x81097AB9189A6D28,
+    ]),
+    Gf::from_be([
+        0x0000018B2020C7C7,
+        0x1D982C5C62C80D65,
+        0xA4B100BB742E8B2D,
+        0x65446D8B08B30966,
+    ]),
+    Gf::from_be([
+        0x0000011A8E19E3EB,
+        0x66AF9096BC7E34DE,
+        0x64942599A3466C5B,
+        0xBA06DD7CEA1F1965,
+    ]),
+    Gf::from_be([
+        0x00000069A4978E05,
+        0x849434341E5C6BD4,
+        0xF589272326666E4D,
+        0xB81E36ADDC2A98A0,
+    ]),
+    Gf::from_be([
+        0x0000008EDA97306F,
+        0xCF930089CA3521D7,
+        0x8EC2C74BB5018602,
+        0x9D9711E89AF13E6D,
+    ]),
+    Gf::from_be([
+        0x000001652FCB0FAF,
+        0x5241485EB1DF35A7,
+        0x5EBB3933C98E5DB9,
+        0xBAC38DA31BCDEB63,
+    ]),
+    Gf::from_be([
+        0x00000008A09F9222,
+        0x8E91449A90202334,
+        0x9880074025A44007,
+        0x010716C39960A135,
+    ]),
+    Gf::from_be([
+        0x000001692F5D0A2D,
+        0x3B4C9AEE209774A6,
+        0x42193F035A4A0530,
+        0xDBCBDC624B1D4714,
+    ]),
+    Gf::from_be([
+        0x000000887B0FFAAE,
+        0x30273AAADCE32D4C,
+        0x4600D3C24340E82A,
+        0x56C76DC86C085F55,
+    ]),
+    Gf::from_be([
+        0x0000005B031990E1,
+        0xCA9474CD6C1865A4,
+        0xF4AD154986EBA404,
+        0xE54A08E7083A9D72,
+    ]),
+    Gf::from_be([
+        0x00000094AFFBC96C,
+        0x5AD9E482DEA52A4F,
+        0xBA263D9ACDA0E222,
+        0x1D8322493BF6E213,
+    ]),
+    Gf::from_be([
+        0x000000BC333377CB,
+        0xB4C252A347DD6B9B,
+        0x72C655F968C1BEED,
+        0xAC9B66E0E69B9238,
+    ]),
+    Gf::from_be([
+        0x0000004DC393DC20,
+        0xF7B840EE7AF6658B,
+        0xB86B95E0F48ACE2A,
+        0xCE5F58E473F3E04A,
+    ]),
+    Gf::from_be([
+        0x0000019A7EB4B107,
+        0xAF3451800A6B065D,
+        0x5874E65336D00A1B,
+        0x33C40E1CFDCDB05E,
+    ]),
+    Gf::from_be([
+        0x0000011BECDF9C0B,
+        0x34C5B424D0C73955,
+        0x5335AF616B62C968,
+        0x0E86A0899CCC9397,
+    ]),
+    Gf::from_be([
+        0x000001428B65418B,
+        0xA050EAF03867796F,
+        0xABF81B910C8C495E,
+        0x0A9FA18A2FA666BE,
+    ]),
+    Gf::from_be([
+        0x000001889B2888E6,
+        0xF27490CD18BF1C85,
+        0xDBD0500ACE4B4472,
+        0xEA91B8740CFCA6AC,
+    ]),
+    Gf::from_be([
+        0x0000000082C5F560,
+        0x9CD2863D7AAD240F,
+        0x8E400BF86C27C322,
+        0x2D9749503FB02B4C,
+    ]),
+    Gf::from_be([
+        0x0000018170D0EE04,
+        0x437692EF642C183A,
+        0xC8F1CCA29E4BA146,
+        0x6C01B502B67D617E,
+    ]),
+    Gf::from_be([
+        0x000001BF760F5922,
+        0xA895925A9027354A,
+        0xC697E3D0074C4006,
+        0x4B8288D9676D4E35,
+    ]),
+    Gf::from_be([
+        0x00000187915920E5,
+        0xBEC6304672FA34A1,
+        0x40335D0B6A4ACF6E,
+        0xA743C962408D1152,
+    ]),
+    Gf::from_be([
+        0x00000058077CE060,
+        0xD6FF786684194DB9,
+        0xCF6C3E88EFCA2505,
+        0xB49978F6E5B97BDB,
+    ]),
+    Gf::from_be([
+        0x0000011505A67208,
+        0xA57FF450C03416FA,
+        0xF89732C03FEC8446,
+        0xCC049E3BF23FA021,
+    ]),
+    Gf::from_be([
+        0x0000010CEA06BDCA,
+        0x96BBAA1CB8BC14C6,
+        0xA4D2827865064473,
+        0xF801C93D0F375C39,
+    ]),
+    Gf::from_be([
+        0x0000007CEDC5AA85,
+        0x0F9614541E0D6F53,
+        0xF7AEBB03366E6344,
+        0x29DA3F9DD2AB9BF6,
+    ]),
+    Gf::from_be([
+        0x00000063646A7966,
+        0x5C46729262C54D7F,
+        0x6BA9A8DAEAC48B79,
+        0x59D878DBEA8B26B2,
+    ]),
+    Gf::from_be([
+        0x000000748F325FE7,
+        0x1144926964B04BDC,
+        0x79EE34FB5A49A477,
+        0xC40976E8E91BB0BA,
+    ]),
+    Gf::from_be([
+        0x000001000FD20848,
+        0x395CF2984CF71FF6,
+        0xFA903C085EC4AD7E,
+        0xDBC4FAAEDA3FE730,
+    ]),
+    Gf::from_be([
+        0x000000B1DE31E469,
+        0x3A37FAD984A32B18,
+        0xD665E5A947CD2077,
+        0x57D273C4E1398B5B,
+    ]),
+    Gf::from_be([
+        0x0000005E0F64ADF1,
+        0xC497AC86FE556E12,
+        0xE20E3A3DA722EF18,
+        0x9C8E6B51826B4344,
+    ]),
+    Gf::from_be([
+        0x00000066ECBFC146,
+        0xD8E452190E84654B,
+        0x702AA79ACAC52274,
+        0x194B098837DB8106,
+    ]),
+    Gf::from_be([
+        0x0000011F067DDBAB,
+        0xEE8C14D63C1F3DDE,
+        0x66B72FD1A26E6540,
+        0xABC6BDE9EA1E5B62,
+    ]),
+    Gf::from_be([
+        0x000000D7145F42CF,
+        0xBBB90413A2AE6B5B,
+        0x990F6F8B55250333,
+        0x7B0F7698A2A1F5D1,
+    ]),
+    Gf::from_be([
+        0x000001CF139F4225,
+        0x6388A0D1084F721F,
+        0x0A7B5783900D0859,
+        0x2ACFC751FA84364A,
+    ]),
+    Gf::from_be([
+        0x0000011FE6EA498B,
+        0x201C105F3C271D6A,
+        0xEAB7A891064F6147,
+        0x4AD1B88A732E7676,
+    ]),
+    Gf::from_be([
+        0x000001F0184FE5C3,
+        0xA30BACE3EE4A7759,
+        0x199C4BB91129AB1D,
+        0x660A9A88A1C4E4F1,
+    ]),
+    Gf::from_be([
+        0x0000004DAA0C4160,
+        0x9F54D2D26CD04D8C,
+        0xE98B02987ECCAC29,
+        0xC51C68E56C2B65A4,
+    ]),
+    Gf::from_be([
+        0x0000011F866DDBAB,
+        0x6A8C145E3C3F3DDA,
+        0x66B72BD1826E6543,
+        0xABC6BDE8E21E5B62,
+    ]),
+    Gf::from_be([
+        0x000001E6E7F3DB62,
+        0xEA8A6492103F78CE,
+        0x1C9ABDD880A44407,
+        0xAB9BB47D7B10E924,
+    ]),
+    Gf::from_be([
+        0x0000000568F3AA60,
+        0x46B382C9805929B4,
+        0x8E238D08A5090D1C,
+        0xF0D765F699316F13,
+    ]),
+    Gf::from_be([
+        0x00000069A4978E05,
+        0x849430349E5C6BD4,
+        0xF589272326466E4D,
+        0xB81E36ADDC2A98A1,
+    ]),
+    Gf::from_be([
+        0x0000017EC2D8D5AC,
+        0x6C98A46A247F568E,
+        0x975E8CF2A4282D1F,
+        0xFB9D8B603B35DA9C,
+    ]),
+    Gf::from_be([
+        0x000001D637D26C07,
+        0xE8DF68008B601C2D,
+        0xBC7E7CA38F801BCF,
+        0x0145E84678F7E95F,
+    ]),
+    Gf::from_be([
+        0x000000450A93D342,
+        0x11C5B648FCF06EB8,
+        0x710B05D85B68EC2E,
+        0xC41B7E26F54BC491,
+    ]),
+    Gf::from_be([
+        0x00000003E979E481,
+        0xF98366709697279F,
+        0x0AE19DA1D1AC6730,
+        0xDB821BB4EEC1373F,
+    ]),
+    Gf::from_be([
+        0x00000197FD622105,
+        0x5AABAEF4E0DC1096,
+        0x20B7F813C12E8D69,
+        0xFD01D0208E160437,
+    ]),
+    Gf::from_be([
+        0x000001576BDAD84B,
+        0xC0FC58EC2E01556D,
+        0xDA3F9CC98ECA2355,
+        0x10DC988A38FDE206,
+    ]),
+    Gf::from_be([
+        0x000001724808120E,
+        0xB0518010CE9A5281,
+        0xA6BC80424D04A625,
+        0xB618963440E60B37,
+    ]),
+    Gf::from_be([
+        0x000001C5D9F16D26,
+        0x82AE146E7546330E,
+        0x645BDDB2026AF9D9,
+        0x1E16D2813B5E485C,
+    ]),
+    Gf::from_be([
+        0x00000048AB607640,
+        0xB103C227FAAF4C3F,
+        0x364818E85183C336,
+        0x3E883D12EAC28F59,
+    ]),
+    Gf::from_be([
+        0x0000007E0BA278C4,
+        0xCD25B717F6545EB3,
+        0x6A4E10CAB377EE19,
+        0x9D0CAE22929A6319,
+    ]),
+    Gf::from_be([
+        0x000001724848120E,
+        0xB07184104E9B5281,
+        0xA6BC88424D24A725,
+        0xB698962440E60B36,
+    ]),
+    Gf::from_be([
+        0x000000244ED9EF65,
+        0x18C2E6A6729D26DA,
+        0x1E02ADBB48A2C764,
+        0xFD865E78B601FF04,
+    ]),
+    Gf::from_be([
+        0x000000F659F05D4A,
+        0x9E6C34A73CC04A0B,
+        0x4C8EDCF86A63696D,
+        0x110C331437C97C30,
+    ]),
+    Gf::from_be([
+        0x000000B073A77369,
+        0xDFCB8019D695289A,
+        0x2E64D3D9F905E664,
+        0xCD933561B2433E5B,
+    ]),
+    Gf::from_be([
+        0x0000005747028203,
+        0xA9A9FAEFEF4C0417,
+        0x748FB00111CBBA89,
+        0x6D445D44CE8A8821,
+    ]),
+    Gf::from_be([
+        0x0000016F63EDC0AC,
+        0x21912655A4777A8B,
+        0xAA5B9B82152F2D1F,
+        0xCA8BF26177F7231D,
+    ]),
+    Gf::from_be([
+        0x000001C29BCAF3A7,
+        0xA703860E8E0A5A19,
+        0x313858D331222340,
+        0x6358B744B5C6D5C3,
+    ]),
+    Gf::from_be([
+        0x000000DA6534E22D,
+        0x87FF3875DE0C5F1A,
+        0xDD2CB6833F4FE340,
+        0x3808BFD5E62CEC83,
+    ]),
+    Gf::from_be([
+        0x00000116ECDF9689,
+        0x7CFC922056A33165,
+        0xF276AF61EE40E376,
+        0x1786858F1CFE971E,
+    ]),
+    Gf::from_be([
+        0x000000FB53611068,
+        0x8E9DB252C0206730,
+        0xC48DD948274C8142,
+        0x051A1AD781790C25,
+    ]),
+    Gf::from_be([
+        0x000001EB10A78B80,
+        0xD53DBAA0FED17FC5,
+        0xD7194310F740EE2D,
+        0xD4DBEFBD5CBD9FC1,
+    ]),
+    Gf::from_be([
+        0x000001F0F1CE6282,
+        0x4CD75A2082405C69,
+        0xFC3CDA80AFC00B08,
+        0x055CFD0F31EFBD57,
+    ]),
+    Gf::from_be([
+        0x00000123E4E887AE,
+        0xD1B8CA854AB11EB1,
+        0xA3D1A832D4838722,
+        0xD4D5EE62D1E652BC,
+    ]),
+    Gf::from_be([
+        0x0000002703C2AF40,
+        0x21EE5A48001303F4,
+        0x7A0318381AC80550,
+        0xC6C116BED95AF610,
+    ]),
+    Gf::from_be([
+        0x000001C39639F66F,
+        0x8A667AC94A397999,
+        0x70D965EB0AC98652,
+        0xE1CAE5F0A1DF806C,
+    ]),
+];
+
+impl Gf {
+    pub(crate) const ZERO: Gf = Gf([0, 0, 0, 0]);
+    pub(crate) const ONE: Gf = Gf([1, 0, 0, 0]);
+
+    // Creates a field element from four limbs in big-endian order, which is the
+    // order used by the GFw64be macro in c-xs233. The value must be reduced.
+    pub(crate) const fn from_be(w: [u64; 4]) -> Self {
+        Gf([w[3], w[2], w[1], w[0]])
+    }
+
+    // Creates a field element from four little-endian limbs that may hold any
+    // 256-bit value.
+    #[cfg(any(test, feature = "ffi"))]
+    pub(crate) fn from_limbs(w: [u64; 4]) -> Self {
+        reduce([w[0], w[1], w[2], w[3], 0, 0, 0, 0])
+    }
+
+    // Returns the four little-endian limbs.
+    #[cfg(feature = "ffi")]
+    pub(crate) fn to_limbs(self) -> [u64; 4] {
+        self.0
+    }
+
+    // Decodes a field element from 30 little-endian bytes. Fails if the value
+    // is not canonical, i.e. if any of the top seven bits are set. On failure,
+    // the returned element is zero.
+    pub(crate) fn decode(src: &[u8; 30]) -> (Self, Choice) {
+        let mut w = [0u64; 4];
+        for (i, byte) in src.iter().enumerate() {
+            w[i / 8] |= (*byte as u64) << (8 * (i % 8));
+        }
+
+        let is_valid = src[29].ct_eq(&0) | src[29].ct_eq(&1);
+        let out = Gf::conditional_select(&Gf::ZERO, &Gf(w), is_valid);
+        (out, is_valid)
+    }
+
+    pub(crate) fn encode(&self) -> [u8; 30] {
+        let mut out = [0u8; 30];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = (self.0[i / 8] >> (8 * (i % 8))) as u8;
+        }
+        out
+    }
+
+    pub(crate) fn is_zero(&self) -> Choice {
+        self.ct_eq(&Gf::ZERO)
+    }
+
+    pub(crate) fn square(&self) -> Self {
+        let mut c = [0u64; 8];
+        for i in 0..4 {
+            c[2 * i] = expand(self.0[i] as u32);
+            c[2 * i + 1] = expand((self.0[i] >> 32) as u32);
+        }
+        reduce(c)
+    }
+
+    // Computes self^(2^n). n is not secret.
+    pub(crate) fn square_n(&self, n: u32) -> Self {
+        let mut out = *self;
+        for _ in 0..n {
+            out = out.square();
+        }
+        out
+    }
+
+    // The square root of a = ae + z*ao is sqrt(ae) + sqrt(z)*sqrt(ao), where ae
+    // and ao only have even powers of z, so their square roots are obtained by
+    // squeezing out the odd bits.
+    //
+    // sqrt(ao) has at most 116 bits and sqrt(z) only six, so we multiply them
+    // with shifts.
+    pub(crate) fn sqrt(&self) -> Self {
+        let [o0, o1, _, _] = squeeze_limbs(self.0.map(|w| w >> 1));
+        let mut c = [0u64; 8];
+        for shift in SQRT_Z_POWERS {
+            let (q, r) = (shift / 64, shift % 64);
+            c[q] ^= o0 << r;
+            c[q + 1] ^= (o0 >> (64 - r)) | (o1 << r);
+            c[q + 2] ^= o1 >> (64 - r);
+        }
+        reduce(c) + Gf(squeeze_limbs(self.0))
+    }
+
+    // Computes 1/self using Fermat's little theorem, with the Itoh-Tsujii
+    // addition chain 1, 2, 3, 6, 7, 14, 28, 29, 58, 116, 232 for the exponent
+    // 2^232 - 1. The inverse of zero is zero.
+    pub(crate) fn invert(&self) -> Self {
+        // bk = self^(2^k - 1)
+        let b1 = *self;
+        let b2 = b1.square() * b1;
+        let b3 = b2.square() * b1;
+        let b6 = b3.square_n(3) * b3;
+        let b7 = b6.square() * b1;
+        let b14 = b7.square_n(7) * b7;
+        let b28 = b14.square_n(14) * b14;
+        let b29 = b28.square() * b1;
+        let b58 = b29.square_n(29) * b29;
+        let b116 = b58.square_n(58) * b58;
+        let b232 = b116.square_n(116) * b116;
+        b232.square()
+    }
+
+    // Tr(a) = a_0 + a_159 for this field.
+    pub(crate) fn trace(&self) -> Choice {
+        Choice::from(((self.0[0] ^ (self.0[2] >> 31)) & 1) as u8)
+    }
+
+    // The half-trace H(a) is the sum of a^(2^(2*i)) for i = 0 to 116. If
+    // Tr(a) = 0, then H(a) is a solution of x^2 + x = a.
+    //
+    // As in c-xs233, we avoid the 232 squarings: H is linear, and H(x) =
+    // H(sqrt(x)) + sqrt(x). Splitting a = ae + z*ao into its even and odd bits,
+    // H(a) = H(sqrt(ae)) + sqrt(ae) + H(z*ao), and sqrt(ae) has half the size
+    // of a. After eight such steps, a single bit b is left, with H(b) = b. The
+    // half-traces of the odd bits that were split off are taken from a table.
+    pub(crate) fn halftrace(&self) -> Self {
+        let mut odd = [0u64; 4];
+        let mut out = [0u64; 4];
+        let mut v = self.0;
+        for _ in 0..8 {
+            for i in 0..4 {
+                odd[i] ^= v[i];
+            }
+            v = squeeze_limbs(v);
+            for i in 0..4 {
+                out[i] ^= v[i];
+            }
+        }
+        out[0] ^= v[0];
+
+        // the odd bits of each limb are paired with 32 consecutive entries
+        for (i, entries) in HALFTRACE.chunks(32).enumerate() {
+            let mut bits = odd[i] >> 1;
+            for entry in entries {
+                let mask = (bits & 1).wrapping_neg();
+                bits >>= 2;
+                out[0] ^= mask & entry.0[0];
+                out[1] ^= mask & entry.0[1];
+                out[2] ^= mask & entry.0[2];
+                out[3] ^= mask & entry.0[3];
+            }
+        }
+        Gf(out)
+    }
+}
+
+impl ConstantTimeEq for Gf {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl ConditionallySelectable for Gf {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut out = Gf::ZERO;
+        for i in 0..4 {
+            out.0[i] = u64::conditional_select(&a.0[i], &b.0[i], choice);
+        }
+        out
+    }
+}
+
+impl core::ops::Add for Gf {
+    type Output = Gf;
+
+    fn add(self, rhs: Gf) -> Gf {
+        Gf([
+            self.0[0] ^ rhs.0[0],
+            self.0[1] ^ rhs.0[1],
+            self.0[2] ^ rhs.0[2],
+            self.0[3] ^ rhs.0[3],
+        ])
+    }
+}
+
+impl core::ops::AddAssign for Gf {
+    fn add_assign(&mut self, rhs: Gf) {
+        *self = *self + rhs;
+    }
+}
+
+impl core::ops::Mul for Gf {
+    type Output = Gf;
+
+    // Two levels of Karatsuba, for a total of nine 64x64 carryless products.
+    fn mul(self, rhs: Gf) -> Gf {
+        let a = &self.0;
+        let b = &rhs.0;
+
+        let lo = mul128([a[0], a[1]], [b[0], b[1]]);
+        let hi = mul128([a[2], a[3]], [b[2], b[3]]);
+        let mut mid = mul128([a[0] ^ a[2], a[1] ^ a[3]], [b[0] ^ b[2], b[1] ^ b[3]]);
+        for i in 0..4 {
+            mid[i] ^= lo[i] ^ hi[i];
+        }
+
+        let mut c = [lo[0], lo[1], lo[2], lo[3], hi[0], hi[1], hi[2], hi[3]];
+        for i in 0..4 {
+            c[i + 2] ^= mid[i];
+        }
+        reduce(c)
+    }
+}
+
+impl core::ops::MulAssign for Gf {
+    fn mul_assign(&mut self, rhs: Gf) {
+        *self = *self * rhs;
+    }
+}
+
+// Multiplies two 128-bit polynomials using Karatsuba.
+fn mul128(a: [u64; 2], b: [u64; 2]) -> [u64; 4] {
+    let (l0, l1) = clmul(a[0], b[0]);
+    let (h0, h1) = clmul(a[1], b[1]);
+    let (m0, m1) = clmul(a[0] ^ a[1], b[0] ^ b[1]);
+    let m0 = m0 ^ l0 ^ h0;
+    let m1 = m1 ^ l1 ^ h1;
+    [l0, l1 ^ m0, h0 ^ m1, h1]
+}
+
+// Carryless multiplication of two 64-bit polynomials, returning the low and
+// high halves of the product. The high half is obtained by multiplying the
+// bit-reversed operands.
+fn clmul(x: u64, y: u64) -> (u64, u64) {
+    let lo = bmul64(x, y);
+    let hi = bmul64(x.reverse_bits(), y.reverse_bits()).reverse_bits() >> 1;
+    (lo, hi)
+}
+
+// Low 64 bits of the carryless product of x and y. This uses integer
+// multiplications on operands with "holes" (as in BearSSL's ghash_ctmul64),
+// so that carries never spill into bits that are kept.
+fn bmul64(x: u64, y: u64) -> u64 {
+    const M0: u64 = 0x1111111111111111;
+    const M1: u64 = 0x2222222222222222;
+    const M2: u64 = 0x4444444444444444;
+    const M3: u64 = 0x8888888888888888;
+
+    let (x0, x1, x2, x3) = (x & M0, x & M1, x & M2, x & M3);
+    let (y0, y1, y2, y3) = (y & M0, y & M1, y & M2, y & M3);
+
+    let z0 = x0.wrapping_mul(y0) ^ x1.wrapping_mul(y3) ^ x2.wrapping_mul(y2) ^ x3.wrapping_mul(y1);
+    let z1 = x0.wrapping_mul(y1) ^ x1.wrapping_mul(y0) ^ x2.wrapping_mul(y3) ^ x3.wrapping_mul(y2);
+    let z2 = x0.wrapping_mul(y2) ^ x1.wrapping_mul(y1) ^ x2.wrapping_mul(y0) ^ x3.wrapping_mul(y3);
+    let z3 = x0.wrapping_mul(y3) ^ x1.wrapping_mul(y2) ^ x2.wrapping_mul(y1) ^ x3.wrapping_mul(y0);
+
+    (z0 & M0) | (z1 & M1) | (z2 & M2) | (z3 & M3)
+}
+
+// Reduces a polynomial of degree at most 511 modulo z^233 + z^74 + 1. Since
+// z^233 = z^74 + 1, bit 233+k is folded into bits k and 74+k.
+fn reduce(mut c: [u64; 8]) -> Gf {
+    for i in (4..8).rev() {
+        let t = c[i];
+        c[i - 4] ^= t << 23;
+        c[i - 3] ^= (t >> 41) ^ (t << 33);
+        c[i - 2] ^= t >> 31;
+    }
+
+    let t = c[3] >> 41;
+    c[0] ^= t;
+    c[1] ^= t << 10;
+    c[3] &= 0x000001FFFFFFFFFF;
+
+    Gf([c[0], c[1], c[2], c[3]])
+}
+
+// Spreads the 32 bits of x to the even bit positions of the output.
+fn expand(x: u32) -> u64 {
+    let mut x = x as u64;
+    x = (x | (x << 16)) & 0x0000FFFF0000FFFF;
+    x = (x | (x << 8)) & 0x00FF00FF00FF00FF;
+    x = (x | (x << 4)) & 0x0F0F0F0F0F0F0F0F;
+    x = (x | (x << 2)) & 0x3333333333333333;
+    x = (x | (x << 1)) & 0x5555555555555555;
+    x
+}
+
+// Collects the even-indexed bits of the four limbs into the low half of the
+// output, i.e. computes the square root of the even part of a field element.
+fn squeeze_limbs(w: [u64; 4]) -> [u64; 4] {
+    let mut out = [0u64; 4];
+    for i in 0..4 {
+        out[i / 2] |= squeeze(w[i]) << (32 * (i % 2));
+    }
+    out
+}
+
+// Collects the even-indexed bits of x into the low 32 bits of the output.
+fn squeeze(x: u64) -> u64 {
+    let mut x = x & 0x5555555555555555;
+    x = (x | (x >> 1)) & 0x3333333333333333;
+    x = (x | (x >> 2)) & 0x0F0F0F0F0F0F0F0F;
+    x = (x | (x >> 4)) & 0x00FF00FF00FF00FF;
+    x = (x | (x >> 8)) & 0x0000FFFF0000FFFF;
+    x = (x | (x >> 16)) & 0x00000000FFFFFFFF;
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    fn random(rng: &mut ChaCha8Rng) -> Gf {
+        Gf::from_limbs(rng.gen())
+    }
+
+    // bit-by-bit shift-and-add multiplication, to check the real thing against
+    fn slow_mul(a: &Gf, b: &Gf) -> Gf {
+        let mut out = Gf::ZERO;
+        let mut a = *a;
+        for i in 0..233 {
+            let bit = Choice::from(((b.0[i / 64] >> (i % 64)) & 1) as u8);
+            out += Gf::conditional_select(&Gf::ZERO, &a, bit);
+            // multiply a by z
+            let carry = a.0[3] >> 40;
+            a.0[3] = (a.0[3] << 1 | a.0[2] >> 63) & 0x000001FFFFFFFFFF;
+            a.0[2] = a.0[2] << 1 | a.0[1] >> 63;
+            a.0[1] = a.0[1] << 1 | a.0[0] >> 63;
+            a.0[0] <<= 1;
+            a.0[0] ^= carry;
+            a.0[1] ^= carry << 10;
+        }
+        out
+    }
+
+    #[test]
+    fn mul_and_square() {
+        let mut rng = ChaCha8Rng::from_seed([1u8; 32]);
+
+        for _ in 0..1000 {
+            let a = random(&mut rng);
+            let b = random(&mut rng);
+            assert_eq!(a * b, slow_mul(&a, &b));
+            assert_eq!(a * b, b * a);
+            assert_eq!(a.square(), slow_mul(&a, &a));
+        }
+
+        // z^232 * z = z^74 + 1
+        let z = Gf([2, 0, 0, 0]);
+        let z232 = Gf([0, 0, 0, 1 << 40]);
+        assert_eq!(z232 * z, Gf([1, 1 << 10, 0, 0]));
+    }
+
+    #[test]
+    fn sqrt_invert_trace() {
+        let mut rng = ChaCha8Rng::from_seed([2u8; 32]);
+
+        assert_eq!(Gf([2, 0, 0, 0]).sqrt().square(), Gf([2, 0, 0, 0]));
+        assert_eq!(Gf::ZERO.invert(), Gf::ZERO);
+
+        for _ in 0..100 {
+            let a = random(&mut rng);
+            assert_eq!(a.sqrt().square(), a);
+            assert_eq!(a.invert() * a, Gf::ONE);
+
+            // the trace is the sum of all a^(2^i)
+            let mut trace = a;
+            let mut t = a;
+            for _ in 1..233 {
+                t = t.square();
+                trace += t;
+            }
+            let expected = Choice::from(trace.0[0] as u8 & 1);
+            assert!(bool::from(trace.ct_eq(&Gf::ZERO) | trace.ct_eq(&Gf::ONE)));
+            assert_eq!(a.trace().unwrap_u8(), expected.unwrap_u8());
+
+            // the half-trace is the sum of all a^(2^(2*i))
+            let mut halftrace = a;
+            let mut t = a;
+            for _ in 0..116 {
+                t = t.square().square();
+                halftrace += t;
+            }
+            assert_eq!(a.halftrace(), halftrace);
+
+            if !bool::from(a.trace()) {
+                let h = a.halftrace();
+                assert_eq!(h.square() + h, a);
+            }
+        }
+    }
+
+    #[test]
+    fn encode_decode() {
+        let mut rng = ChaCha8Rng::from_seed([3u8; 32]);
+
+        for _ in 0..100 {
+            let a = random(&mut rng);
+            let (b, is_valid) = Gf::decode(&a.encode());
+            assert!(bool::from(is_valid));
+            assert_eq!(a, b);
+        }
+
+        let mut buf = [0xffu8; 30];
+        buf[29] = 2;
+        let (b, is_valid) = Gf::decode(&buf);
+        assert!(!bool::from(is_valid));
+        assert_eq!(b, Gf::ZERO);
+    }
+}
@@ -0,0 +1,372 @@
+//! Hashing to the xs233 groups and to scalars, in the style of RFC 9380.
+//!
+//! [`expand_message_xmd`] is the expander of RFC 9380 §5.3.1 with SHA-256. A field
+//! element is 30 bytes of its output, read as little-endian like the encodings of
+//! this crate, with the bits above 2^233 cleared, which is exactly uniform.
+//!
+//! RFC 9380 does not define maps for binary curves, so field elements are mapped with
+//! Icart's map for curves over GF(2^m) with odd m (Icart, "How to Hash into Elliptic
+//! Curves", CRYPTO 2009). Unlike [`map_uniform_bytes_to_curve`], it has no failure
+//! case and runs in constant time: the cube root is an exponentiation by the fixed
+//! exponent (2^234 - 1)/3. The point on the standard curve is multiplied by the
+//! cofactor, and then moved into the xs233 group by adding the point `N` of order 2
+//! (see xs233_common.h in c-xs233).
+//!
+//! [`encode_to_curve`] maps a single field element, and its output is not uniformly
+//! distributed. [`hash_to_curve`] adds the images of two field elements, which is
+//! indifferentiable from a random oracle, and should be used unless a protocol
+//! specifies otherwise.
+//!
+//! [`map_uniform_bytes_to_curve`]: crate::map_uniform_bytes_to_curve
+
+use crate::curve::Curve;
+use crate::gf233::Gf;
+use crate::pure::{CurveParams, Extended};
+use crate::scalar::ModScalar;
+use crate::xsb233::Xsb233;
+use crate::xsk233::Xsk233;
+use crate::{Error, Point};
+use sha2::{Digest, Sha256};
+use subtle::Choice;
+
+/// The maximum output length of [`expand_message_xmd`], 255 times the hash length.
+pub const MAX_EXPAND_LEN: usize = 255 * 32;
+
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+/// The groups that can be hashed to, [`Xsk233`] and [`Xsb233`].
+pub trait HashToCurve: Curve + sealed::Sealed {}
+
+mod sealed {
+    pub trait Sealed {
+        // Maps canonical field elements to points, and returns the encoding of their
+        // sum.
+        fn map_to_curve(u: &[[u8; 30]]) -> [u8; 30];
+    }
+}
+
+macro_rules! impl_hash_to_curve {
+    ($curve:ty, $params:ty) => {
+        impl sealed::Sealed for $curve {
+            fn map_to_curve(u: &[[u8; 30]]) -> [u8; 30] {
+                let doublings = <$curve as Curve>::COFACTOR.trailing_zeros();
+                let mut sum = <$params as CurveParams>::NEUTRAL;
+                for u in u {
+                    let (u, _) = Gf::decode(u);
+                    sum = sum.add::<$params>(&map_to_group::<$params>(&u, doublings));
+                }
+                sum.encode()
+            }
+        }
+
+        impl HashToCurve for $curve {}
+    };
+}
+
+impl_hash_to_curve!(Xsk233, crate::pure::xsk233::Params);
+impl_hash_to_curve!(Xsb233, crate::pure::xsb233::Params);
+
+/// Fills `out` with bytes derived from the concatenation of the parts of `msg`, with
+/// the domain separation tag `dst`, as `expand_message_xmd` of RFC 9380 with
+/// SHA-256. Tags longer than 255 bytes are hashed as specified.
+///
+/// Fails with [`Error::InvalidLength`] if `out` is longer than [`MAX_EXPAND_LEN`].
+pub fn expand_message_xmd(msg: &[&[u8]], dst: &[u8], out: &mut [u8]) -> Result<(), Error> {
+    if out.len() > MAX_EXPAND_LEN {
+        return Err(Error::InvalidLength {
+            expected: MAX_EXPAND_LEN,
+            actual: out.len(),
+        });
+    }
+
+    let oversize_dst;
+    let dst = if dst.len() > 255 {
+        oversize_dst = Sha256::new()
+            .chain_update(OVERSIZE_DST_PREFIX)
+            .chain_update(dst)
+            .finalize();
+        &oversize_dst[..]
+    } else {
+        dst
+    };
+    let dst_len = [dst.len() as u8];
+
+    let mut hasher = Sha256::new().chain_update([0u8; 64]);
+    for part in msg {
+        hasher.update(part);
+    }
+    let b_0 = hasher
+        .chain_update((out.len() as u16).to_be_bytes())
+        .chain_update([0])
+        .chain_update(dst)
+        .chain_update(dst_len)
+        .finalize();
+
+    let mut b_i = Sha256::new()
+        .chain_update(b_0)
+        .chain_update([1])
+        .chain_update(dst)
+        .chain_update(dst_len)
+        .finalize();
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        if i > 0 {
+            for (b, b0) in b_i.iter_mut().zip(b_0) {
+                *b ^= b0;
+            }
+            b_i = Sha256::new()
+                .chain_update(b_i)
+                .chain_update([i as u8 + 1])
+                .chain_update(dst)
+                .chain_update(dst_len)
+                .finalize();
+        }
+        chunk.copy_from_slice(&b_i[..chunk.len()]);
+    }
+    Ok(())
+}
+
+fn hash_to_field<const N: usize>(msg: &[&[u8]], dst: &[u8]) -> [[u8; 30]; N] {
+    let mut u = [[0u8; 30]; N];
+    let mut uniform = [0u8; 60];
+    expand_message_xmd(msg, dst, &mut uniform[..30 * N])
+        .expect("the output is shorter than the maximum");
+    for (u, bytes) in u.iter_mut().zip(uniform.chunks(30)) {
+        u.copy_from_slice(bytes);
+        u[29] &= 1;
+    }
+    u
+}
+
+fn decode_point<C: Curve>(repr: &[u8; 30]) -> C::Point {
+    let mut point = C::Point::default();
+    let is_valid = point.decode(repr);
+    debug_assert!(bool::from(is_valid), "the map outputs group elements");
+    point
+}
+
+/// Hashes the concatenation of the parts of `msg` to a point, uniformly, with the
+/// domain separation tag `dst`.
+pub fn hash_to_curve<C: HashToCurve>(msg: &[&[u8]], dst: &[u8]) -> C::Point {
+    let u = hash_to_field::<2>(msg, dst);
+    decode_point::<C>(&C::map_to_curve(&u))
+}
+
+/// Hashes the concatenation of the parts of `msg` to a point with a single map, with
+/// the domain separation tag `dst`. This is faster than [`hash_to_curve`], but the
+/// output is not uniformly distributed, and does not reach all points.
+pub fn encode_to_curve<C: HashToCurve>(msg: &[&[u8]], dst: &[u8]) -> C::Point {
+    let u = hash_to_field::<1>(msg, dst);
+    decode_point::<C>(&C::map_to_curve(&u))
+}
+
+/// Hashes the concatenation of the parts of `msg` to a scalar, with the domain
+/// separation tag `dst`, as `hash_to_field` of RFC 9380 for the integers modulo the
+/// order.
+///
+/// The expanded bytes are read as a big-endian integer of 128 bits more than the
+/// order, so the output is statistically close to uniform.
+pub fn hash_to_scalar<C: Curve>(msg: &[&[u8]], dst: &[u8]) -> ModScalar<C> {
+    let order_bits = 256 - C::ORDER[3].leading_zeros() as usize;
+    let len = (order_bits + 128).div_ceil(8);
+    let mut uniform = [0u8; 64];
+    expand_message_xmd(msg, dst, &mut uniform[..len])
+        .expect("the output is shorter than the maximum");
+    uniform[..len].reverse();
+    ModScalar::from_bytes_mod_order(&uniform[..len])
+}
+
+// Computes x^((2^234 - 1)/3), which is the unique cube root of x, since 3 does not
+// divide 2^233 - 1. The exponent is the sum of 4^i for i = 0 to 116.
+fn cbrt(x: &Gf) -> Gf {
+    let mut out = *x;
+    let mut t = *x;
+    for _ in 0..116 {
+        t = t.square_n(2);
+        out *= t;
+    }
+    out
+}
+
+// Icart's map to the standard curve y^2 + x*y = x^3 + a*x^2 + b^2:
+//   v = a + u + u^2
+//   x = (v^4 + v^3 + b^2)^(1/3) + v
+//   y = u*x + v^2
+// followed by the change of variable y -> y + b to the curve with the term b*x.
+fn icart<P: CurveParams>(u: &Gf) -> (Gf, Gf) {
+    let v = P::A + *u + u.square();
+    let v2 = v.square();
+    let x = cbrt(&(v2.square() + v2 * v + P::B.square())) + v;
+    let y = *u * x + v2 + P::B;
+    (x, y)
+}
+
+// Doubles a point (x, y) with x != 0 on the curve y^2 + x*y = x^3 + a*x^2 + b*x.
+fn double<P: CurveParams>(x: &Gf, y: &Gf) -> (Gf, Gf) {
+    let lambda = *x + (*y + P::B) * x.invert();
+    let x3 = lambda.square() + lambda + P::A;
+    let y3 = lambda * (*x + x3) + x3 + *y;
+    (x3, y3)
+}
+
+// Maps u to a point Q of the curve, computes h*Q with `doublings` doublings, and
+// returns the group element h*Q + N.
+fn map_to_group<P: CurveParams>(u: &Gf, doublings: u32) -> Extended {
+    let (mut x, mut y) = icart::<P>(u);
+
+    // If Q has order 2 or 4, one of the multiples is N, which has x = 0, and h*Q is
+    // the point at infinity. The result is then N, the neutral of the group. The
+    // arithmetic on the invalid values continues, to stay constant time.
+    let mut is_exceptional = Choice::from(0);
+    for _ in 0..doublings {
+        is_exceptional |= x.is_zero();
+        (x, y) = double::<P>(&x, &y);
+    }
+    is_exceptional |= x.is_zero();
+
+    let (xn, yn) = P::add_n(&x, &y);
+
+    // s = y^2/x, which is x^2 + a*x + b + y on the curve
+    let s = xn.square() + P::A * xn + P::B + yn;
+    let point = Extended::from_affine(xn * P::INV_SQRT_B, s * P::INV_SQRT_B);
+    Extended::conditional_select(&point, &P::NEUTRAL, is_exceptional)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::affine::Backend;
+    use crate::test_utils::curve_tests;
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use subtle::ConstantTimeEq;
+
+    // RFC 9380 K.1
+    #[test]
+    fn expand_message_xmd_sha256() {
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        for (msg, expected) in [
+            (
+                &b""[..],
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                b"abc",
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+        ] {
+            let mut out = [0u8; 32];
+            expand_message_xmd(&[msg], dst, &mut out).unwrap();
+            assert_eq!(hex::encode(out), expected);
+        }
+
+        let mut out = [0u8; 0x80];
+        expand_message_xmd(&[b"", b""], dst, &mut out).unwrap();
+        assert_eq!(
+            hex::encode(out),
+            "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbe\
+             e0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18\
+             eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dc\
+             c541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced"
+        );
+
+        // the message is the concatenation of the parts
+        let mut split = [0u8; 32];
+        expand_message_xmd(&[b"a", b"", b"bc"], dst, &mut split).unwrap();
+        expand_message_xmd(&[b"abc"], dst, &mut out[..32]).unwrap();
+        assert_eq!(split, out[..32]);
+
+        // long tags are hashed
+        let long_dst = [b'x'; 256];
+        let hashed_dst = Sha256::new()
+            .chain_update(OVERSIZE_DST_PREFIX)
+            .chain_update(long_dst)
+            .finalize();
+        expand_message_xmd(&[b"abc"], &long_dst, &mut split).unwrap();
+        expand_message_xmd(&[b"abc"], &hashed_dst, &mut out[..32]).unwrap();
+        assert_eq!(split, out[..32]);
+
+        let mut too_long = [0u8; MAX_EXPAND_LEN + 1];
+        expand_message_xmd(&[b"abc"], dst, &mut too_long[..MAX_EXPAND_LEN]).unwrap();
+        assert_eq!(
+            expand_message_xmd(&[b"abc"], dst, &mut too_long),
+            Err(Error::InvalidLength {
+                expected: MAX_EXPAND_LEN,
+                actual: MAX_EXPAND_LEN + 1
+            })
+        );
+    }
+
+    fn check_map<C: Curve, P: CurveParams>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let doublings = C::COFACTOR.trailing_zeros();
+        for _ in 0..20 {
+            let mut bytes = [0u8; 30];
+            rng.fill_bytes(&mut bytes);
+            bytes[29] &= 1;
+            let (u, _) = Gf::decode(&bytes);
+
+            // Icart's map yields points on the curve with the term b*x
+            let (x, y) = icart::<P>(&u);
+            assert_eq!(
+                y.square() + x * y,
+                x.square() * x + P::A * x.square() + P::B * x
+            );
+
+            // the coordinates of the result are those of a decoded group element
+            let point = map_to_group::<P>(&u, doublings);
+            let repr = point.encode();
+            let (x, s, is_valid) = P::decode_affine(&repr);
+            assert!(bool::from(is_valid));
+            assert_eq!(x * P::INV_SQRT_B * point.z, point.x);
+            assert_eq!(s * P::INV_SQRT_B * point.z.square(), point.s);
+            assert!(!bool::from(point.is_neutral()));
+        }
+
+        // u and u + 1 yield the same v, and opposite points
+        let zero = map_to_group::<P>(&Gf::ZERO, doublings);
+        let one = map_to_group::<P>(&Gf::ONE, doublings);
+        assert!(bool::from(zero.add::<P>(&one).is_neutral()));
+    }
+
+    fn check_hash<C: HashToCurve>() {
+        let dst = b"xs233-hash-to-curve-test";
+        let p = hash_to_curve::<C>(&[b"message"], dst);
+        let q = encode_to_curve::<C>(&[b"message"], dst);
+        assert!(!bool::from(p.is_neutral()));
+        assert!(!bool::from(q.is_neutral()));
+        assert!(!bool::from(p.ct_eq(&q)));
+
+        // the output is a point of the group, which is deterministic, and depends on
+        // the message and the tag
+        let repr = p.to_encoded();
+        assert!(C::Point::try_decode(&repr).is_ok());
+        assert!(bool::from(
+            hash_to_curve::<C>(&[b"mess", b"age"], dst).ct_eq(&p)
+        ));
+        assert!(!bool::from(
+            hash_to_curve::<C>(&[b"message!"], dst).ct_eq(&p)
+        ));
+        assert!(!bool::from(
+            hash_to_curve::<C>(&[b"message"], b"another tag").ct_eq(&p)
+        ));
+
+        // the sum of the maps of the two field elements
+        let u = hash_to_field::<2>(&[b"message"], dst);
+        let mut sum = decode_point::<C>(&C::map_to_curve(&u[..1]));
+        sum.add_assign(&decode_point::<C>(&C::map_to_curve(&u[1..])));
+        assert!(bool::from(sum.ct_eq(&p)));
+        assert!(bool::from(
+            decode_point::<C>(&C::map_to_curve(&hash_to_field::<1>(&[b"message"], dst))).ct_eq(&q)
+        ));
+
+        let s = hash_to_scalar::<C>(&[b"message"], dst);
+        assert!(!bool::from(s.is_zero()));
+        assert_ne!(s, hash_to_scalar::<C>(&[b"message"], b"another tag"));
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn icart_map() { check_map::<C, <<C as Curve>::Point as Backend>::Params>(seed) }
+        fn hash_and_encode() { check_hash::<C>() }
+    }
+}
@@ -0,0 +1,860 @@
+//! Hybrid public key encryption (HPKE), as specified in RFC 9180, with a DHKEM over
+//! the xs233 groups.
+//!
+//! The KEM is the DHKEM of RFC 9180 §4.1 with HKDF-SHA256. Keys are the
+//! [`ecdh`](crate::ecdh) types, and public keys and encapsulated keys use the 30-byte
+//! encodings, so `Nenc`, `Npk` and `Nsk` are 30, and `Nsecret` is 32.
+//! [`derive_key_pair`] samples candidate scalars as in §7.1.3, but reads them in the
+//! little-endian scalar encoding of this crate, with the most significant byte masked
+//! to the bit length of the group order.
+//!
+//! The KEM ids of the xs233 groups are not registered with IANA, so ciphertexts only
+//! interoperate with other implementations that use the same ids. The KDF is
+//! HKDF-SHA256, and the AEAD is one of [`Aes128Gcm`], [`Aes256Gcm`] and
+//! [`ChaCha20Poly1305`].
+//!
+//! The four modes of HPKE are selected by the optional arguments of [`setup_sender`]
+//! and [`setup_receiver`]: a [`Psk`] for the PSK modes, and the static key of the
+//! sender for the authenticated modes.
+
+use crate::curve::Curve;
+use crate::ecdh::{PublicKey, StaticSecret};
+use crate::xsb233::Xsb233;
+use crate::xsk233::Xsk233;
+use crate::Error;
+use aes_gcm::aead::{Key, KeyInit, Nonce, Payload};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use hkdf::{Hkdf, HkdfExtract};
+use rand_core::CryptoRngCore;
+use sha2::Sha256;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+pub use aes_gcm::{Aes128Gcm, Aes256Gcm};
+pub use chacha20poly1305::ChaCha20Poly1305;
+
+/// The maximum length of an exported secret, 255 times the hash length.
+pub const MAX_EXPORT_LEN: usize = 255 * 32;
+
+// the id of HKDF-SHA256
+const KDF_ID: u16 = 0x0001;
+
+const VERSION_LABEL: &[u8] = b"HPKE-v1";
+
+/// The groups that the DHKEM is implemented for, [`Xsk233`] and [`Xsb233`].
+pub trait KemCurve: Curve {
+    /// The KEM id, which is bound into all derived keys. The ids of the xs233 groups
+    /// are not registered with IANA.
+    const KEM_ID: u16;
+}
+
+impl KemCurve for Xsk233 {
+    const KEM_ID: u16 = 0xfe33;
+}
+
+impl KemCurve for Xsb233 {
+    const KEM_ID: u16 = 0xfe34;
+}
+
+/// The AEADs that HPKE can use, with their ids from RFC 9180 §7.3.
+pub trait Aead: aes_gcm::aead::Aead + KeyInit {
+    /// The AEAD id.
+    const AEAD_ID: u16;
+}
+
+impl Aead for Aes128Gcm {
+    const AEAD_ID: u16 = 0x0001;
+}
+
+impl Aead for Aes256Gcm {
+    const AEAD_ID: u16 = 0x0002;
+}
+
+impl Aead for ChaCha20Poly1305 {
+    const AEAD_ID: u16 = 0x0003;
+}
+
+/// A pre-shared key and its id, for the PSK modes. Neither may be empty.
+#[derive(Clone, Copy)]
+pub struct Psk<'a> {
+    pub key: &'a [u8],
+    pub id: &'a [u8],
+}
+
+/// The shared secret of the KEM.
+pub struct SharedSecret([u8; 32]);
+
+impl SharedSecret {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SharedSecret {}
+
+fn kem_suite_id<C: KemCurve>() -> [u8; 5] {
+    let [hi, lo] = C::KEM_ID.to_be_bytes();
+    [b'K', b'E', b'M', hi, lo]
+}
+
+fn hpke_suite_id<C: KemCurve, A: Aead>() -> [u8; 10] {
+    let mut suite_id = [0u8; 10];
+    suite_id[..4].copy_from_slice(b"HPKE");
+    suite_id[4..6].copy_from_slice(&C::KEM_ID.to_be_bytes());
+    suite_id[6..8].copy_from_slice(&KDF_ID.to_be_bytes());
+    suite_id[8..].copy_from_slice(&A::AEAD_ID.to_be_bytes());
+    suite_id
+}
+
+fn labeled_extract(suite_id: &[u8], salt: &[u8], label: &[u8], ikm: &[u8]) -> [u8; 32] {
+    let mut extract = HkdfExtract::<Sha256>::new(Some(salt));
+    for part in [VERSION_LABEL, suite_id, label, ikm] {
+        extract.input_ikm(part);
+    }
+    extract.finalize().0.into()
+}
+
+fn labeled_expand(
+    suite_id: &[u8],
+    prk: &[u8; 32],
+    label: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), Error> {
+    let too_long = Error::InvalidLength {
+        expected: MAX_EXPORT_LEN,
+        actual: out.len(),
+    };
+    let length = u16::try_from(out.len()).map_err(|_| too_long)?;
+    Hkdf::<Sha256>::from_prk(prk)
+        .expect("the PRK has the length of the hash")
+        .expand_multi_info(
+            &[&length.to_be_bytes(), VERSION_LABEL, suite_id, label, info],
+            out,
+        )
+        .map_err(|_| too_long)
+}
+
+// the bits of the most significant byte of a 30-byte scalar that can be set in
+// scalars below the order
+fn top_byte_mask<C: Curve>() -> u8 {
+    let bits = 256 - C::ORDER[3].leading_zeros();
+    match bits {
+        ..=232 => 0,
+        240.. => 0xff,
+        _ => (1u8 << (bits - 232)) - 1,
+    }
+}
+
+/// Derives a key pair from the input keying material `ikm`, which should have at least
+/// 32 bytes of entropy.
+///
+/// Fails with [`Error::MappingFailed`] if none of the 256 candidates is a valid
+/// secret, which happens with negligible probability.
+pub fn derive_key_pair<C: KemCurve>(ikm: &[u8]) -> Result<(StaticSecret<C>, PublicKey<C>), Error> {
+    let suite_id = kem_suite_id::<C>();
+    let mut prk = labeled_extract(&suite_id, b"", b"dkp_prk", ikm);
+    let mask = top_byte_mask::<C>();
+
+    let mut result = Err(Error::MappingFailed);
+    for counter in 0..=255u8 {
+        let mut candidate = [0u8; 30];
+        labeled_expand(&suite_id, &prk, b"candidate", &[counter], &mut candidate)?;
+        candidate[29] &= mask;
+        let secret = StaticSecret::from_bytes(&candidate);
+        candidate.zeroize();
+        if let Ok(secret) = secret {
+            let public = PublicKey::from(&secret);
+            result = Ok((secret, public));
+            break;
+        }
+    }
+
+    prk.zeroize();
+    result
+}
+
+fn extract_and_expand<C: KemCurve>(dh: &[u8], kem_context: &[u8]) -> SharedSecret {
+    let suite_id = kem_suite_id::<C>();
+    let mut prk = labeled_extract(&suite_id, b"", b"eae_prk", dh);
+    let mut shared_secret = SharedSecret([0; 32]);
+    labeled_expand(
+        &suite_id,
+        &prk,
+        b"shared_secret",
+        kem_context,
+        &mut shared_secret.0,
+    )
+    .expect("the shared secret is shorter than the maximum");
+    prk.zeroize();
+    shared_secret
+}
+
+// Encap and AuthEncap with the given ephemeral secret. The DH outputs are never the
+// neutral, because public keys are not and secrets are not zero.
+fn encap_with<C: KemCurve>(
+    ephemeral: &StaticSecret<C>,
+    pk_r: &PublicKey<C>,
+    sk_s: Option<&StaticSecret<C>>,
+) -> (SharedSecret, [u8; 30]) {
+    let enc = PublicKey::from(ephemeral).to_bytes();
+
+    let mut dh = [0u8; 60];
+    let mut kem_context = [0u8; 90];
+    dh[..30].copy_from_slice(ephemeral.diffie_hellman(pk_r).as_bytes());
+    kem_context[..30].copy_from_slice(&enc);
+    kem_context[30..60].copy_from_slice(&pk_r.to_bytes());
+    let len = match sk_s {
+        Some(sk_s) => {
+            dh[30..].copy_from_slice(sk_s.diffie_hellman(pk_r).as_bytes());
+            kem_context[60..].copy_from_slice(&PublicKey::from(sk_s).to_bytes());
+            2
+        }
+        None => 1,
+    };
+
+    let shared_secret = extract_and_expand::<C>(&dh[..30 * len], &kem_context[..30 * (len + 1)]);
+    dh.zeroize();
+    (shared_secret, enc)
+}
+
+fn decap_with<C: KemCurve>(
+    enc: &[u8; 30],
+    sk_r: &StaticSecret<C>,
+    pk_s: Option<&PublicKey<C>>,
+) -> Result<SharedSecret, Error> {
+    let pk_e = PublicKey::<C>::from_bytes(enc)?;
+
+    let mut dh = [0u8; 60];
+    let mut kem_context = [0u8; 90];
+    dh[..30].copy_from_slice(sk_r.diffie_hellman(&pk_e).as_bytes());
+    kem_context[..30].copy_from_slice(enc);
+    kem_context[30..60].copy_from_slice(&PublicKey::from(sk_r).to_bytes());
+    let len = match pk_s {
+        Some(pk_s) => {
+            dh[30..].copy_from_slice(sk_r.diffie_hellman(pk_s).as_bytes());
+            kem_context[60..].copy_from_slice(&pk_s.to_bytes());
+            2
+        }
+        None => 1,
+    };
+
+    let shared_secret = extract_and_expand::<C>(&dh[..30 * len], &kem_context[..30 * (len + 1)]);
+    dh.zeroize();
+    Ok(shared_secret)
+}
+
+/// Generates a shared secret and its encapsulation to `pk_r`.
+pub fn encap<C: KemCurve>(
+    rng: &mut impl CryptoRngCore,
+    pk_r: &PublicKey<C>,
+) -> (SharedSecret, [u8; 30]) {
+    encap_with(&StaticSecret::random_from_rng(rng), pk_r, None)
+}
+
+/// Recovers the shared secret from its encapsulation `enc`.
+pub fn decap<C: KemCurve>(enc: &[u8; 30], sk_r: &StaticSecret<C>) -> Result<SharedSecret, Error> {
+    decap_with(enc, sk_r, None)
+}
+
+/// Generates a shared secret and its encapsulation to `pk_r`, authenticated with the
+/// static key of the sender.
+pub fn auth_encap<C: KemCurve>(
+    rng: &mut impl CryptoRngCore,
+    pk_r: &PublicKey<C>,
+    sk_s: &StaticSecret<C>,
+) -> (SharedSecret, [u8; 30]) {
+    encap_with(&StaticSecret::random_from_rng(rng), pk_r, Some(sk_s))
+}
+
+/// Recovers the shared secret from its encapsulation `enc`, which must have been
+/// created with the secret key of `pk_s`.
+pub fn auth_decap<C: KemCurve>(
+    enc: &[u8; 30],
+    sk_r: &StaticSecret<C>,
+    pk_s: &PublicKey<C>,
+) -> Result<SharedSecret, Error> {
+    decap_with(enc, sk_r, Some(pk_s))
+}
+
+// the outputs of the key schedule
+struct KeySchedule<A: Aead> {
+    key: Key<A>,
+    base_nonce: Nonce<A>,
+    exporter_secret: [u8; 32],
+}
+
+// The key schedule of RFC 9180 §5.1. The mode is derived from which of the optional
+// inputs are present.
+fn key_schedule<A: Aead>(
+    suite_id: &[u8],
+    shared_secret: &SharedSecret,
+    info: &[u8],
+    psk: Option<Psk<'_>>,
+    auth: bool,
+) -> Result<KeySchedule<A>, Error> {
+    let (psk, psk_id) = match psk {
+        Some(Psk { key, id }) if key.is_empty() || id.is_empty() => return Err(Error::InvalidPsk),
+        Some(Psk { key, id }) => (key, id),
+        None => (&[][..], &[][..]),
+    };
+    let mode = u8::from(!psk.is_empty()) | u8::from(auth) << 1;
+
+    let mut context = [0u8; 65];
+    context[0] = mode;
+    context[1..33].copy_from_slice(&labeled_extract(suite_id, b"", b"psk_id_hash", psk_id));
+    context[33..].copy_from_slice(&labeled_extract(suite_id, b"", b"info_hash", info));
+
+    let mut secret = labeled_extract(suite_id, &shared_secret.0, b"secret", psk);
+    let mut schedule = KeySchedule {
+        key: Key::<A>::default(),
+        base_nonce: Nonce::<A>::default(),
+        exporter_secret: [0; 32],
+    };
+    labeled_expand(suite_id, &secret, b"key", &context, &mut schedule.key)?;
+    labeled_expand(
+        suite_id,
+        &secret,
+        b"base_nonce",
+        &context,
+        &mut schedule.base_nonce,
+    )?;
+    labeled_expand(
+        suite_id,
+        &secret,
+        b"exp",
+        &context,
+        &mut schedule.exporter_secret,
+    )?;
+    secret.zeroize();
+
+    Ok(schedule)
+}
+
+struct Context<C: KemCurve, A: Aead> {
+    cipher: A,
+    base_nonce: Nonce<A>,
+    exporter_secret: [u8; 32],
+    seq: u64,
+    curve: PhantomData<C>,
+}
+
+impl<C: KemCurve, A: Aead> Context<C, A> {
+    fn new(
+        shared_secret: &SharedSecret,
+        info: &[u8],
+        psk: Option<Psk<'_>>,
+        auth: bool,
+    ) -> Result<Self, Error> {
+        let suite_id = hpke_suite_id::<C, A>();
+        let mut schedule = key_schedule::<A>(&suite_id, shared_secret, info, psk, auth)?;
+        let cipher = A::new(&schedule.key);
+        schedule.key.zeroize();
+        Ok(Context {
+            cipher,
+            base_nonce: schedule.base_nonce,
+            exporter_secret: schedule.exporter_secret,
+            seq: 0,
+            curve: PhantomData,
+        })
+    }
+
+    // The nonce of the current sequence number. The last sequence number is never
+    // used, so that incrementing it cannot overflow.
+    fn nonce(&self) -> Result<Nonce<A>, Error> {
+        if self.seq == u64::MAX {
+            return Err(Error::MessageLimitReached);
+        }
+        let mut nonce = self.base_nonce.clone();
+        let offset = nonce.len() - 8;
+        for (n, s) in nonce[offset..].iter_mut().zip(self.seq.to_be_bytes()) {
+            *n ^= s;
+        }
+        Ok(nonce)
+    }
+
+    fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce = self.nonce()?;
+        // the AEADs only fail for messages that exceed their length limits
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| Error::MessageLimitReached)?;
+        self.seq += 1;
+        Ok(ciphertext)
+    }
+
+    fn open(&mut self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce = self.nonce()?;
+        let plaintext = self
+            .cipher
+            .decrypt(
+                &nonce,
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| Error::DecryptionFailed)?;
+        self.seq += 1;
+        Ok(plaintext)
+    }
+
+    fn export(&self, exporter_context: &[u8], out: &mut [u8]) -> Result<(), Error> {
+        labeled_expand(
+            &hpke_suite_id::<C, A>(),
+            &self.exporter_secret,
+            b"sec",
+            exporter_context,
+            out,
+        )
+    }
+}
+
+impl<C: KemCurve, A: Aead> Drop for Context<C, A> {
+    fn drop(&mut self) {
+        self.base_nonce.zeroize();
+        self.exporter_secret.zeroize();
+    }
+}
+
+/// The context of the sender, which encrypts messages in order.
+pub struct SenderContext<C: KemCurve, A: Aead>(Context<C, A>);
+
+/// The context of the receiver, which decrypts messages in the order they were
+/// encrypted.
+pub struct ReceiverContext<C: KemCurve, A: Aead>(Context<C, A>);
+
+impl<C: KemCurve, A: Aead> SenderContext<C, A> {
+    /// Encrypts the next message, and authenticates `aad` along with it.
+    ///
+    /// Fails with [`Error::MessageLimitReached`] when the sequence numbers are used up,
+    /// or the message is too long for the AEAD.
+    pub fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        self.0.seal(aad, plaintext)
+    }
+
+    /// Fills `out` with a secret derived from the context and `exporter_context`.
+    ///
+    /// Fails with [`Error::InvalidLength`] if `out` is longer than
+    /// [`MAX_EXPORT_LEN`].
+    pub fn export(&self, exporter_context: &[u8], out: &mut [u8]) -> Result<(), Error> {
+        self.0.export(exporter_context, out)
+    }
+}
+
+impl<C: KemCurve, A: Aead> ReceiverContext<C, A> {
+    /// Decrypts the next message and checks `aad`.
+    ///
+    /// Fails with [`Error::DecryptionFailed`] if the ciphertext or `aad` were
+    /// modified, or the message is not the next one. The sequence number only
+    /// advances if decryption succeeds.
+    pub fn open(&mut self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        self.0.open(aad, ciphertext)
+    }
+
+    /// Fills `out` with a secret derived from the context and `exporter_context`, the
+    /// same as [`SenderContext::export`].
+    pub fn export(&self, exporter_context: &[u8], out: &mut [u8]) -> Result<(), Error> {
+        self.0.export(exporter_context, out)
+    }
+}
+
+fn setup_sender_with<C: KemCurve, A: Aead>(
+    ephemeral: &StaticSecret<C>,
+    pk_r: &PublicKey<C>,
+    info: &[u8],
+    psk: Option<Psk<'_>>,
+    sk_s: Option<&StaticSecret<C>>,
+) -> Result<([u8; 30], SenderContext<C, A>), Error> {
+    let (shared_secret, enc) = encap_with(ephemeral, pk_r, sk_s);
+    let context = Context::new(&shared_secret, info, psk, sk_s.is_some())?;
+    Ok((enc, SenderContext(context)))
+}
+
+/// Sets up the context of a sender that encrypts to `pk_r`, and returns it with the
+/// encapsulated key for the receiver.
+///
+/// Passing a `psk` selects the PSK mode, passing the static key `sk_s` of the sender
+/// selects the auth mode, and passing both selects the authPSK mode. Fails with
+/// [`Error::InvalidPsk`] if the key or the id of the `psk` is empty.
+pub fn setup_sender<C: KemCurve, A: Aead>(
+    rng: &mut impl CryptoRngCore,
+    pk_r: &PublicKey<C>,
+    info: &[u8],
+    psk: Option<Psk<'_>>,
+    sk_s: Option<&StaticSecret<C>>,
+) -> Result<([u8; 30], SenderContext<C, A>), Error> {
+    setup_sender_with(&StaticSecret::random_from_rng(rng), pk_r, info, psk, sk_s)
+}
+
+/// Sets up the context of a receiver from the encapsulated key `enc`.
+///
+/// The `info`, the `psk` and the public key `pk_s` of the sender must match the
+/// arguments of [`setup_sender`], otherwise decryption fails.
+pub fn setup_receiver<C: KemCurve, A: Aead>(
+    enc: &[u8; 30],
+    sk_r: &StaticSecret<C>,
+    info: &[u8],
+    psk: Option<Psk<'_>>,
+    pk_s: Option<&PublicKey<C>>,
+) -> Result<ReceiverContext<C, A>, Error> {
+    let shared_secret = decap_with(enc, sk_r, pk_s)?;
+    let context = Context::new(&shared_secret, info, psk, pk_s.is_some())?;
+    Ok(ReceiverContext(context))
+}
+
+/// Encrypts a single message to `pk_r`, in the mode selected as in [`setup_sender`].
+/// Returns the encapsulated key and the ciphertext.
+pub fn seal<C: KemCurve, A: Aead>(
+    rng: &mut impl CryptoRngCore,
+    pk_r: &PublicKey<C>,
+    info: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+    psk: Option<Psk<'_>>,
+    sk_s: Option<&StaticSecret<C>>,
+) -> Result<([u8; 30], Vec<u8>), Error> {
+    let (enc, mut context) = setup_sender::<C, A>(rng, pk_r, info, psk, sk_s)?;
+    Ok((enc, context.seal(aad, plaintext)?))
+}
+
+/// Decrypts a single message that was encrypted with [`seal`].
+pub fn open<C: KemCurve, A: Aead>(
+    enc: &[u8; 30],
+    sk_r: &StaticSecret<C>,
+    info: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    psk: Option<Psk<'_>>,
+    pk_s: Option<&PublicKey<C>>,
+) -> Result<Vec<u8>, Error> {
+    let mut context = setup_receiver::<C, A>(enc, sk_r, info, psk, pk_s)?;
+    context.open(aad, ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // the key schedule of RFC 9180 A.1.1, DHKEM(X25519, HKDF-SHA256), HKDF-SHA256,
+    // AES-128-GCM, which does not depend on the KEM
+    #[test]
+    fn rfc9180_key_schedule() {
+        let suite_id = hex::decode("48504b45002000010001").unwrap();
+        let shared_secret = SharedSecret(
+            hex::decode("fe0e18c9f024ce43799ae393c7e8fe8fce9d218875e8227b0187c04e7d2ea1fc")
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        );
+        let info = hex::decode("4f6465206f6e2061204772656369616e2055726e").unwrap();
+
+        let KeySchedule {
+            key,
+            base_nonce,
+            exporter_secret,
+        } = key_schedule::<Aes128Gcm>(&suite_id, &shared_secret, &info, None, false).unwrap();
+        assert_eq!(hex::encode(key), "4531685d41d65f03dc48f6b8302c05b0");
+        assert_eq!(hex::encode(base_nonce), "56d890e5accaaf011cff4b7d");
+        assert_eq!(
+            hex::encode(exporter_secret),
+            "45ff1c2e220db587171952c0592d5f5ebe103f1561a2614e38f2ffd47e99e3f8"
+        );
+
+        let mut context = Context::<Xsk233, Aes128Gcm> {
+            cipher: Aes128Gcm::new(&key),
+            base_nonce,
+            exporter_secret,
+            seq: 0,
+            curve: PhantomData,
+        };
+        let plaintext = hex::decode("4265617574792069732074727574682c20747275746820626561757479");
+        let ciphertext = context.seal(b"Count-0", &plaintext.unwrap()).unwrap();
+        assert_eq!(
+            hex::encode(ciphertext),
+            "f938558b5d72f1a23810b4be2ab4f84331acc02fc97babc53a52ae8218a355a96d8770ac83d07bea87e13c512a"
+        );
+
+        for (exporter_context, expected) in [
+            (
+                &b""[..],
+                "3853fe2b4035195a573ffc53856e77058e15d9ea064de3e59f4961d0095250ee",
+            ),
+            (
+                b"\0",
+                "2e8f0b54673c7029649d4eb9d5e33bf1872cf76d623ff164ac185da9e88c21a5",
+            ),
+            (
+                b"TestContext",
+                "e9e43065102c3836401bed8c3c3c75ae46be1639869391d62c61f1ec7af54931",
+            ),
+        ] {
+            let mut exported = [0u8; 32];
+            labeled_expand(
+                &suite_id,
+                &exporter_secret,
+                b"sec",
+                exporter_context,
+                &mut exported,
+            )
+            .unwrap();
+            assert_eq!(hex::encode(exported), expected);
+        }
+    }
+
+    struct Vectors {
+        sk_r: &'static str,
+        pk_r: &'static str,
+        enc: &'static str,
+        ciphertext: &'static str,
+        exported: &'static str,
+    }
+
+    // generated with this implementation, base mode with AES-128-GCM
+    const K233: Vectors = Vectors {
+        sk_r: "2560d4c999b7c509ac216d30c1919463b5d010f285d203e9b1611fb71f00",
+        pk_r: "c0dc4595b5959896ab6ba2e7ca93cbf2cf6d0da09c07baf9bc9d57794001",
+        enc: "f1a7637833ab691e544165ce72aa210f35b6572c6c7fdff73a554e5b8500",
+        ciphertext: "1b6271d398f5552b5675d19f6116f0482ea3e6389b84db6230",
+        exported: "497a4150d9bac675caeb044a2554d0f4b9b35f4fd75f9731ea14d638826b6edb",
+    };
+
+    const B233: Vectors = Vectors {
+        sk_r: "c04d98beaccf9fad2f8cad882f73401d2d923f4ab1b6b779f27ac3a77700",
+        pk_r: "b64d5c4d346e5e54618ce5bee23700d9fd61048cb87ddcba87c58a7f1601",
+        enc: "3b7fc0625a1d0033cf4dc18207495876ef95e2165a1b663b68ee37044800",
+        ciphertext: "1cf9556d3043da128f031577c47ec13b7cee3f3dfc3a630efb",
+        exported: "e6cb854c2e75d9369e5a8496e8b67cf7976ce942129246542ac63d114f2c7d19",
+    };
+
+    fn check_vectors<C: KemCurve>(vectors: &Vectors) {
+        let (sk_r, pk_r) = derive_key_pair::<C>(b"xs233 hpke test recipient").unwrap();
+        assert_eq!(hex::encode(sk_r.to_bytes()), vectors.sk_r);
+        assert_eq!(hex::encode(pk_r.to_bytes()), vectors.pk_r);
+
+        let (ephemeral, _) = derive_key_pair::<C>(b"xs233 hpke test ephemeral").unwrap();
+        let (enc, mut sender) =
+            setup_sender_with::<C, Aes128Gcm>(&ephemeral, &pk_r, b"info", None, None).unwrap();
+        assert_eq!(hex::encode(enc), vectors.enc);
+        let ciphertext = sender.seal(b"aad", b"plaintext").unwrap();
+        assert_eq!(hex::encode(&ciphertext), vectors.ciphertext);
+        let mut exported = [0u8; 32];
+        sender.export(b"context", &mut exported).unwrap();
+        assert_eq!(hex::encode(exported), vectors.exported);
+
+        let mut receiver =
+            setup_receiver::<C, Aes128Gcm>(&enc, &sk_r, b"info", None, None).unwrap();
+        assert_eq!(receiver.open(b"aad", &ciphertext).unwrap(), b"plaintext");
+    }
+
+    fn check_kem<C: KemCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let sk_r = StaticSecret::<C>::random_from_rng(&mut rng);
+        let pk_r = PublicKey::from(&sk_r);
+        let sk_s = StaticSecret::<C>::random_from_rng(&mut rng);
+        let pk_s = PublicKey::from(&sk_s);
+
+        let (shared_secret, enc) = encap(&mut rng, &pk_r);
+        assert_eq!(
+            decap(&enc, &sk_r).unwrap().as_bytes(),
+            shared_secret.as_bytes()
+        );
+
+        let (auth_shared_secret, enc) = auth_encap(&mut rng, &pk_r, &sk_s);
+        assert_eq!(
+            auth_decap(&enc, &sk_r, &pk_s).unwrap().as_bytes(),
+            auth_shared_secret.as_bytes()
+        );
+        // the secret depends on the sender key
+        assert_ne!(
+            decap(&enc, &sk_r).unwrap().as_bytes(),
+            auth_shared_secret.as_bytes()
+        );
+        assert_ne!(
+            auth_decap(&enc, &sk_r, &pk_r).unwrap().as_bytes(),
+            auth_shared_secret.as_bytes()
+        );
+    }
+
+    fn check_derive_key_pair<C: KemCurve>(seed: u8) {
+        // derived key pairs are deterministic
+        let (sk, pk) = derive_key_pair::<C>(&[seed; 32]).unwrap();
+        let (sk2, pk2) = derive_key_pair::<C>(&[seed; 32]).unwrap();
+        assert_eq!(sk.to_bytes(), sk2.to_bytes());
+        assert_eq!(pk, pk2);
+        assert_ne!(derive_key_pair::<C>(&[seed; 33]).unwrap().1, pk);
+    }
+
+    fn check_invalid_enc<C: KemCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let sk_r = StaticSecret::<C>::random_from_rng(&mut rng);
+
+        // invalid encapsulated keys
+        assert_eq!(decap(&[0; 30], &sk_r).err(), Some(Error::IdentityPoint));
+        let mut w_is_one = [0u8; 30];
+        w_is_one[0] = 1;
+        assert_eq!(decap(&w_is_one, &sk_r).err(), Some(Error::InvalidEncoding));
+    }
+
+    fn check_modes<C: KemCurve, A: Aead>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let sk_r = StaticSecret::<C>::random_from_rng(&mut rng);
+        let pk_r = PublicKey::from(&sk_r);
+        let sk_s = StaticSecret::<C>::random_from_rng(&mut rng);
+        let pk_s = PublicKey::from(&sk_s);
+        let psk = Psk {
+            key: b"a pre-shared key of at least 32 bytes",
+            id: b"psk id",
+        };
+
+        for (psk, auth) in [
+            (None, false),
+            (Some(psk), false),
+            (None, true),
+            (Some(psk), true),
+        ] {
+            let sk_s = auth.then_some(&sk_s);
+            let pk_s = auth.then_some(&pk_s);
+            let (enc, mut sender) =
+                setup_sender::<C, A>(&mut rng, &pk_r, b"info", psk, sk_s).unwrap();
+            let mut receiver = setup_receiver::<C, A>(&enc, &sk_r, b"info", psk, pk_s).unwrap();
+
+            let first = sender.seal(b"aad 0", b"first message").unwrap();
+            let second = sender.seal(b"aad 1", b"second message").unwrap();
+            assert_ne!(first, second);
+
+            // out of order, or modified
+            assert_eq!(
+                receiver.open(b"aad 1", &second),
+                Err(Error::DecryptionFailed)
+            );
+            assert_eq!(
+                receiver.open(b"aad 1", &first),
+                Err(Error::DecryptionFailed)
+            );
+            let mut modified = first.clone();
+            modified[0] ^= 1;
+            assert_eq!(
+                receiver.open(b"aad 0", &modified),
+                Err(Error::DecryptionFailed)
+            );
+
+            assert_eq!(receiver.open(b"aad 0", &first).unwrap(), b"first message");
+            assert_eq!(receiver.open(b"aad 1", &second).unwrap(), b"second message");
+
+            let mut sent = [0u8; 64];
+            let mut received = [0u8; 64];
+            sender.export(b"context", &mut sent).unwrap();
+            receiver.export(b"context", &mut received).unwrap();
+            assert_eq!(sent, received);
+
+            // mismatched inputs of the receiver
+            let other_psk = Psk {
+                key: b"another pre-shared key",
+                id: b"psk id",
+            };
+            let (enc, ciphertext) =
+                seal::<C, A>(&mut rng, &pk_r, b"info", b"aad", b"message", psk, sk_s).unwrap();
+            assert_eq!(
+                open::<C, A>(&enc, &sk_r, b"info", b"aad", &ciphertext, psk, pk_s).unwrap(),
+                b"message"
+            );
+            for (info, psk, pk_s) in [
+                (&b"other info"[..], psk, pk_s),
+                (b"info", psk.xor(Some(other_psk)), pk_s),
+                (b"info", psk, pk_s.xor(Some(&pk_r))),
+            ] {
+                assert_eq!(
+                    open::<C, A>(&enc, &sk_r, info, b"aad", &ciphertext, psk, pk_s),
+                    Err(Error::DecryptionFailed)
+                );
+            }
+        }
+
+        // empty pre-shared keys or ids
+        for psk in [
+            Psk {
+                key: b"",
+                id: b"id",
+            },
+            Psk {
+                key: b"key",
+                id: b"",
+            },
+        ] {
+            assert!(matches!(
+                setup_sender::<C, A>(&mut rng, &pk_r, b"info", Some(psk), None),
+                Err(Error::InvalidPsk)
+            ));
+        }
+    }
+
+    fn check_limits<C: KemCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let sk_r = StaticSecret::<C>::random_from_rng(&mut rng);
+        let pk_r = PublicKey::from(&sk_r);
+        let (enc, mut sender) =
+            setup_sender::<C, ChaCha20Poly1305>(&mut rng, &pk_r, b"", None, None).unwrap();
+        let mut receiver =
+            setup_receiver::<C, ChaCha20Poly1305>(&enc, &sk_r, b"", None, None).unwrap();
+
+        // the nonce is the base nonce xor the big-endian sequence number
+        sender.0.seq = u64::MAX - 1;
+        receiver.0.seq = u64::MAX - 1;
+        let mut nonce = sender.0.base_nonce;
+        nonce[4..].iter_mut().for_each(|n| *n ^= 0xff);
+        nonce[11] ^= 1;
+        assert_eq!(sender.0.nonce().unwrap(), nonce);
+
+        let ciphertext = sender.seal(b"", b"last message").unwrap();
+        assert_eq!(receiver.open(b"", &ciphertext).unwrap(), b"last message");
+        assert_eq!(sender.seal(b"", b""), Err(Error::MessageLimitReached));
+        assert_eq!(
+            receiver.open(b"", &ciphertext),
+            Err(Error::MessageLimitReached)
+        );
+
+        let mut exported = [0u8; MAX_EXPORT_LEN + 1];
+        sender.export(b"", &mut exported[..MAX_EXPORT_LEN]).unwrap();
+        assert_eq!(
+            sender.export(b"", &mut exported),
+            Err(Error::InvalidLength {
+                expected: MAX_EXPORT_LEN,
+                actual: MAX_EXPORT_LEN + 1
+            })
+        );
+    }
+
+    #[test]
+    fn vectors() {
+        check_vectors::<Xsk233>(&K233);
+        check_vectors::<Xsb233>(&B233);
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn kem() { check_kem::<C>(seed) }
+        fn deterministic_key_pairs() { check_derive_key_pair::<C>(seed) }
+        fn invalid_enc() { check_invalid_enc::<C>(seed) }
+        fn modes_aes_128_gcm() { check_modes::<C, Aes128Gcm>(seed) }
+        fn modes_aes_256_gcm() { check_modes::<C, Aes256Gcm>(seed) }
+        fn modes_chacha20_poly1305() { check_modes::<C, ChaCha20Poly1305>(seed) }
+        fn limits() { check_limits::<C>(seed) }
+    }
+}
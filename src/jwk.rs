@@ -0,0 +1,324 @@
+//! JSON Web Keys (RFC 7517) for the keys in [`crate::keys`].
+//!
+//! The [`KeyFormat`] selects the key type, as for PKCS#8 documents:
+//!
+//! - [`KeyFormat::Sec1`] keys have the `kty` `EC` and the `crv` `K-233` or `B-233`,
+//!   with the big-endian coordinates `x` and `y` of the standard curve and the
+//!   big-endian scalar `d`, as in RFC 7518, section 6.2.
+//! - [`KeyFormat::Native`] keys have the `kty` `OKP` and the `crv` `xsk233` or
+//!   `xsb233`, with the 30-byte point encoding `x` and the 30-byte scalar `d`, in the
+//!   style of RFC 8037.
+//!
+//! None of these curve names are registered with IANA. Decoding accepts both key
+//! types, validates points through [`Point::decode`](crate::Point::decode) and
+//! scalars as canonical and non-zero, and checks that the public members of a private
+//! key match its secret scalar.
+//!
+//! [`Jwk`] implements `Serialize` and `Deserialize`, so it can be converted from and
+//! to JSON with e.g. serde_json. Members that this crate does not use, such as `kid`,
+//! are ignored.
+
+use crate::keys::{KeyCurve, KeyFormat, PublicKey, SecretKey};
+use crate::Error;
+use alloc::string::String;
+use alloc::vec::Vec;
+use base64ct::{Base64UrlUnpadded, Encoding};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+/// A JSON Web Key, with the members used by this crate. All byte strings are
+/// base64url encoded without padding.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    /// The key type, `EC` or `OKP`.
+    pub kty: String,
+    /// The name of the curve.
+    pub crv: String,
+    /// The x coordinate of `EC` keys, or the point encoding of `OKP` keys.
+    pub x: String,
+    /// The y coordinate of `EC` keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    /// The secret scalar of private keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+}
+
+impl Jwk {
+    /// Returns whether the key has the private member `d`.
+    pub fn is_private(&self) -> bool {
+        self.d.is_some()
+    }
+
+    /// Returns a copy of the key without the private member `d`.
+    pub fn to_public(&self) -> Jwk {
+        Jwk {
+            kty: self.kty.clone(),
+            crv: self.crv.clone(),
+            x: self.x.clone(),
+            y: self.y.clone(),
+            d: None,
+        }
+    }
+
+    fn format<C: KeyCurve>(&self) -> Result<KeyFormat, Error> {
+        match (self.kty.as_str(), self.crv.as_str()) {
+            ("EC", crv) if crv == C::STANDARD_NAME => Ok(KeyFormat::Sec1),
+            ("OKP", crv) if crv == C::NAME => Ok(KeyFormat::Native),
+            _ => Err(Error::InvalidKey),
+        }
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    Base64UrlUnpadded::encode_string(bytes)
+}
+
+fn decode(value: &str) -> Result<Zeroizing<Vec<u8>>, Error> {
+    Base64UrlUnpadded::decode_vec(value)
+        .map(Zeroizing::new)
+        .map_err(|_| Error::InvalidKey)
+}
+
+impl<C: KeyCurve> PublicKey<C> {
+    /// Converts the key to a JWK of the given format.
+    pub fn to_jwk(&self, format: KeyFormat) -> Jwk {
+        match format {
+            KeyFormat::Sec1 => {
+                let sec1 = self.to_sec1_bytes();
+                Jwk {
+                    kty: "EC".into(),
+                    crv: C::STANDARD_NAME.into(),
+                    x: encode(&sec1[1..31]),
+                    y: Some(encode(&sec1[31..])),
+                    d: None,
+                }
+            }
+            KeyFormat::Native => Jwk {
+                kty: "OKP".into(),
+                crv: C::NAME.into(),
+                x: encode(&self.to_bytes()),
+                y: None,
+                d: None,
+            },
+        }
+    }
+
+    /// Decodes the public members of a public or private JWK.
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, Error> {
+        let format = jwk.format::<C>()?;
+        let x = decode(&jwk.x)?;
+        match (format, &jwk.y) {
+            (KeyFormat::Sec1, Some(y)) => {
+                let y = decode(y)?;
+                // the coordinates must have the full length
+                if x.len() != 30 || y.len() != 30 {
+                    return Err(Error::InvalidLength {
+                        expected: 30,
+                        actual: if x.len() != 30 { x.len() } else { y.len() },
+                    });
+                }
+                let mut sec1 = [0u8; 61];
+                sec1[0] = 0x04;
+                sec1[1..31].copy_from_slice(&x);
+                sec1[31..].copy_from_slice(&y);
+                PublicKey::decode(format, &sec1)
+            }
+            (KeyFormat::Native, None) => PublicKey::decode(format, &x),
+            _ => Err(Error::InvalidKey),
+        }
+    }
+}
+
+impl<C: KeyCurve> SecretKey<C> {
+    /// Converts the key to a private JWK of the given format, which includes the
+    /// public members.
+    pub fn to_jwk(&self, format: KeyFormat) -> Jwk {
+        let mut jwk = self.public_key().to_jwk(format);
+        jwk.d = Some(match format {
+            KeyFormat::Sec1 => {
+                let mut scalar = self.to_sec1_bytes();
+                let d = encode(&scalar);
+                scalar.as_mut_slice().zeroize();
+                d
+            }
+            KeyFormat::Native => encode(&Zeroizing::new(self.to_bytes())[..]),
+        });
+        jwk
+    }
+
+    /// Decodes a private JWK. The public members must match the secret scalar.
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, Error> {
+        let format = jwk.format::<C>()?;
+        let scalar = decode(jwk.d.as_deref().ok_or(Error::InvalidKey)?)?;
+        let secret_key = SecretKey::decode(format, &scalar)?;
+        if PublicKey::from_jwk(jwk)? != *secret_key.public_key() {
+            return Err(Error::InvalidKey);
+        }
+        Ok(secret_key)
+    }
+}
+
+impl core::fmt::Debug for Jwk {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Jwk")
+            .field("kty", &self.kty)
+            .field("crv", &self.crv)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .field("d", &self.d.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl Drop for Jwk {
+    fn drop(&mut self) {
+        self.d.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use crate::xsk233::Xsk233;
+    use pkcs8::DecodePublicKey;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // the public key of the K-233 vector in crate::keys
+    const K233_SPKI: &str = "3052301006072a8648ce3d020106052b8104001a033e0004004a5539cfd9ed97b25365da52d50fdadf4ae89f7a8608be4fb697cf256401ab6e53bb6c1ebe02596c1d47cec5b07f0b0fde2d5c64d3a5524532ebf6";
+
+    #[test]
+    fn vector() {
+        let spki = hex::decode(K233_SPKI).unwrap();
+        let public_key = PublicKey::<Xsk233>::from_public_key_der(&spki).unwrap();
+        let json = serde_json::to_string(&public_key.to_jwk(KeyFormat::Sec1)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kty":"EC","crv":"K-233","x":"AEpVOc_Z7ZeyU2XaUtUP2t9K6J96hgi-T7aXzyVk","y":"AatuU7tsHr4CWWwdR87FsH8LD94tXGTTpVJFMuv2"}"#
+        );
+    }
+
+    fn check_roundtrip<C: KeyCurve, Other: KeyCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret_key = SecretKey::<C>::random_from_rng(&mut rng);
+        let public_key = *secret_key.public_key();
+
+        for format in [KeyFormat::Sec1, KeyFormat::Native] {
+            // round trips through JSON
+            let jwk = secret_key.to_jwk(format);
+            assert!(jwk.is_private());
+            let json = serde_json::to_string(&jwk).unwrap();
+            let decoded: Jwk = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, jwk);
+            let decoded = SecretKey::<C>::from_jwk(&decoded).unwrap();
+            assert_eq!(decoded.to_bytes(), secret_key.to_bytes());
+            assert_eq!(PublicKey::<C>::from_jwk(&jwk), Ok(public_key));
+
+            let jwk = public_key.to_jwk(format);
+            assert_eq!(jwk, secret_key.to_jwk(format).to_public());
+            assert_eq!(PublicKey::<C>::from_jwk(&jwk), Ok(public_key));
+            assert_eq!(
+                SecretKey::<C>::from_jwk(&jwk).err(),
+                Some(Error::InvalidKey)
+            );
+            assert_eq!(PublicKey::<Other>::from_jwk(&jwk), Err(Error::InvalidKey));
+        }
+    }
+
+    fn check_members<C: KeyCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret_key = SecretKey::<C>::random_from_rng(&mut rng);
+        let public_key = *secret_key.public_key();
+
+        // the member names and encodings
+        let jwk = secret_key.to_jwk(KeyFormat::Native);
+        assert_eq!(jwk.kty, "OKP");
+        assert_eq!(jwk.crv, C::NAME);
+        assert_eq!(*decode(&jwk.x).unwrap(), public_key.to_bytes());
+        assert_eq!(
+            *decode(jwk.d.as_ref().unwrap()).unwrap(),
+            secret_key.to_bytes()
+        );
+        let jwk = secret_key.to_jwk(KeyFormat::Sec1);
+        assert_eq!(jwk.kty, "EC");
+        assert_eq!(jwk.crv, C::STANDARD_NAME);
+        assert_eq!(
+            *decode(jwk.d.as_ref().unwrap()).unwrap(),
+            secret_key.to_sec1_bytes().as_slice()
+        );
+        assert!(format!("{jwk:?}").contains("<redacted>"));
+
+        // unknown members are ignored
+        let mut json: serde_json::Value = serde_json::to_value(&jwk).unwrap();
+        json["kid"] = "key-1".into();
+        let decoded: Jwk = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, jwk);
+    }
+
+    fn check_invalid_points<C: KeyCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret_key = SecretKey::<C>::random_from_rng(&mut rng);
+        let public_key = *secret_key.public_key();
+
+        // invalid points are rejected through Point::decode
+        let mut jwk = public_key.to_jwk(KeyFormat::Native);
+        jwk.x = encode(&[0; 30]);
+        assert_eq!(PublicKey::<C>::from_jwk(&jwk), Err(Error::IdentityPoint));
+        let mut w_is_one = [0u8; 30];
+        w_is_one[0] = 1;
+        jwk.x = encode(&w_is_one);
+        assert_eq!(PublicKey::<C>::from_jwk(&jwk), Err(Error::InvalidEncoding));
+        jwk.x = encode(&[0; 29]);
+        assert_eq!(
+            PublicKey::<C>::from_jwk(&jwk),
+            Err(Error::InvalidLength {
+                expected: 30,
+                actual: 29
+            })
+        );
+        jwk.x = "not base64!".into();
+        assert_eq!(PublicKey::<C>::from_jwk(&jwk), Err(Error::InvalidKey));
+
+        let mut jwk = public_key.to_jwk(KeyFormat::Sec1);
+        let mut y = decode(jwk.y.as_ref().unwrap()).unwrap();
+        y[29] ^= 1;
+        jwk.y = Some(encode(&y));
+        assert_eq!(PublicKey::<C>::from_jwk(&jwk), Err(Error::InvalidEncoding));
+        jwk.y = None;
+        assert_eq!(PublicKey::<C>::from_jwk(&jwk), Err(Error::InvalidKey));
+    }
+
+    fn check_invalid_scalars<C: KeyCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let secret_key = SecretKey::<C>::random_from_rng(&mut rng);
+
+        // invalid scalars, and public members of another key
+        let mut jwk = secret_key.to_jwk(KeyFormat::Native);
+        jwk.d = Some(encode(&[0; 30]));
+        assert_eq!(
+            SecretKey::<C>::from_jwk(&jwk).err(),
+            Some(Error::ZeroScalar)
+        );
+        jwk.d = Some(encode(&[0xff; 30]));
+        assert_eq!(
+            SecretKey::<C>::from_jwk(&jwk).err(),
+            Some(Error::NonCanonicalScalar)
+        );
+        let other = SecretKey::<C>::random_from_rng(&mut rng);
+        jwk.d = other.to_jwk(KeyFormat::Native).d.clone();
+        assert_eq!(
+            SecretKey::<C>::from_jwk(&jwk).err(),
+            Some(Error::InvalidKey)
+        );
+    }
+
+    curve_tests! {
+        |C, Other, seed|
+        fn roundtrip() { check_roundtrip::<C, Other>(seed) }
+        fn members() { check_members::<C>(seed) }
+        fn invalid_points() { check_invalid_points::<C>(seed) }
+        fn invalid_scalars() { check_invalid_scalars::<C>(seed) }
+    }
+}
@@ -1,7 +1,63 @@
+#![cfg_attr(not(test), no_std)]
+#![deny(clippy::undocumented_unsafe_blocks)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(not(any(feature = "ffi", feature = "pure-rust")))]
+compile_error!("either the ffi or the pure-rust feature must be enabled");
+
+pub mod affine;
+#[cfg(feature = "alloc")]
+pub mod batch;
+#[cfg(feature = "cose")]
+mod cose;
+pub mod curve;
+#[cfg(feature = "ecdh")]
+pub mod ecdh;
+#[cfg(feature = "ecdsa")]
+pub mod ecdsa;
+#[cfg(feature = "ecies")]
+pub mod ecies;
+mod error;
+mod gf233;
+#[cfg(feature = "hash-to-curve")]
+pub mod hash_to_curve;
+#[cfg(feature = "hpke")]
+pub mod hpke;
+#[cfg(feature = "jwk")]
+pub mod jwk;
+#[cfg(feature = "pkcs8")]
+pub mod keys;
+pub mod lookup;
+#[cfg(feature = "oprf")]
+pub mod oprf;
+#[cfg(feature = "pure-rust")]
+pub mod pure;
+#[cfg(not(feature = "pure-rust"))]
+mod pure;
+#[cfg(test)]
+mod reference;
 pub mod scalar;
+#[cfg(feature = "schnorr")]
+pub mod schnorr;
+#[cfg(feature = "ecdsa")]
+mod sec1;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod vartime;
+#[cfg(feature = "vrf")]
+pub mod vrf;
 pub mod xsb233;
 pub mod xsk233;
 
+pub use curve::Curve;
+pub use error::Error;
+
+/// A group element.
+///
+/// The [`Default`] value is the neutral for all point types of this crate, on both
+/// backends.
 pub trait Point:
     core::fmt::Debug
     + Default
@@ -28,38 +84,103 @@ pub trait Point:
     fn sub_assign(&mut self, rhs: &Self);
     fn neg(&mut self, point: &Self);
     fn neg_inplace(&mut self);
+    /// Sets `self` to `-point` if `choice` is set and to `point` otherwise, in
+    /// constant time. The FFI backend uses the conditional negation of c-xs233, and
+    /// [`LookupTable::select_signed`](lookup::LookupTable::select_signed) is built on
+    /// this.
+    fn condneg(&mut self, point: &Self, choice: subtle::Choice) {
+        *self = *point;
+        self.condneg_inplace(choice);
+    }
+    fn condneg_inplace(&mut self, choice: subtle::Choice) {
+        self.conditional_negate(choice);
+    }
     fn double(&mut self, point: &Self);
     fn double_inplace(&mut self);
+    /// Sets `self` to the unique point `Q` such that `2*Q = point`.
+    fn halve(&mut self, point: &Self);
+    fn halve_inplace(&mut self) {
+        let point = *self;
+        self.halve(&point);
+    }
     fn xdouble(&mut self, point: &Self, n: u32);
     fn xdouble_inplace(&mut self, n: u32);
+
+    /// Returns the encoding of the point.
+    fn to_encoded(&self) -> Self::EncodedPoint
+    where
+        Self::EncodedPoint: Default,
+    {
+        let mut repr = Self::EncodedPoint::default();
+        self.encode(&mut repr);
+        repr
+    }
+
+    /// Decodes a point, or returns [`Error::InvalidEncoding`].
+    fn try_decode(repr: &Self::EncodedPoint) -> Result<Self, Error> {
+        let mut point = Self::default();
+        if point.decode(repr).into() {
+            Ok(point)
+        } else {
+            Err(Error::InvalidEncoding)
+        }
+    }
+
+    /// Like [`Point::try_decode`], but also rejects the neutral with
+    /// [`Error::IdentityPoint`], e.g. for public keys.
+    fn try_decode_non_neutral(repr: &Self::EncodedPoint) -> Result<Self, Error> {
+        let point = Self::try_decode(repr)?;
+        if point.is_neutral().into() {
+            Err(Error::IdentityPoint)
+        } else {
+            Ok(point)
+        }
+    }
 }
 
 // this is a janky way to do it and it might not be very good actually
 // it for sure isn't constant time
 //   well, rejection sampling never is
 // it is not really injective
-// it may even loop forever if you are very unlucky
+// it panics if none of the 256 candidates is valid, see
+// try_map_uniform_bytes_to_curve for a version that returns an error instead
 pub fn map_uniform_bytes_to_curve<const L: usize, P: Point<EncodedPoint = [u8; L]>>(
-    mut data: [u8; L],
+    data: [u8; L],
 ) -> P {
+    try_map_uniform_bytes_to_curve(data).expect("no valid point among the candidates")
+}
+
+/// Like [`map_uniform_bytes_to_curve`], but returns [`Error::MappingFailed`] if
+/// none of the 256 candidate encodings is valid, which happens with probability
+/// about 2^-256.
+pub fn try_map_uniform_bytes_to_curve<const L: usize, P: Point<EncodedPoint = [u8; L]>>(
+    mut data: [u8; L],
+) -> Result<P, Error> {
     let mut out = P::default();
-    let mut ctr = 0u8;
-    let mut is_valid: bool = false;
 
     // this works for the two points in this crate but is not truly generic
     data[L - 1] &= 1;
 
-    while !is_valid {
+    for ctr in 0..=255u8 {
         data[0] ^= ctr;
-        is_valid = out.decode(&data).into();
+        let is_valid: bool = out.decode(&data).into();
         data[0] ^= ctr;
-        ctr += 1;
+
+        if is_valid {
+            return Ok(out);
+        }
     }
 
-    out
+    Err(Error::MappingFailed)
 }
 
-// The macro impl_ops! implements arithmetic and comparison for types that implement Point
+// The macro impl_ops! implements arithmetic, comparison and formatting for types that
+// implement Point. The formatting and parsing impls require that the EncodedPoint is a
+// byte array. Together with ConditionallySelectable, the impl of Neg for references
+// provides ConditionallyNegatable through the blanket impl in subtle, so the type must
+// not implement it itself; types that did so before 0.3.0 must drop that impl. A
+// dedicated constant-time negation can be provided by overriding Point::condneg and
+// Point::condneg_inplace instead.
 #[macro_export]
 macro_rules! impl_ops {
     ($type:ty) => {
@@ -67,11 +188,30 @@ macro_rules! impl_ops {
             type Output = Self;
 
             fn add(mut self, rhs: Self) -> Self::Output {
-                self.add_assign(&rhs);
+                <$type as $crate::Point>::add_assign(&mut self, &rhs);
                 self
             }
         }
 
+        impl ::core::ops::Add<&$type> for $type {
+            type Output = Self;
+
+            fn add(mut self, rhs: &$type) -> Self::Output {
+                <$type as $crate::Point>::add_assign(&mut self, rhs);
+                self
+            }
+        }
+
+        impl ::core::ops::Add<$type> for &$type {
+            type Output = $type;
+
+            fn add(self, mut rhs: $type) -> Self::Output {
+                // addition is commutative
+                <$type as $crate::Point>::add_assign(&mut rhs, self);
+                rhs
+            }
+        }
+
         impl ::core::ops::Add for &$type {
             type Output = $type;
 
@@ -88,15 +228,40 @@ macro_rules! impl_ops {
             }
         }
 
+        impl ::core::ops::AddAssign<&$type> for $type {
+            fn add_assign(&mut self, rhs: &$type) {
+                <Self as $crate::Point>::add_assign(self, rhs);
+            }
+        }
+
         impl ::core::ops::Sub for $type {
             type Output = Self;
 
             fn sub(mut self, rhs: Self) -> Self::Output {
-                self.sub_assign(&rhs);
+                <$type as $crate::Point>::sub_assign(&mut self, &rhs);
+                self
+            }
+        }
+
+        impl ::core::ops::Sub<&$type> for $type {
+            type Output = Self;
+
+            fn sub(mut self, rhs: &$type) -> Self::Output {
+                <$type as $crate::Point>::sub_assign(&mut self, rhs);
                 self
             }
         }
 
+        impl ::core::ops::Sub<$type> for &$type {
+            type Output = $type;
+
+            fn sub(self, rhs: $type) -> Self::Output {
+                let mut out = <$type as ::core::default::Default>::default();
+                <$type as $crate::Point>::sub(&mut out, self, &rhs);
+                out
+            }
+        }
+
         impl ::core::ops::Sub for &$type {
             type Output = $type;
 
@@ -113,20 +278,52 @@ macro_rules! impl_ops {
             }
         }
 
+        impl ::core::ops::SubAssign<&$type> for $type {
+            fn sub_assign(&mut self, rhs: &$type) {
+                <Self as $crate::Point>::sub_assign(self, rhs);
+            }
+        }
+
         impl ::core::ops::Neg for $type {
             type Output = Self;
 
             fn neg(mut self) -> Self::Output {
-                self.neg_inplace();
+                <$type as $crate::Point>::neg_inplace(&mut self);
                 self
             }
         }
 
+        impl ::core::ops::Neg for &$type {
+            type Output = $type;
+
+            fn neg(self) -> Self::Output {
+                let mut out = <$type as ::core::default::Default>::default();
+                <$type as $crate::Point>::neg(&mut out, self);
+                out
+            }
+        }
+
+        impl ::core::iter::Sum for $type {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(*<$type as $crate::Point>::neutral(), |acc, point| {
+                    acc + point
+                })
+            }
+        }
+
+        impl<'a> ::core::iter::Sum<&'a $type> for $type {
+            fn sum<I: Iterator<Item = &'a $type>>(iter: I) -> Self {
+                iter.fold(*<$type as $crate::Point>::neutral(), |acc, point| {
+                    acc + point
+                })
+            }
+        }
+
         impl<const N: usize> ::core::ops::Mul<$type> for $crate::scalar::Scalar<N> {
             type Output = $type;
 
             fn mul(self, mut rhs: $type) -> $type {
-                rhs.mul_inplace(&self);
+                <$type as $crate::Point>::mul_inplace(&mut rhs, &self);
                 rhs
             }
         }
@@ -135,7 +332,7 @@ macro_rules! impl_ops {
             type Output = $type;
 
             fn mul(self, mut rhs: $type) -> $type {
-                rhs.mul_inplace(self);
+                <$type as $crate::Point>::mul_inplace(&mut rhs, self);
                 rhs
             }
         }
@@ -160,27 +357,240 @@ macro_rules! impl_ops {
             }
         }
 
+        impl<const N: usize> ::core::ops::Mul<$crate::scalar::Scalar<N>> for $type {
+            type Output = $type;
+
+            fn mul(mut self, rhs: $crate::scalar::Scalar<N>) -> $type {
+                <$type as $crate::Point>::mul_inplace(&mut self, &rhs);
+                self
+            }
+        }
+
+        impl<const N: usize> ::core::ops::Mul<&$crate::scalar::Scalar<N>> for $type {
+            type Output = $type;
+
+            fn mul(mut self, rhs: &$crate::scalar::Scalar<N>) -> $type {
+                <$type as $crate::Point>::mul_inplace(&mut self, rhs);
+                self
+            }
+        }
+
+        impl<const N: usize> ::core::ops::Mul<$crate::scalar::Scalar<N>> for &$type {
+            type Output = $type;
+
+            fn mul(self, rhs: $crate::scalar::Scalar<N>) -> $type {
+                let mut out = <$type as ::core::default::Default>::default();
+                <$type as $crate::Point>::mul(&mut out, self, &rhs);
+                out
+            }
+        }
+
+        impl<const N: usize> ::core::ops::Mul<&$crate::scalar::Scalar<N>> for &$type {
+            type Output = $type;
+
+            fn mul(self, rhs: &$crate::scalar::Scalar<N>) -> $type {
+                let mut out = <$type as ::core::default::Default>::default();
+                <$type as $crate::Point>::mul(&mut out, self, rhs);
+                out
+            }
+        }
+
+        impl<const N: usize> ::core::ops::MulAssign<$crate::scalar::Scalar<N>> for $type {
+            fn mul_assign(&mut self, rhs: $crate::scalar::Scalar<N>) {
+                <$type as $crate::Point>::mul_inplace(self, &rhs);
+            }
+        }
+
+        impl<const N: usize> ::core::ops::MulAssign<&$crate::scalar::Scalar<N>> for $type {
+            fn mul_assign(&mut self, rhs: &$crate::scalar::Scalar<N>) {
+                <$type as $crate::Point>::mul_inplace(self, rhs);
+            }
+        }
+
         impl ::core::cmp::PartialEq for $type {
             fn eq(&self, other: &Self) -> bool {
-                self.ct_eq(other).unwrap_u8() == 1
+                ::subtle::ConstantTimeEq::ct_eq(self, other).unwrap_u8() == 1
             }
         }
 
         impl ::core::cmp::Eq for $type {}
+
+        // the internal limbs are not canonical, so hashing and ordering is done on the
+        // encoding. the order has no arithmetic meaning and is not constant time.
+        impl ::core::hash::Hash for $type {
+            fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                let mut buf =
+                    <<$type as $crate::Point>::EncodedPoint as ::core::default::Default>::default();
+                <$type as $crate::Point>::encode(self, &mut buf);
+                ::core::convert::AsRef::<[u8]>::as_ref(&buf).hash(state);
+            }
+        }
+
+        impl ::core::cmp::Ord for $type {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                let mut lhs =
+                    <<$type as $crate::Point>::EncodedPoint as ::core::default::Default>::default();
+                let mut rhs =
+                    <<$type as $crate::Point>::EncodedPoint as ::core::default::Default>::default();
+                <$type as $crate::Point>::encode(self, &mut lhs);
+                <$type as $crate::Point>::encode(other, &mut rhs);
+
+                let lhs: &[u8] = ::core::convert::AsRef::as_ref(&lhs);
+                let rhs: &[u8] = ::core::convert::AsRef::as_ref(&rhs);
+                lhs.cmp(rhs)
+            }
+        }
+
+        impl ::core::cmp::PartialOrd for $type {
+            fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        // the hex string is formatted into a buffer first, so that width, fill,
+        // alignment and the alternate flag (`{:#x}`) are honored.
+        impl ::core::fmt::LowerHex for $type {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let mut buf =
+                    <<$type as $crate::Point>::EncodedPoint as ::core::default::Default>::default();
+                <$type as $crate::Point>::encode(self, &mut buf);
+
+                let mut hex =
+                    [0u8; 2 * ::core::mem::size_of::<<$type as $crate::Point>::EncodedPoint>()];
+                let hex = $crate::__hex_encode(::core::convert::AsRef::as_ref(&buf), &mut hex)?;
+                f.pad_integral(true, "0x", hex)
+            }
+        }
+
+        impl ::core::fmt::Display for $type {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let mut buf =
+                    <<$type as $crate::Point>::EncodedPoint as ::core::default::Default>::default();
+                <$type as $crate::Point>::encode(self, &mut buf);
+
+                let mut hex =
+                    [0u8; 2 * ::core::mem::size_of::<<$type as $crate::Point>::EncodedPoint>()];
+                f.pad($crate::__hex_encode(
+                    ::core::convert::AsRef::as_ref(&buf),
+                    &mut hex,
+                )?)
+            }
+        }
+
+        // the internal representation is projective, so equal points may have
+        // different limbs. we print the canonical encoding instead.
+        impl ::core::fmt::Debug for $type {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_tuple(::core::stringify!($type))
+                    .field(&::core::format_args!("{:x}", self))
+                    .finish()
+            }
+        }
+
+        impl ::core::convert::TryFrom<&[u8]> for $type {
+            type Error = $crate::Error;
+
+            fn try_from(bytes: &[u8]) -> ::core::result::Result<Self, $crate::Error> {
+                let mut buf =
+                    <<$type as $crate::Point>::EncodedPoint as ::core::default::Default>::default();
+                let dst = ::core::convert::AsMut::<[u8]>::as_mut(&mut buf);
+                if bytes.len() != dst.len() {
+                    return Err($crate::Error::InvalidLength {
+                        expected: dst.len(),
+                        actual: bytes.len(),
+                    });
+                }
+                dst.copy_from_slice(bytes);
+
+                <$type as $crate::Point>::try_decode(&buf)
+            }
+        }
+
+        impl ::core::str::FromStr for $type {
+            type Err = $crate::ParsePointError;
+
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                let mut buf =
+                    <<$type as $crate::Point>::EncodedPoint as ::core::default::Default>::default();
+                let dst = ::core::convert::AsMut::<[u8]>::as_mut(&mut buf);
+                let src = s.as_bytes();
+
+                if src.len() != 2 * dst.len() {
+                    return Err($crate::ParsePointError::InvalidLength);
+                }
+
+                for (byte, pair) in dst.iter_mut().zip(src.chunks_exact(2)) {
+                    let hi = (pair[0] as char)
+                        .to_digit(16)
+                        .ok_or($crate::ParsePointError::InvalidHexDigit)?;
+                    let lo = (pair[1] as char)
+                        .to_digit(16)
+                        .ok_or($crate::ParsePointError::InvalidHexDigit)?;
+                    *byte = (hi << 4 | lo) as u8;
+                }
+
+                let mut point = <$type as ::core::default::Default>::default();
+                if <$type as $crate::Point>::decode(&mut point, &buf).into() {
+                    Ok(point)
+                } else {
+                    Err($crate::ParsePointError::InvalidEncoding)
+                }
+            }
+        }
     };
 }
 
+// Writes the lower-case hex encoding of bytes to the start of out, for the
+// formatting impls of impl_ops!. Fails if out is too short.
+#[doc(hidden)]
+pub fn __hex_encode<'a>(bytes: &[u8], out: &'a mut [u8]) -> Result<&'a str, core::fmt::Error> {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let out = out.get_mut(..2 * bytes.len()).ok_or(core::fmt::Error)?;
+    for (pair, byte) in out.chunks_exact_mut(2).zip(bytes) {
+        pair[0] = DIGITS[(byte >> 4) as usize];
+        pair[1] = DIGITS[(byte & 0x0f) as usize];
+    }
+    core::str::from_utf8(out).map_err(|_| core::fmt::Error)
+}
+
+/// The error returned when parsing a point from a hex string fails. It converts
+/// into [`Error`], so `?` works in functions that return the crate error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParsePointError {
+    /// The string does not contain exactly two hex digits per encoded byte.
+    InvalidLength,
+    /// The string contains a character that is not a hex digit.
+    InvalidHexDigit,
+    /// The decoded bytes are not a valid point encoding.
+    InvalidEncoding,
+}
+
+impl core::fmt::Display for ParsePointError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParsePointError::InvalidLength => f.write_str("invalid length of hex encoded point"),
+            ParsePointError::InvalidHexDigit => f.write_str("invalid hex digit in encoded point"),
+            ParsePointError::InvalidEncoding => f.write_str("invalid point encoding"),
+        }
+    }
+}
+
+impl core::error::Error for ParsePointError {}
+
 // c-xs233 uses 0xffffffff for true and 0 for false
 // to implement constant time functionality. In order
 // to be compatible with the rust ecosystem we want to
 // use subtle::Choice, which uses 1 and 0, so we need
 // to convert between the two (in constant time).
 
+#[cfg(feature = "ffi")]
 fn to_choice(val: u32) -> subtle::Choice {
     let zero_or_one = val.wrapping_neg();
     subtle::Choice::from(zero_or_one as u8)
 }
 
+#[cfg(feature = "ffi")]
 fn from_choice(choice: subtle::Choice) -> u32 {
     let zero_or_one = choice.unwrap_u8() as u32;
     zero_or_one.wrapping_neg()
@@ -188,9 +598,103 @@ fn from_choice(choice: subtle::Choice) -> u32 {
 
 #[cfg(test)]
 mod tests {
+    use crate::scalar::Scalar;
+    use crate::Point;
     use rand::{Rng, SeedableRng};
     use rand_chacha::ChaCha8Rng;
 
+    // checks that all operator variants from impl_ops! agree with the Point methods
+    macro_rules! check_ops {
+        ($type:ty, $seed:expr) => {{
+            let mut rng = ChaCha8Rng::from_seed([$seed; 32]);
+            let mut buf = [0u8; 30];
+            let points: Vec<$type> = (0..5)
+                .map(|_| {
+                    rng.fill(&mut buf);
+                    <$type>::mulgen(&Scalar::new(buf))
+                })
+                .collect();
+            rng.fill(&mut buf);
+            let k = Scalar::new(buf);
+            let (a, b) = (points[0], points[1]);
+            assert_eq!(<$type>::default(), *<$type>::neutral());
+
+            let mut sum = <$type>::default();
+            sum.add(&a, &b);
+            assert_eq!(a + b, sum);
+            assert_eq!(a + &b, sum);
+            assert_eq!(&a + b, sum);
+            assert_eq!(&a + &b, sum);
+            let mut assigned = a;
+            assigned += b;
+            assert_eq!(assigned, sum);
+            let mut assigned = a;
+            assigned += &b;
+            assert_eq!(assigned, sum);
+
+            let mut diff = <$type>::default();
+            diff.sub(&a, &b);
+            assert_eq!(a - b, diff);
+            assert_eq!(a - &b, diff);
+            assert_eq!(&a - b, diff);
+            assert_eq!(&a - &b, diff);
+            let mut assigned = a;
+            assigned -= b;
+            assert_eq!(assigned, diff);
+            let mut assigned = a;
+            assigned -= &b;
+            assert_eq!(assigned, diff);
+
+            let mut neg = <$type>::default();
+            neg.neg(&a);
+            assert_eq!(-a, neg);
+            assert_eq!(-&a, neg);
+
+            let mut product = <$type>::default();
+            product.mul(&a, &k);
+            assert_eq!(k * a, product);
+            assert_eq!(a * k, product);
+            assert_eq!(a * &k, product);
+            assert_eq!(&a * k, product);
+            assert_eq!(&a * &k, product);
+            let mut assigned = a;
+            assigned *= k;
+            assert_eq!(assigned, product);
+            let mut assigned = a;
+            assigned *= &k;
+            assert_eq!(assigned, product);
+
+            let mut expected = *<$type>::neutral();
+            for point in points.iter() {
+                expected.add_assign(point);
+            }
+            assert_eq!(points.iter().sum::<$type>(), expected);
+            assert_eq!(points.into_iter().sum::<$type>(), expected);
+            assert_eq!(
+                core::iter::empty::<$type>().sum::<$type>(),
+                *<$type>::neutral()
+            );
+        }};
+    }
+
+    #[test]
+    fn try_map_to_curve() {
+        let mut buf = [0u8; 30];
+        let mut rng = ChaCha8Rng::from_seed([24u8; 32]);
+
+        for _ in 0..100 {
+            rng.fill(&mut buf);
+            let point: crate::xsb233::Xsb233Point = crate::map_uniform_bytes_to_curve(buf);
+            assert_eq!(crate::try_map_uniform_bytes_to_curve(buf), Ok(point));
+        }
+    }
+
+    #[test]
+    fn ops() {
+        check_ops!(crate::xsk233::Xsk233Point, 1);
+        check_ops!(crate::xsb233::Xsb233Point, 2);
+    }
+
     #[test]
     fn map_to_curve_100k_elements() {
         let mut buf = [0u8; 30];
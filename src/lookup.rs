@@ -0,0 +1,189 @@
+#[cfg(any(test, feature = "pure-rust", feature = "alloc"))]
+use crate::scalar::Scalar;
+use crate::Point;
+use subtle::{Choice, ConstantTimeEq};
+
+/// A table of points with constant-time lookups, for windowed algorithms.
+///
+/// The lookups scan the whole table and use the `ConditionallySelectable` impl and
+/// [`Point::condneg_inplace`] of the point, so neither the index nor the digit leak
+/// through timing or memory accesses.
+#[derive(Clone, Copy, Debug)]
+pub struct LookupTable<P: Point, const W: usize>([P; W]);
+
+impl<P: Point + 'static, const W: usize> LookupTable<P, W> {
+    /// Builds the table of the multiples `1*point, 2*point, ..., W*point`, for use
+    /// with [`LookupTable::select_signed`].
+    pub fn new(point: &P) -> Self {
+        let mut entries = [*point; W];
+        for i in 1..W {
+            let (prev, rest) = entries.split_at_mut(i);
+            rest[0].add(&prev[i - 1], point);
+        }
+        LookupTable(entries)
+    }
+
+    /// Returns the entry at `index`, or the neutral if `index` is out of bounds.
+    pub fn select(&self, index: u32) -> P {
+        let mut out = *P::neutral();
+        for (i, entry) in self.0.iter().enumerate() {
+            out.conditional_assign(entry, (i as u32).ct_eq(&index));
+        }
+        out
+    }
+
+    /// Returns `digit*point` for a table built with [`LookupTable::new`], i.e. the
+    /// entry at `|digit| - 1`, negated if `digit` is negative, or the neutral if
+    /// `digit` is zero. Digits with `|digit| > W` also return the neutral.
+    pub fn select_signed(&self, digit: i8) -> P {
+        // constant-time absolute value and sign
+        let mask = digit >> 7;
+        let abs = (digit ^ mask).wrapping_sub(mask) as u8;
+        let is_negative = Choice::from((mask & 1) as u8);
+
+        let mut out = *P::neutral();
+        for (i, entry) in self.0.iter().enumerate() {
+            out.conditional_assign(entry, ((i + 1) as u8).ct_eq(&abs));
+        }
+        out.condneg_inplace(is_negative);
+        out
+    }
+}
+
+impl<P: Point, const W: usize> From<[P; W]> for LookupTable<P, W> {
+    fn from(entries: [P; W]) -> Self {
+        LookupTable(entries)
+    }
+}
+
+// Recodes each byte of the scalar into two signed 4-bit digits in [-8, 8),
+// low digit first, and returns the final carry.
+#[cfg(any(test, feature = "pure-rust", feature = "alloc"))]
+pub(crate) fn recode_signed_4<const N: usize>(scalar: &Scalar<N>) -> ([[i8; 2]; N], i8) {
+    let mut digits = [[0i8; 2]; N];
+    let mut carry = 0u8;
+    for (byte, digits) in scalar.as_bytes().iter().zip(digits.iter_mut()) {
+        for (i, digit) in digits.iter_mut().enumerate() {
+            let nibble = ((byte >> (4 * i)) & 0x0f) + carry;
+            carry = (nibble + 8) >> 4;
+            *digit = nibble as i8 - ((carry << 4) as i8);
+        }
+    }
+    (digits, carry as i8)
+}
+
+// Constant-time multiplication with a signed 4-bit window, using a table of
+// 1*P..8*P.
+#[cfg(any(test, feature = "pure-rust"))]
+pub(crate) fn mul_signed_window<P: Point + 'static, const N: usize>(
+    point: &P,
+    scalar: &Scalar<N>,
+) -> P {
+    let table = LookupTable::<P, 8>::new(point);
+    let (digits, carry) = recode_signed_4(scalar);
+
+    let mut acc = table.select_signed(carry);
+    for digits in digits.iter().rev() {
+        for digit in digits.iter().rev() {
+            acc.xdouble_inplace(4);
+            acc.add_assign(&table.select_signed(*digit));
+        }
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mul_signed_window, recode_signed_4, LookupTable};
+    use crate::scalar::Scalar;
+    use crate::test_utils::curve_tests;
+    use crate::{Curve, Point};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    fn random_point<P: Point>(rng: &mut ChaCha8Rng) -> P {
+        let mut buf = [0u8; 30];
+        rng.fill(&mut buf);
+        P::mulgen(&Scalar::new(buf))
+    }
+
+    fn check_select<P: Point + PartialEq + 'static>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let point: P = random_point(&mut rng);
+
+        let table = LookupTable::<P, 8>::new(&point);
+        for index in 0..10u32 {
+            let mut expected = *P::neutral();
+            if index < 8 {
+                expected.mul(&point, &Scalar::new([index as u8 + 1]));
+            }
+            assert!(table.select(index) == expected);
+        }
+    }
+
+    fn check_select_signed<P: Point + PartialEq + 'static>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let point: P = random_point(&mut rng);
+
+        let table = LookupTable::<P, 8>::new(&point);
+        for digit in -10i8..=10 {
+            let mut expected = P::default();
+            expected.mul(&point, &Scalar::new([digit.unsigned_abs()]));
+            if digit < 0 {
+                expected.neg_inplace();
+            }
+            if digit.unsigned_abs() > 8 {
+                expected = *P::neutral();
+            }
+            assert!(table.select_signed(digit) == expected, "{digit}");
+        }
+        assert!(table.select_signed(i8::MIN) == *P::neutral());
+    }
+
+    fn check_mul_signed_window<P: Point + PartialEq + 'static>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let point: P = random_point(&mut rng);
+
+        let mut buf = [0u8; 30];
+        for _ in 0..10 {
+            rng.fill(&mut buf);
+            let scalar = Scalar::new(buf);
+            let mut expected = P::default();
+            expected.mul(&point, &scalar);
+            assert!(mul_signed_window(&point, &scalar) == expected);
+
+            let short: Scalar<5> = Scalar::new(buf[..5].try_into().unwrap());
+            expected.mul(&point, &short);
+            assert!(mul_signed_window(&point, &short) == expected);
+        }
+
+        let all_ones = Scalar::new([0xff; 29]);
+        let mut expected = P::default();
+        expected.mul(&point, &all_ones);
+        assert!(mul_signed_window(&point, &all_ones) == expected);
+    }
+
+    #[test]
+    fn recode() {
+        let mut rng = ChaCha8Rng::from_seed([3; 32]);
+        for _ in 0..100 {
+            let value: u32 = rng.gen();
+            let (digits, carry) = recode_signed_4(&Scalar::new(value.to_le_bytes()));
+
+            let mut recoded = (carry as i64) << 32;
+            for (i, digits) in digits.iter().enumerate() {
+                assert!(digits.iter().all(|digit| (-8..8).contains(digit)));
+                recoded += (digits[0] as i64) << (8 * i);
+                recoded += (digits[1] as i64) << (8 * i + 4);
+            }
+            assert_eq!(recoded, value as i64);
+        }
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn select() { check_select::<<C as Curve>::Point>(seed) }
+        fn select_signed() { check_select_signed::<<C as Curve>::Point>(seed) }
+        fn mul() { check_mul_signed_window::<<C as Curve>::Point>(seed) }
+    }
+}
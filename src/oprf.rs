@@ -0,0 +1,1203 @@
+//! Oblivious pseudorandom functions, following RFC 9497.
+//!
+//! A client learns the output of a PRF keyed by the server on an input of its choice,
+//! without the server learning the input or the output. The client hashes the input
+//! to a point, blinds it with a random scalar and sends the [`Element`] to the
+//! server, which multiplies it with its secret key. The client removes the blind and
+//! hashes the result to the 32-byte output. All three modes of RFC 9497 are
+//! implemented, each with a server and a client type:
+//!
+//! - [`OprfServer`] and [`OprfClient`], the base mode.
+//! - [`VoprfServer`] and [`VoprfClient`], in which the server proves with a DLEQ
+//!   proof that it used the key of its public key. Many elements can be evaluated
+//!   with a single proof.
+//! - [`PoprfServer`] and [`PoprfClient`], in which the PRF additionally depends on
+//!   public info that both parties know, and the proof is for the public key tweaked
+//!   by the info.
+//!
+//! The ciphersuite of a group is its [`OprfCurve::IDENTIFIER`], with the hashes of
+//! [`crate::hash_to_curve`] as `HashToGroup` and `HashToScalar`, and SHA-256 as
+//! `Hash`. Elements are the 30-byte point encodings, and are never the neutral.
+//! Scalars are the 30-byte little-endian encodings. RFC 9497 does not define suites
+//! for binary curves, so the identifiers are not registered, and the outputs are not
+//! interoperable with other implementations.
+
+use crate::curve::Curve;
+use crate::hash_to_curve::{hash_to_curve, hash_to_scalar, HashToCurve};
+use crate::scalar::ModScalar;
+use crate::xsb233::Xsb233;
+use crate::xsk233::Xsk233;
+use crate::{Error, Point};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// The length of an encoded proof in bytes.
+pub const PROOF_LEN: usize = 60;
+
+/// The length of the output in bytes.
+pub const OUTPUT_LEN: usize = 32;
+
+/// The length of the seed of [`OprfServer::derive_key_pair`] and the other servers.
+pub const SEED_LEN: usize = 32;
+
+const MODE_OPRF: u8 = 0x00;
+const MODE_VOPRF: u8 = 0x01;
+const MODE_POPRF: u8 = 0x02;
+
+// the length prefixes of serialized elements and of hashes
+const ELEMENT_LEN_PREFIX: [u8; 2] = [0, 30];
+const HASH_LEN_PREFIX: [u8; 2] = [0, 32];
+
+/// The groups that the OPRF is implemented for, [`Xsk233`] and [`Xsb233`].
+pub trait OprfCurve: HashToCurve {
+    /// The identifier of the ciphersuite, which is bound into all hashes. The
+    /// identifiers of the xs233 groups are not registered with IANA.
+    const IDENTIFIER: &'static str;
+}
+
+impl OprfCurve for Xsk233 {
+    const IDENTIFIER: &'static str = "xsk233-SHA256";
+}
+
+impl OprfCurve for Xsb233 {
+    const IDENTIFIER: &'static str = "xsb233-SHA256";
+}
+
+// a prefix followed by the contextString of RFC 9497 §3.1
+struct Dst {
+    bytes: [u8; 64],
+    len: usize,
+}
+
+impl Dst {
+    fn new<C: OprfCurve>(prefix: &[u8], mode: u8) -> Self {
+        let mut bytes = [0u8; 64];
+        let mut len = 0;
+        for part in [prefix, b"OPRFV1-", &[mode], b"-", C::IDENTIFIER.as_bytes()] {
+            bytes[len..len + part.len()].copy_from_slice(part);
+            len += part.len();
+        }
+        Dst { bytes, len }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+// the I2OSP(len(x), 2) prefix of RFC 9497, for inputs that can be longer
+fn len_prefix(bytes: &[u8]) -> Result<[u8; 2], Error> {
+    u16::try_from(bytes.len())
+        .map(u16::to_be_bytes)
+        .map_err(|_| Error::InvalidInput)
+}
+
+// the batch index is encoded in two bytes
+fn check_batch_len(len: usize) -> Result<(), Error> {
+    if len > 1 << 16 {
+        return Err(Error::InvalidInput);
+    }
+    Ok(())
+}
+
+fn check_same_len(expected: usize, actual: usize) -> Result<(), Error> {
+    if expected != actual {
+        return Err(Error::InvalidLength { expected, actual });
+    }
+    Ok(())
+}
+
+// k*P, clearing the encoding of k afterwards
+fn mul<C: Curve>(point: &C::Point, k: &ModScalar<C>) -> C::Point {
+    let mut scalar = k.to_scalar();
+    let mut out = C::Point::default();
+    out.mul(point, &scalar);
+    scalar.zeroize();
+    out
+}
+
+fn mulgen<C: Curve>(k: &ModScalar<C>) -> C::Point {
+    let mut scalar = k.to_scalar();
+    let out = C::Point::mulgen(&scalar);
+    scalar.zeroize();
+    out
+}
+
+// HashToGroup, which rejects the neutral as required by Blind and Evaluate
+fn hash_to_group<C: OprfCurve>(mode: u8, input: &[u8]) -> Result<C::Point, Error> {
+    let point = hash_to_curve::<C>(&[input], Dst::new::<C>(b"HashToGroup-", mode).as_bytes());
+    if point.is_neutral().into() {
+        return Err(Error::InvalidInput);
+    }
+    Ok(point)
+}
+
+fn hash_to_scalar_with_context<C: OprfCurve>(mode: u8, msg: &[&[u8]]) -> ModScalar<C> {
+    hash_to_scalar::<C>(msg, Dst::new::<C>(b"HashToScalar-", mode).as_bytes())
+}
+
+// DeriveKeyPair of RFC 9497 §3.2.1
+fn derive_secret<C: OprfCurve>(
+    mode: u8,
+    seed: &[u8; SEED_LEN],
+    info: &[u8],
+) -> Result<ModScalar<C>, Error> {
+    let info_len = len_prefix(info)?;
+    let dst = Dst::new::<C>(b"DeriveKeyPair", mode);
+    for counter in 0..=u8::MAX {
+        let secret = hash_to_scalar::<C>(&[seed, &info_len, info, &[counter]], dst.as_bytes());
+        if !bool::from(secret.is_zero()) {
+            return Ok(secret);
+        }
+    }
+    // only reachable with negligible probability
+    Err(Error::ZeroScalar)
+}
+
+// the scalar m of RFC 9497 §3.3.3, which tweaks the key of the POPRF mode
+fn tweak<C: OprfCurve>(info: &[u8]) -> Result<ModScalar<C>, Error> {
+    let info_len = len_prefix(info)?;
+    Ok(hash_to_scalar_with_context::<C>(
+        MODE_POPRF,
+        &[b"Info", &info_len, info],
+    ))
+}
+
+// the hash of the unblinded element in Finalize and Evaluate. the info is only
+// hashed in the POPRF mode.
+fn finalize_hash<C: OprfCurve>(
+    input: &[u8],
+    info: Option<&[u8]>,
+    unblinded: &C::Point,
+) -> Result<[u8; OUTPUT_LEN], Error> {
+    let mut hasher = Sha256::new();
+    hasher.update(len_prefix(input)?);
+    hasher.update(input);
+    if let Some(info) = info {
+        hasher.update(len_prefix(info)?);
+        hasher.update(info);
+    }
+    hasher.update(ELEMENT_LEN_PREFIX);
+    hasher.update(unblinded.to_encoded());
+    hasher.update(b"Finalize");
+    Ok(hasher.finalize().into())
+}
+
+// ComputeComposites of RFC 9497 §2.2.1. the server passes its key, and computes Z
+// as k*M like ComputeCompositesFast.
+fn compute_composites<C: OprfCurve>(
+    mode: u8,
+    k: Option<&ModScalar<C>>,
+    b: &Element<C>,
+    c: &[Element<C>],
+    d: &[Element<C>],
+) -> (C::Point, C::Point) {
+    let seed_dst = Dst::new::<C>(b"Seed-", mode);
+    let seed: [u8; 32] = Sha256::new()
+        .chain_update(ELEMENT_LEN_PREFIX)
+        .chain_update(b.bytes)
+        .chain_update((seed_dst.len as u16).to_be_bytes())
+        .chain_update(seed_dst.as_bytes())
+        .finalize()
+        .into();
+
+    let mut m = *C::Point::neutral();
+    let mut z = *C::Point::neutral();
+    for (i, (ci, di)) in c.iter().zip(d).enumerate() {
+        let weight = hash_to_scalar_with_context::<C>(
+            mode,
+            &[
+                &HASH_LEN_PREFIX,
+                &seed,
+                &(i as u16).to_be_bytes(),
+                &ELEMENT_LEN_PREFIX,
+                &ci.bytes,
+                &ELEMENT_LEN_PREFIX,
+                &di.bytes,
+                b"Composite",
+            ],
+        );
+        m.add_assign(&mul::<C>(&ci.point, &weight));
+        if k.is_none() {
+            z.add_assign(&mul::<C>(&di.point, &weight));
+        }
+    }
+    if let Some(k) = k {
+        z = mul::<C>(&m, k);
+    }
+    (m, z)
+}
+
+fn challenge<C: OprfCurve>(mode: u8, b: &Element<C>, points: [&C::Point; 4]) -> ModScalar<C> {
+    let [m, z, t2, t3] = points.map(|point| point.to_encoded());
+    hash_to_scalar_with_context::<C>(
+        mode,
+        &[
+            &ELEMENT_LEN_PREFIX,
+            &b.bytes,
+            &ELEMENT_LEN_PREFIX,
+            &m,
+            &ELEMENT_LEN_PREFIX,
+            &z,
+            &ELEMENT_LEN_PREFIX,
+            &t2,
+            &ELEMENT_LEN_PREFIX,
+            &t3,
+            b"Challenge",
+        ],
+    )
+}
+
+// GenerateProof of RFC 9497 §2.2.1, that B = k*G and D[i] = k*C[i]
+fn generate_proof<C: OprfCurve>(
+    mode: u8,
+    rng: &mut impl CryptoRngCore,
+    k: &ModScalar<C>,
+    b: &Element<C>,
+    c: &[Element<C>],
+    d: &[Element<C>],
+) -> Proof<C> {
+    let (m, z) = compute_composites(mode, Some(k), b, c, d);
+    let mut r = ModScalar::random(rng);
+    let t2 = mulgen::<C>(&r);
+    let t3 = mul::<C>(&m, &r);
+
+    let c = challenge(mode, b, [&m, &z, &t2, &t3]);
+    let s = r - c * *k;
+    r.zeroize();
+    Proof { c, s }
+}
+
+// VerifyProof of RFC 9497 §2.2.2
+fn verify_proof<C: OprfCurve>(
+    mode: u8,
+    b: &Element<C>,
+    c: &[Element<C>],
+    d: &[Element<C>],
+    proof: &Proof<C>,
+) -> Result<(), Error> {
+    let (m, z) = compute_composites(mode, None, b, c, d);
+
+    // t2 = s*G + c*B and t3 = s*M + c*Z
+    let mut t2 = mulgen::<C>(&proof.s);
+    t2.add_assign(&mul::<C>(&b.point, &proof.c));
+    let mut t3 = mul::<C>(&m, &proof.s);
+    t3.add_assign(&mul::<C>(&z, &proof.c));
+
+    if challenge(mode, b, [&m, &z, &t2, &t3]) == proof.c {
+        Ok(())
+    } else {
+        Err(Error::VerificationFailed)
+    }
+}
+
+fn blind<C: OprfCurve>(
+    mode: u8,
+    rng: &mut impl CryptoRngCore,
+    input: &[u8],
+) -> Result<(Blind<C>, Element<C>), Error> {
+    let input_element = hash_to_group::<C>(mode, input)?;
+    let blind = ModScalar::random(rng);
+    let blinded = Element::from_point(mul::<C>(&input_element, &blind));
+    Ok((Blind(blind), blinded))
+}
+
+fn unblind<C: OprfCurve>(blind: &Blind<C>, evaluated: &Element<C>) -> C::Point {
+    let mut inverse = blind.0.invert();
+    let unblinded = mul::<C>(&evaluated.point, &inverse);
+    inverse.zeroize();
+    unblinded
+}
+
+/// A group element of the protocol: a blinded or evaluated element, or a public key.
+/// It is never the neutral.
+#[derive(Clone, Copy, Debug)]
+pub struct Element<C: OprfCurve> {
+    point: C::Point,
+    bytes: [u8; 30],
+}
+
+/// A proof that evaluated elements were computed with the key of the server.
+#[derive(Clone, Copy, Debug)]
+pub struct Proof<C: OprfCurve> {
+    c: ModScalar<C>,
+    s: ModScalar<C>,
+}
+
+/// The secret blind of an input, which the client keeps until it finalizes the
+/// evaluation of the input.
+pub struct Blind<C: OprfCurve>(ModScalar<C>);
+
+impl<C: OprfCurve> Element<C> {
+    fn from_point(point: C::Point) -> Self {
+        Element {
+            point,
+            bytes: point.to_encoded(),
+        }
+    }
+
+    /// Decodes an element, rejecting invalid encodings and the neutral.
+    pub fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+        let point = C::Point::try_decode_non_neutral(bytes)?;
+        Ok(Element {
+            point,
+            bytes: *bytes,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        self.bytes
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 30] {
+        &self.bytes
+    }
+
+    pub fn as_point(&self) -> &C::Point {
+        &self.point
+    }
+}
+
+impl<C: OprfCurve> Proof<C> {
+    /// Decodes a proof, rejecting non-canonical scalars.
+    pub fn from_bytes(bytes: &[u8; PROOF_LEN]) -> Result<Self, Error> {
+        let c_bytes: &[u8; 30] = bytes[..30].try_into().unwrap();
+        let s_bytes: &[u8; 30] = bytes[30..].try_into().unwrap();
+        let c = Option::from(ModScalar::from_canonical_bytes(c_bytes));
+        let s = Option::from(ModScalar::from_canonical_bytes(s_bytes));
+        match (c, s) {
+            (Some(c), Some(s)) => Ok(Proof { c, s }),
+            _ => Err(Error::NonCanonicalScalar),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; PROOF_LEN] {
+        let mut out = [0u8; PROOF_LEN];
+        out[..30].copy_from_slice(&self.c.to_bytes());
+        out[30..].copy_from_slice(&self.s.to_bytes());
+        out
+    }
+}
+
+impl<C: OprfCurve> Blind<C> {
+    /// Decodes a blind that was stored with [`Blind::to_bytes`]. Zero is rejected.
+    pub fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+        let blind = ModScalar::from_bytes_non_zero(bytes)?;
+        Ok(Blind(blind))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        self.0.to_bytes()
+    }
+}
+
+// the key of a server, in any mode
+#[derive(Clone)]
+struct KeyPair<C: OprfCurve> {
+    secret: ModScalar<C>,
+    public_key: Element<C>,
+}
+
+impl<C: OprfCurve> KeyPair<C> {
+    fn from_scalar(secret: ModScalar<C>) -> Self {
+        KeyPair {
+            secret,
+            public_key: Element::from_point(mulgen::<C>(&secret)),
+        }
+    }
+
+    fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+        let secret = ModScalar::from_bytes_non_zero(bytes)?;
+        Ok(Self::from_scalar(secret))
+    }
+
+    fn evaluate(&self, elements: &[Element<C>]) -> Vec<Element<C>> {
+        elements
+            .iter()
+            .map(|element| Element::from_point(mul::<C>(&element.point, &self.secret)))
+            .collect()
+    }
+}
+
+impl<C: OprfCurve> Drop for KeyPair<C> {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// The server of the base mode.
+#[derive(Clone)]
+pub struct OprfServer<C: OprfCurve> {
+    key: KeyPair<C>,
+}
+
+/// The server of the verifiable mode.
+#[derive(Clone)]
+pub struct VoprfServer<C: OprfCurve> {
+    key: KeyPair<C>,
+}
+
+/// The server of the partially oblivious mode.
+#[derive(Clone)]
+pub struct PoprfServer<C: OprfCurve> {
+    key: KeyPair<C>,
+}
+
+// the key management, which is the same in all modes except for the derivation
+macro_rules! impl_server_key {
+    ($server:ident, $mode:expr) => {
+        impl<C: OprfCurve> $server<C> {
+            pub fn random_from_rng(rng: &mut impl CryptoRngCore) -> Self {
+                $server {
+                    key: KeyPair::from_scalar(ModScalar::random(rng)),
+                }
+            }
+
+            /// Decodes a secret key from its canonical 30-byte encoding. Zero is
+            /// rejected.
+            pub fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+                Ok($server {
+                    key: KeyPair::from_bytes(bytes)?,
+                })
+            }
+
+            /// Derives the key from a seed and public info, as DeriveKeyPair of
+            /// RFC 9497 §3.2.1. The key depends on the mode.
+            pub fn derive_key_pair(seed: &[u8; SEED_LEN], info: &[u8]) -> Result<Self, Error> {
+                Ok($server {
+                    key: KeyPair::from_scalar(derive_secret::<C>($mode, seed, info)?),
+                })
+            }
+
+            pub fn to_bytes(&self) -> [u8; 30] {
+                self.key.secret.to_bytes()
+            }
+
+            pub fn public_key(&self) -> &Element<C> {
+                &self.key.public_key
+            }
+        }
+
+        impl<C: OprfCurve> ZeroizeOnDrop for $server<C> {}
+    };
+}
+
+impl_server_key!(OprfServer, MODE_OPRF);
+impl_server_key!(VoprfServer, MODE_VOPRF);
+impl_server_key!(PoprfServer, MODE_POPRF);
+
+impl<C: OprfCurve> OprfServer<C> {
+    /// Evaluates a blinded element, as BlindEvaluate of RFC 9497 §3.3.1.
+    pub fn blind_evaluate(&self, blinded: &Element<C>) -> Element<C> {
+        Element::from_point(mul::<C>(&blinded.point, &self.key.secret))
+    }
+
+    /// Computes the output for an input without blinding, as Evaluate of RFC 9497
+    /// §3.3.1. This is the output that a client finalizes for the input.
+    pub fn evaluate(&self, input: &[u8]) -> Result<[u8; OUTPUT_LEN], Error> {
+        let input_element = hash_to_group::<C>(MODE_OPRF, input)?;
+        finalize_hash::<C>(input, None, &mul::<C>(&input_element, &self.key.secret))
+    }
+}
+
+impl<C: OprfCurve> VoprfServer<C> {
+    /// Evaluates a blinded element and proves that it used the key of the public key,
+    /// as BlindEvaluate of RFC 9497 §3.3.2.
+    pub fn blind_evaluate(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        blinded: &Element<C>,
+    ) -> (Element<C>, Proof<C>) {
+        let (evaluated, proof) = self
+            .blind_evaluate_batch(rng, core::slice::from_ref(blinded))
+            .expect("a single element is a valid batch");
+        (evaluated[0], proof)
+    }
+
+    /// Evaluates many blinded elements with a single proof. Batches are limited to
+    /// 2^16 elements.
+    pub fn blind_evaluate_batch(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        blinded: &[Element<C>],
+    ) -> Result<(Vec<Element<C>>, Proof<C>), Error> {
+        check_batch_len(blinded.len())?;
+        let evaluated = self.key.evaluate(blinded);
+        let proof = generate_proof(
+            MODE_VOPRF,
+            rng,
+            &self.key.secret,
+            &self.key.public_key,
+            blinded,
+            &evaluated,
+        );
+        Ok((evaluated, proof))
+    }
+
+    /// Computes the output for an input without blinding, as Evaluate of RFC 9497
+    /// §3.3.2.
+    pub fn evaluate(&self, input: &[u8]) -> Result<[u8; OUTPUT_LEN], Error> {
+        let input_element = hash_to_group::<C>(MODE_VOPRF, input)?;
+        finalize_hash::<C>(input, None, &mul::<C>(&input_element, &self.key.secret))
+    }
+}
+
+impl<C: OprfCurve> PoprfServer<C> {
+    // the key tweaked by the info, which is zero only for a malicious choice of info
+    fn tweaked_secret(&self, info: &[u8]) -> Result<ModScalar<C>, Error> {
+        let mut tweaked = self.key.secret + tweak::<C>(info)?;
+        if tweaked.is_zero().into() {
+            tweaked.zeroize();
+            return Err(Error::InvalidInput);
+        }
+        Ok(tweaked)
+    }
+
+    /// Evaluates a blinded element for the info, and proves that it used the key of
+    /// the public key, as BlindEvaluate of RFC 9497 §3.3.3.
+    pub fn blind_evaluate(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        blinded: &Element<C>,
+        info: &[u8],
+    ) -> Result<(Element<C>, Proof<C>), Error> {
+        let (evaluated, proof) =
+            self.blind_evaluate_batch(rng, core::slice::from_ref(blinded), info)?;
+        Ok((evaluated[0], proof))
+    }
+
+    /// Evaluates many blinded elements for the info with a single proof. Batches are
+    /// limited to 2^16 elements.
+    pub fn blind_evaluate_batch(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        blinded: &[Element<C>],
+        info: &[u8],
+    ) -> Result<(Vec<Element<C>>, Proof<C>), Error> {
+        check_batch_len(blinded.len())?;
+        let mut tweaked = self.tweaked_secret(info)?;
+        let mut inverse = tweaked.invert();
+        let evaluated: Vec<Element<C>> = blinded
+            .iter()
+            .map(|element| Element::from_point(mul::<C>(&element.point, &inverse)))
+            .collect();
+        inverse.zeroize();
+
+        // the proof is for the inverse relation, blinded = t*evaluated
+        let tweaked_key = Element::from_point(mulgen::<C>(&tweaked));
+        let proof = generate_proof(MODE_POPRF, rng, &tweaked, &tweaked_key, &evaluated, blinded);
+        tweaked.zeroize();
+        Ok((evaluated, proof))
+    }
+
+    /// Computes the output for an input and info without blinding, as Evaluate of
+    /// RFC 9497 §3.3.3.
+    pub fn evaluate(&self, input: &[u8], info: &[u8]) -> Result<[u8; OUTPUT_LEN], Error> {
+        let input_element = hash_to_group::<C>(MODE_POPRF, input)?;
+        let mut tweaked = self.tweaked_secret(info)?;
+        let mut inverse = tweaked.invert();
+        tweaked.zeroize();
+        let evaluated = mul::<C>(&input_element, &inverse);
+        inverse.zeroize();
+        finalize_hash::<C>(input, Some(info), &evaluated)
+    }
+}
+
+/// The client of the base mode.
+#[derive(Clone, Copy, Debug)]
+pub struct OprfClient<C: OprfCurve>(PhantomData<C>);
+
+/// The client of the verifiable mode, which verifies the proofs of the server.
+#[derive(Clone, Copy, Debug)]
+pub struct VoprfClient<C: OprfCurve> {
+    public_key: Element<C>,
+}
+
+/// The client of the partially oblivious mode, which verifies the proofs of the
+/// server.
+#[derive(Clone, Copy, Debug)]
+pub struct PoprfClient<C: OprfCurve> {
+    public_key: Element<C>,
+}
+
+impl<C: OprfCurve> OprfClient<C> {
+    pub fn new() -> Self {
+        OprfClient(PhantomData)
+    }
+
+    /// Hashes and blinds the input, as Blind of RFC 9497 §3.3.1. The blinded element
+    /// is sent to the server, and the blind is kept for [`OprfClient::finalize`].
+    pub fn blind(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        input: &[u8],
+    ) -> Result<(Blind<C>, Element<C>), Error> {
+        blind(MODE_OPRF, rng, input)
+    }
+
+    /// Unblinds the evaluated element of the server and computes the output, as
+    /// Finalize of RFC 9497 §3.3.1.
+    pub fn finalize(
+        &self,
+        input: &[u8],
+        blind: &Blind<C>,
+        evaluated: &Element<C>,
+    ) -> Result<[u8; OUTPUT_LEN], Error> {
+        finalize_hash::<C>(input, None, &unblind(blind, evaluated))
+    }
+}
+
+impl<C: OprfCurve> Default for OprfClient<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: OprfCurve> VoprfClient<C> {
+    pub fn new(public_key: Element<C>) -> Self {
+        VoprfClient { public_key }
+    }
+
+    /// Hashes and blinds the input, as Blind of RFC 9497 §3.3.2.
+    pub fn blind(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        input: &[u8],
+    ) -> Result<(Blind<C>, Element<C>), Error> {
+        blind(MODE_VOPRF, rng, input)
+    }
+
+    /// Verifies the proof of the server, unblinds the evaluated element and computes
+    /// the output, as Finalize of RFC 9497 §3.3.2.
+    pub fn finalize(
+        &self,
+        input: &[u8],
+        blind: &Blind<C>,
+        blinded: &Element<C>,
+        evaluated: &Element<C>,
+        proof: &Proof<C>,
+    ) -> Result<[u8; OUTPUT_LEN], Error> {
+        let outputs = self.finalize_batch(
+            &[input],
+            core::slice::from_ref(blind),
+            core::slice::from_ref(blinded),
+            core::slice::from_ref(evaluated),
+            proof,
+        )?;
+        Ok(outputs[0])
+    }
+
+    /// Verifies the proof of a batch, and computes the outputs of all inputs. The
+    /// inputs, blinds and elements must be in the order of the batch.
+    pub fn finalize_batch(
+        &self,
+        inputs: &[&[u8]],
+        blinds: &[Blind<C>],
+        blinded: &[Element<C>],
+        evaluated: &[Element<C>],
+        proof: &Proof<C>,
+    ) -> Result<Vec<[u8; OUTPUT_LEN]>, Error> {
+        check_batch_len(blinded.len())?;
+        check_same_len(blinded.len(), inputs.len())?;
+        check_same_len(blinded.len(), blinds.len())?;
+        check_same_len(blinded.len(), evaluated.len())?;
+        verify_proof(MODE_VOPRF, &self.public_key, blinded, evaluated, proof)?;
+
+        inputs
+            .iter()
+            .zip(blinds.iter().zip(evaluated))
+            .map(|(input, (blind, evaluated))| {
+                finalize_hash::<C>(input, None, &unblind(blind, evaluated))
+            })
+            .collect()
+    }
+}
+
+impl<C: OprfCurve> PoprfClient<C> {
+    pub fn new(public_key: Element<C>) -> Self {
+        PoprfClient { public_key }
+    }
+
+    // the public key tweaked by the info, which is the neutral only for a malicious
+    // choice of the key
+    fn tweaked_key(&self, info: &[u8]) -> Result<Element<C>, Error> {
+        let mut tweaked = C::Point::mulgen(&tweak::<C>(info)?.to_scalar());
+        tweaked.add_assign(&self.public_key.point);
+        if tweaked.is_neutral().into() {
+            return Err(Error::InvalidInput);
+        }
+        Ok(Element::from_point(tweaked))
+    }
+
+    /// Hashes and blinds the input, as Blind of RFC 9497 §3.3.3. This also rejects
+    /// infos for which the tweaked public key is the neutral.
+    pub fn blind(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        input: &[u8],
+        info: &[u8],
+    ) -> Result<(Blind<C>, Element<C>), Error> {
+        self.tweaked_key(info)?;
+        blind(MODE_POPRF, rng, input)
+    }
+
+    /// Verifies the proof of the server for the info, unblinds the evaluated element
+    /// and computes the output, as Finalize of RFC 9497 §3.3.3.
+    pub fn finalize(
+        &self,
+        input: &[u8],
+        blind: &Blind<C>,
+        blinded: &Element<C>,
+        evaluated: &Element<C>,
+        proof: &Proof<C>,
+        info: &[u8],
+    ) -> Result<[u8; OUTPUT_LEN], Error> {
+        let outputs = self.finalize_batch(
+            &[input],
+            core::slice::from_ref(blind),
+            core::slice::from_ref(blinded),
+            core::slice::from_ref(evaluated),
+            proof,
+            info,
+        )?;
+        Ok(outputs[0])
+    }
+
+    /// Verifies the proof of a batch for the info, and computes the outputs of all
+    /// inputs. The inputs, blinds and elements must be in the order of the batch.
+    pub fn finalize_batch(
+        &self,
+        inputs: &[&[u8]],
+        blinds: &[Blind<C>],
+        blinded: &[Element<C>],
+        evaluated: &[Element<C>],
+        proof: &Proof<C>,
+        info: &[u8],
+    ) -> Result<Vec<[u8; OUTPUT_LEN]>, Error> {
+        check_batch_len(blinded.len())?;
+        check_same_len(blinded.len(), inputs.len())?;
+        check_same_len(blinded.len(), blinds.len())?;
+        check_same_len(blinded.len(), evaluated.len())?;
+        let tweaked_key = self.tweaked_key(info)?;
+        verify_proof(MODE_POPRF, &tweaked_key, evaluated, blinded, proof)?;
+
+        inputs
+            .iter()
+            .zip(blinds.iter().zip(evaluated))
+            .map(|(input, (blind, evaluated))| {
+                finalize_hash::<C>(input, Some(info), &unblind(blind, evaluated))
+            })
+            .collect()
+    }
+}
+
+impl<C: OprfCurve> TryFrom<&[u8]> for Element<C> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: &[u8; 30] = bytes.try_into().map_err(|_| Error::InvalidLength {
+            expected: 30,
+            actual: bytes.len(),
+        })?;
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<C: OprfCurve> TryFrom<&[u8]> for Proof<C> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: &[u8; PROOF_LEN] = bytes.try_into().map_err(|_| Error::InvalidLength {
+            expected: PROOF_LEN,
+            actual: bytes.len(),
+        })?;
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<C: OprfCurve> PartialEq for Element<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<C: OprfCurve> Eq for Element<C> {}
+
+impl<C: OprfCurve> PartialEq for Proof<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.c == other.c && self.s == other.s
+    }
+}
+
+impl<C: OprfCurve> Eq for Proof<C> {}
+
+impl<C: OprfCurve> Drop for Blind<C> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<C: OprfCurve> ZeroizeOnDrop for Blind<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    const SEED: [u8; SEED_LEN] = [0xa3; SEED_LEN];
+    const KEY_INFO: &[u8] = b"test key";
+    const INPUT: &[u8] = b"\x00";
+    const INFO: &[u8] = b"test info";
+
+    // (secret key, proof, output) in the OPRF, VOPRF and POPRF modes, for the key
+    // derived from SEED and KEY_INFO, and the blinds and proof randomness of a
+    // ChaCha8Rng with a zero seed. generated with this implementation.
+    const XSK233_VECTORS: [(&str, &str, &str); 3] = [
+        (
+            "ce01677d1eaf56961f56f8f50a713eb510f43c82bc44703276410ee53100",
+            "",
+            "2307179010f9102299fdf54980149b528e249060884e115e69b0e2d40be2d876",
+        ),
+        (
+            "a44ae9794828734be684a43a1971c96eff3a5a87d975ca75f7cd67f75800",
+            "13ef3ad5f257c552231ed1c966fac210e1d071288cba4fa95109c62577008c17e8c3669d4058fea619343422be509bc7c1b27f072ef44ccdacf52800",
+            "133c6e98d90c67852459b31695d07f05473b948bfb7aacafc5bebed472fce697",
+        ),
+        (
+            "63d60406072cbb176a3589388768b60c6de38fac031120a47f5369513c00",
+            "ce06c6b62190d62e87a56894b2a982f28eb11243a994aae049325352650021e4c220e2df9ca32f4569bf8ee941e1adc823ad3a43b183914e77b77400",
+            "23a8f39cc2d905153b636e718eff59b55b906ec374b39763d765fbd1061e9ea7",
+        ),
+    ];
+    const XSB233_VECTORS: [(&str, &str, &str); 3] = [
+        (
+            "9544ced63a2df59dd69257270413ac4d7b94e01161df729f90b6c2262a00",
+            "",
+            "7d0504cc877f2538f8e16298c09d4e9656236c921b9ee0bacfae1aa7505c8855",
+        ),
+        (
+            "46d47810a11a7c25a73a9f6e5d97708fdae7f5f8daf4841722f798094d00",
+            "b80597f547e5e36fd2022180415fb673f8a3ad1bf3d172a2d35a5e96f80064ac8ea0dbc23894c88fbaac951235145d9960ab72551b307c7467129b00",
+            "c55759172d318f09648265412c9a975bbaadeacc9ec3558280fec2d05c6c948b",
+        ),
+        (
+            "e6eefc6e7276a2b5b59fa07864821f2b56b2355d0165d7f88edf34c1ea00",
+            "f63c21d4eb271073252f98ffc53cdef40f7c09221c32b217902aa0c400009f3c7e802004fdb54e158ccb32e60ee7108f9510817f2213ba1c80c3f500",
+            "94bc195c67d4e1f99676474e5879d7adff86de97388ee6d364fc51cea41f6849",
+        ),
+    ];
+
+    fn check_vectors<C: OprfCurve>(vectors: &[(&str, &str, &str); 3]) {
+        let [oprf, voprf, poprf] = vectors;
+
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let server = OprfServer::<C>::derive_key_pair(&SEED, KEY_INFO).unwrap();
+        assert_eq!(hex::encode(server.to_bytes()), oprf.0);
+        let client = OprfClient::<C>::new();
+        let (blind, blinded) = client.blind(&mut rng, INPUT).unwrap();
+        let evaluated = server.blind_evaluate(&blinded);
+        let output = client.finalize(INPUT, &blind, &evaluated).unwrap();
+        assert_eq!(hex::encode(output), oprf.2);
+        assert_eq!(server.evaluate(INPUT), Ok(output));
+
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let server = VoprfServer::<C>::derive_key_pair(&SEED, KEY_INFO).unwrap();
+        assert_eq!(hex::encode(server.to_bytes()), voprf.0);
+        let client = VoprfClient::new(*server.public_key());
+        let (blind, blinded) = client.blind(&mut rng, INPUT).unwrap();
+        let (evaluated, proof) = server.blind_evaluate(&mut rng, &blinded);
+        assert_eq!(hex::encode(proof.to_bytes()), voprf.1);
+        let output = client
+            .finalize(INPUT, &blind, &blinded, &evaluated, &proof)
+            .unwrap();
+        assert_eq!(hex::encode(output), voprf.2);
+        assert_eq!(server.evaluate(INPUT), Ok(output));
+
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let server = PoprfServer::<C>::derive_key_pair(&SEED, KEY_INFO).unwrap();
+        assert_eq!(hex::encode(server.to_bytes()), poprf.0);
+        let client = PoprfClient::new(*server.public_key());
+        let (blind, blinded) = client.blind(&mut rng, INPUT, INFO).unwrap();
+        let (evaluated, proof) = server.blind_evaluate(&mut rng, &blinded, INFO).unwrap();
+        assert_eq!(hex::encode(proof.to_bytes()), poprf.1);
+        let output = client
+            .finalize(INPUT, &blind, &blinded, &evaluated, &proof, INFO)
+            .unwrap();
+        assert_eq!(hex::encode(output), poprf.2);
+        assert_eq!(server.evaluate(INPUT, INFO), Ok(output));
+    }
+
+    fn check_oprf<C: OprfCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let server = OprfServer::<C>::random_from_rng(&mut rng);
+        let client = OprfClient::<C>::new();
+
+        // the blinds are fresh, but the output only depends on the key and the input
+        let (first_blind, first_blinded) = client.blind(&mut rng, b"input").unwrap();
+        let (second_blind, second_blinded) = client.blind(&mut rng, b"input").unwrap();
+        assert_ne!(first_blinded, second_blinded);
+        let first = client
+            .finalize(
+                b"input",
+                &first_blind,
+                &server.blind_evaluate(&first_blinded),
+            )
+            .unwrap();
+        let second = client
+            .finalize(
+                b"input",
+                &second_blind,
+                &server.blind_evaluate(&second_blinded),
+            )
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(server.evaluate(b"input"), Ok(first));
+        assert_ne!(server.evaluate(b"other"), Ok(first));
+
+        // another key gives another output
+        let other = OprfServer::<C>::random_from_rng(&mut rng);
+        assert_ne!(other.evaluate(b"input"), Ok(first));
+
+        // the modes are domain-separated
+        let voprf = VoprfServer::<C>::from_bytes(&server.to_bytes()).unwrap();
+        assert_ne!(voprf.evaluate(b"input"), Ok(first));
+    }
+
+    fn check_oprf_roundtrip<C: OprfCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let server = OprfServer::<C>::random_from_rng(&mut rng);
+        let client = OprfClient::<C>::new();
+        let (blind, blinded) = client.blind(&mut rng, b"input").unwrap();
+        let output = server.evaluate(b"input").unwrap();
+
+        let decoded = OprfServer::<C>::from_bytes(&server.to_bytes()).unwrap();
+        assert_eq!(decoded.public_key(), server.public_key());
+        let decoded_blind = Blind::<C>::from_bytes(&blind.to_bytes()).unwrap();
+        let evaluated = server.blind_evaluate(&blinded);
+        assert_eq!(
+            client.finalize(b"input", &decoded_blind, &evaluated),
+            Ok(output)
+        );
+        assert_eq!(
+            Element::<C>::try_from(&evaluated.to_bytes()[..]),
+            Ok(evaluated)
+        );
+    }
+
+    fn check_strict_decoding<C: OprfCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let server = OprfServer::<C>::random_from_rng(&mut rng);
+
+        // strict decoding, and inputs that cannot be length-prefixed
+        assert_eq!(
+            Element::<C>::from_bytes(&[0; 30]),
+            Err(Error::IdentityPoint)
+        );
+        assert_eq!(
+            Element::<C>::from_bytes(&[0xff; 30]),
+            Err(Error::InvalidEncoding)
+        );
+        assert!(matches!(
+            OprfServer::<C>::from_bytes(&[0; 30]),
+            Err(Error::ZeroScalar)
+        ));
+        assert!(matches!(
+            Blind::<C>::from_bytes(&[0xff; 30]),
+            Err(Error::NonCanonicalScalar)
+        ));
+        let long = [0u8; 1 << 16];
+        assert_eq!(server.evaluate(&long), Err(Error::InvalidInput));
+        assert!(matches!(
+            OprfServer::<C>::derive_key_pair(&SEED, &long),
+            Err(Error::InvalidInput)
+        ));
+    }
+
+    fn check_voprf<C: OprfCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let server = VoprfServer::<C>::random_from_rng(&mut rng);
+        let client = VoprfClient::new(*server.public_key());
+        let inputs: [&[u8]; 4] = [b"first", b"second", b"third", b""];
+
+        let mut blinds = Vec::new();
+        let mut blinded = Vec::new();
+        for input in inputs {
+            let (blind, element) = client.blind(&mut rng, input).unwrap();
+            blinds.push(blind);
+            blinded.push(element);
+        }
+        let (evaluated, proof) = server.blind_evaluate_batch(&mut rng, &blinded).unwrap();
+        let outputs = client
+            .finalize_batch(&inputs, &blinds, &blinded, &evaluated, &proof)
+            .unwrap();
+        for (input, output) in inputs.iter().zip(&outputs) {
+            assert_eq!(server.evaluate(input), Ok(*output));
+        }
+        assert_eq!(Proof::<C>::from_bytes(&proof.to_bytes()), Ok(proof));
+
+        // a single element with its own proof
+        let (evaluated_one, proof_one) = server.blind_evaluate(&mut rng, &blinded[1]);
+        assert_eq!(evaluated_one, evaluated[1]);
+        assert_eq!(
+            client.finalize(
+                inputs[1],
+                &blinds[1],
+                &blinded[1],
+                &evaluated_one,
+                &proof_one
+            ),
+            Ok(outputs[1])
+        );
+
+        // swapped or modified elements, and the proof of another batch or key
+        let mut swapped = evaluated.clone();
+        swapped.swap(0, 2);
+        assert_eq!(
+            client.finalize_batch(&inputs, &blinds, &blinded, &swapped, &proof),
+            Err(Error::VerificationFailed)
+        );
+        assert_eq!(
+            client.finalize_batch(
+                &inputs[..3],
+                &blinds[..3],
+                &blinded[..3],
+                &evaluated[..3],
+                &proof
+            ),
+            Err(Error::VerificationFailed)
+        );
+        assert_eq!(
+            client.finalize(inputs[1], &blinds[1], &blinded[1], &evaluated_one, &proof),
+            Err(Error::VerificationFailed)
+        );
+        let other = VoprfServer::<C>::random_from_rng(&mut rng);
+        let (forged, forged_proof) = other.blind_evaluate(&mut rng, &blinded[0]);
+        assert_eq!(
+            client.finalize(inputs[0], &blinds[0], &blinded[0], &forged, &forged_proof),
+            Err(Error::VerificationFailed)
+        );
+        let mut bytes = proof.to_bytes();
+        bytes[0] ^= 1;
+        let tampered = Proof::<C>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            client.finalize_batch(&inputs, &blinds, &blinded, &evaluated, &tampered),
+            Err(Error::VerificationFailed)
+        );
+
+        // mismatched lengths, and strict decoding
+        assert_eq!(
+            client.finalize_batch(&inputs[..3], &blinds, &blinded, &evaluated, &proof),
+            Err(Error::InvalidLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+        let mut bytes = proof.to_bytes();
+        bytes[30..].copy_from_slice(&[0xff; 30]);
+        assert_eq!(
+            Proof::<C>::from_bytes(&bytes),
+            Err(Error::NonCanonicalScalar)
+        );
+        assert_eq!(
+            Proof::<C>::try_from(&bytes[..59]),
+            Err(Error::InvalidLength {
+                expected: PROOF_LEN,
+                actual: 59
+            })
+        );
+    }
+
+    fn check_poprf<C: OprfCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let server = PoprfServer::<C>::random_from_rng(&mut rng);
+        let client = PoprfClient::new(*server.public_key());
+        let inputs: [&[u8]; 3] = [b"first", b"second", b"third"];
+
+        let mut blinds = Vec::new();
+        let mut blinded = Vec::new();
+        for input in inputs {
+            let (blind, element) = client.blind(&mut rng, input, INFO).unwrap();
+            blinds.push(blind);
+            blinded.push(element);
+        }
+        let (evaluated, proof) = server
+            .blind_evaluate_batch(&mut rng, &blinded, INFO)
+            .unwrap();
+        let outputs = client
+            .finalize_batch(&inputs, &blinds, &blinded, &evaluated, &proof, INFO)
+            .unwrap();
+        for (input, output) in inputs.iter().zip(&outputs) {
+            assert_eq!(server.evaluate(input, INFO), Ok(*output));
+        }
+
+        // the output depends on the info, and the proof binds it
+        assert_ne!(server.evaluate(inputs[0], b"other info"), Ok(outputs[0]));
+        assert_eq!(
+            client.finalize_batch(
+                &inputs,
+                &blinds,
+                &blinded,
+                &evaluated,
+                &proof,
+                b"other info"
+            ),
+            Err(Error::VerificationFailed)
+        );
+        let (other_evaluated, other_proof) = server
+            .blind_evaluate(&mut rng, &blinded[0], b"other info")
+            .unwrap();
+        assert_eq!(
+            client.finalize(
+                inputs[0],
+                &blinds[0],
+                &blinded[0],
+                &other_evaluated,
+                &other_proof,
+                INFO
+            ),
+            Err(Error::VerificationFailed)
+        );
+        let mut swapped = evaluated.clone();
+        swapped.swap(0, 1);
+        assert_eq!(
+            client.finalize_batch(&inputs, &blinds, &blinded, &swapped, &proof, INFO),
+            Err(Error::VerificationFailed)
+        );
+
+        // an info that tweaks the key to zero is rejected by both sides
+        let info = b"evil";
+        let evil = PoprfServer::<C>::from_bytes(&(-tweak::<C>(info).unwrap()).to_bytes()).unwrap();
+        assert!(matches!(
+            evil.evaluate(INPUT, info),
+            Err(Error::InvalidInput)
+        ));
+        assert!(matches!(
+            evil.blind_evaluate(&mut rng, &blinded[0], info),
+            Err(Error::InvalidInput)
+        ));
+        assert!(matches!(
+            PoprfClient::new(*evil.public_key()).blind(&mut rng, INPUT, info),
+            Err(Error::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn vectors() {
+        check_vectors::<Xsk233>(&XSK233_VECTORS);
+        check_vectors::<Xsb233>(&XSB233_VECTORS);
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn oprf_evaluate() { check_oprf::<C>(seed) }
+        fn oprf_roundtrip() { check_oprf_roundtrip::<C>(seed) }
+        fn strict_decoding() { check_strict_decoding::<C>(seed) }
+        fn voprf_batch_proofs() { check_voprf::<C>(seed) }
+        fn poprf_info() { check_poprf::<C>(seed) }
+    }
+}
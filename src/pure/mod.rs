@@ -0,0 +1,588 @@
+// A pure Rust implementation of the xsk233 and xsb233 groups, enabled by the
+// pure-rust feature. It uses the same formulas as c-xs233 and produces the same
+// encodings, but does not need a C toolchain. It is considerably slower than
+// the FFI backend. The field arithmetic and the formulas are always compiled,
+// since the affine points use them with either backend.
+//
+// See xs233_common.h in c-xs233 for the derivation of the formulas. In short,
+// we work on the curve y^2 + x*y = x^3 + a*x^2 + b*x with a in {0, 1}, in
+// (x, s) coordinates with s = y^2/x, and represent points in extended
+// coordinates (X:S:Z:T) with
+//   Z != 0
+//   x = sqrt(b)*X/Z
+//   s = sqrt(b)*S/Z^2
+//   T = X*Z
+
+// without the pure-rust feature, only the parts needed for affine points are used
+#![cfg_attr(not(feature = "pure-rust"), allow(dead_code))]
+
+pub(crate) mod xsb233;
+pub(crate) mod xsk233;
+
+#[cfg(feature = "pure-rust")]
+pub use xsb233::Xsb233Point;
+#[cfg(feature = "pure-rust")]
+pub use xsk233::Xsk233Point;
+
+use crate::gf233::Gf;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+pub(crate) trait CurveParams {
+    // whether a is 1 (otherwise it is 0)
+    const A_IS_ONE: bool;
+    const A: Gf = if Self::A_IS_ONE { Gf::ONE } else { Gf::ZERO };
+    const B: Gf;
+    const SQRT_B: Gf;
+    const INV_SQRT_B: Gf;
+    const NEUTRAL: Extended;
+    const GENERATOR: Extended;
+
+    fn mul_sqrt_b(v: Gf) -> Gf {
+        v * Self::SQRT_B
+    }
+
+    // Adds N = (0, 0) to the point (x, y) of the curve y^2 + x*y = x^3 + a*x^2 + b*x,
+    // which must not be N itself or the point at infinity. For x = 0, the result is
+    // garbage.
+    #[cfg(any(feature = "ecdsa", feature = "hash-to-curve"))]
+    fn add_n(x: &Gf, y: &Gf) -> (Gf, Gf) {
+        let lambda = *y * x.invert();
+        let x_out = lambda.square() + lambda + Self::A + *x;
+        let y_out = lambda * (*x + x_out) + x_out + *y;
+        (x_out, y_out)
+    }
+
+    // Decoding w needs a single inversion, of d^2 for the d returned by
+    // decode_denominator. It is split in two so that the inversion can be
+    // shared when decoding in batches.
+    fn decode_denominator(w: &Gf) -> Gf;
+
+    // Finishes decoding w into affine (x, s) coordinates (not scaled by
+    // 1/sqrt(b)), given inv_dd = 1/d^2, or zero if d is zero. On failure,
+    // this returns the neutral. The canonicity of w is not checked.
+    fn decode_finish(w: &Gf, d: &Gf, inv_dd: &Gf) -> (Gf, Gf, Choice);
+
+    fn decode_affine(repr: &[u8; 30]) -> (Gf, Gf, Choice) {
+        let (w, is_canonical) = Gf::decode(repr);
+        let d = Self::decode_denominator(&w);
+        let (x, s, is_valid) = Self::decode_finish(&w, &d, &d.square().invert());
+        (x, s, is_canonical & is_valid)
+    }
+}
+
+// The extended coordinates of c-xs233 points. xs233-sys always builds c-xs233
+// with pclmul support, and then a point holds X, S, Z and T in that order, each
+// as four little-endian 64-bit limbs. The limbs are only partially reduced, so
+// they may hold any 256-bit value; c-xs233 accepts fully reduced ones, too.
+//
+// The public API of c-xs233 does not promise this layout, so Cargo.toml pins the
+// version of xs233-sys, and the FFI point types call assert_opaque_layout before
+// their first conversion.
+#[cfg(feature = "ffi")]
+impl Extended {
+    pub(crate) fn from_opaque(opaque: &[u64; 16]) -> Self {
+        let coordinate = |i: usize| {
+            let limbs = [opaque[i], opaque[i + 1], opaque[i + 2], opaque[i + 3]];
+            Gf::from_limbs(limbs)
+        };
+        Extended {
+            x: coordinate(0),
+            s: coordinate(4),
+            z: coordinate(8),
+            t: coordinate(12),
+        }
+    }
+
+    pub(crate) fn to_opaque(self) -> [u64; 16] {
+        let mut opaque = [0u64; 16];
+        let coordinates = [self.x, self.s, self.z, self.t];
+        for (limbs, coordinate) in opaque.chunks_exact_mut(4).zip(coordinates) {
+            limbs.copy_from_slice(&coordinate.to_limbs());
+        }
+        opaque
+    }
+}
+
+// Checks the layout assumed by Extended::from_opaque against the generator and
+// its double as computed by c-xs233, and panics if it does not hold. Reading the
+// coordinates wrongly would silently compute wrong points, so this fails loudly
+// instead, e.g. after an update of c-xs233.
+#[cfg(feature = "ffi")]
+pub(crate) fn assert_opaque_layout<C: CurveParams>(
+    generator: &[u64; 16],
+    doubled: &[u64; 16],
+    doubled_encoding: &[u8; 30],
+) {
+    let expected = C::GENERATOR;
+    let generator = Extended::from_opaque(generator);
+    let doubled = Extended::from_opaque(doubled);
+    let holds = (generator.x, generator.s, generator.z, generator.t)
+        == (expected.x, expected.s, expected.z, expected.t)
+        && doubled.encode() == *doubled_encoding
+        && bool::from(doubled.ct_eq(&expected.double::<C>()));
+    assert!(
+        holds,
+        "the point layout of c-xs233 does not match Extended::from_opaque; \
+         xs233 needs xs233-sys 0.1.0"
+    );
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Extended {
+    pub(crate) x: Gf,
+    pub(crate) s: Gf,
+    pub(crate) z: Gf,
+    pub(crate) t: Gf,
+}
+
+impl Extended {
+    pub(crate) fn decode<C: CurveParams>(repr: &[u8; 30]) -> (Self, Choice) {
+        let (x, s, is_valid) = C::decode_affine(repr);
+
+        // We set Z = 1, hence X = x/sqrt(b), S = s/sqrt(b), T = X
+        let point = Extended::from_affine(x * C::INV_SQRT_B, s * C::INV_SQRT_B);
+        (point, is_valid)
+    }
+
+    pub(crate) fn from_affine(x: Gf, s: Gf) -> Self {
+        Extended {
+            x,
+            s,
+            z: Gf::ONE,
+            t: x,
+        }
+    }
+
+    // w = sqrt(s/x) = sqrt(S/T). For the neutral, T = 0 and the inverse is 0,
+    // which yields the encoding of the neutral.
+    pub(crate) fn encode(&self) -> [u8; 30] {
+        (self.s * self.t.invert()).sqrt().encode()
+    }
+
+    // The neutral is the only point with x = 0.
+    pub(crate) fn is_neutral(&self) -> Choice {
+        self.x.is_zero()
+    }
+
+    // The mapping to w is injective, so we compare w^2 = S/T.
+    pub(crate) fn ct_eq(&self, other: &Self) -> Choice {
+        (self.s * other.t).ct_eq(&(other.s * self.t))
+    }
+
+    pub(crate) fn add<C: CurveParams>(&self, rhs: &Self) -> Self {
+        // x1x2 <- X1*X2
+        // s1s2 <- S1*S2
+        // z1z2 <- Z1*Z2
+        // d <- (S1 + T1)*(S2 + T2)
+        // e <- (a^2)*T1*T2
+        // f <- x1x2^2
+        // g <- z1z2^2
+        // X3 <- d + s1s2
+        // S3 <- sqrt(b)*(g*(s1s2 + e) + f*(d + e))
+        // Z3 <- sqrt(b)*(f + g)
+        // T3 <- X3*Z3
+        let x1x2 = self.x * rhs.x;
+        let s1s2 = self.s * rhs.s;
+        let z1z2 = self.z * rhs.z;
+        let d = (self.s + self.t) * (rhs.s + rhs.t);
+        let e = if C::A_IS_ONE {
+            self.t * rhs.t
+        } else {
+            Gf::ZERO
+        };
+        let f = x1x2.square();
+        let g = z1z2.square();
+
+        let x = d + s1s2;
+        let s = C::mul_sqrt_b(g * (s1s2 + e) + f * (d + e));
+        let z = C::mul_sqrt_b(f + g);
+        let t = x * z;
+
+        Extended { x, s, z, t }
+    }
+
+    // Mixed addition with a point in affine coordinates (Z2 = 1, T2 = X2),
+    // which saves a multiplication.
+    pub(crate) fn add_affine<C: CurveParams>(&self, x2: &Gf, s2: &Gf) -> Self {
+        let x1x2 = self.x * *x2;
+        let s1s2 = self.s * *s2;
+        let d = (self.s + self.t) * (*s2 + *x2);
+        let e = if C::A_IS_ONE { self.t * *x2 } else { Gf::ZERO };
+        let f = x1x2.square();
+        let g = self.z.square();
+
+        let x = d + s1s2;
+        let s = C::mul_sqrt_b(g * (s1s2 + e) + f * (d + e));
+        let z = C::mul_sqrt_b(f + g);
+        let t = x * z;
+
+        Extended { x, s, z, t }
+    }
+
+    pub(crate) fn double<C: CurveParams>(&self) -> Self {
+        // zz <- Z^2
+        // tt <- T^2
+        // D  <- (S + a*T)*(S + (a + 1)*T)
+        // E  <- (S + (a + 1)*T + sqrt(b)*zz)^2
+        // X' <- sqrt(b)*tt
+        // Z' <- D + a*tt
+        // S' <- sqrt(b)*(E*(E + Z' + tt) + (D + sqrt(b)*X')^2)
+        // T' <- X'*Z'
+        let zz = self.z.square();
+        let tt = self.t.square();
+        let (s_at, s_a1t) = if C::A_IS_ONE {
+            (self.s + self.t, self.s)
+        } else {
+            (self.s, self.s + self.t)
+        };
+
+        let d = s_at * s_a1t;
+        let e = (s_a1t + C::mul_sqrt_b(zz)).square();
+        let x = C::mul_sqrt_b(tt);
+        let z = if C::A_IS_ONE { d + tt } else { d };
+        let s = C::mul_sqrt_b(e * (e + z + tt) + (d + C::mul_sqrt_b(x)).square());
+        let t = x * z;
+
+        Extended { x, s, z, t }
+    }
+
+    // Point halving, see HalfPoint. This needs an inversion to get to affine
+    // coordinates.
+    pub(crate) fn halve<C: CurveParams>(&self) -> Self {
+        let half = HalfPoint::new::<C>(self).halve::<C>().to_extended::<C>();
+        Extended::conditional_select(&half, &C::NEUTRAL, self.is_neutral())
+    }
+
+    // The negation of (x, s) is (x, s + x), i.e. we add T to S.
+    pub(crate) fn neg(&self) -> Self {
+        Extended {
+            s: self.s + self.t,
+            ..*self
+        }
+    }
+
+    pub(crate) fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Extended {
+            x: Gf::conditional_select(&a.x, &b.x, choice),
+            s: Gf::conditional_select(&a.s, &b.s, choice),
+            z: Gf::conditional_select(&a.z, &b.z, choice),
+            t: Gf::conditional_select(&a.t, &b.t, choice),
+        }
+    }
+}
+
+// A point in the affine form used for halving, with x, w and x' = b/x. The
+// notation is that of the decoding notes in xs233_common.h: the group element
+// Q = (x, w) is the curve point P + N for an r-torsion point P, and P = (x', w
+// + 1). A half (x'', w'') of Q is
+//   lambda = halftrace(x' + a)
+//   x'' = sqrt((lambda + w)*x' + b)
+//   w'' = sqrt(lambda + a)
+// and lambda + 1 yields the other half, (x'' + sqrt(x'), w'' + 1). Of the two
+// halves of P, we return the one which is in the group, i.e. which is not an
+// r-torsion point. Since the other half is its sum with N, the product of their
+// x coordinates is b, so the new x' is x'' + sqrt(x') and further halvings need
+// no inversion.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HalfPoint {
+    x: Gf,
+    w: Gf,
+    xp: Gf,
+}
+
+impl HalfPoint {
+    // Converts a point to affine coordinates, with a single inversion. The
+    // result is meaningless for the neutral, which has no half but itself.
+    pub(crate) fn new<C: CurveParams>(point: &Extended) -> Self {
+        // with i = 1/(Z*T), we have w^2 = S/T = S*Z*i, x = sqrt(b)*X/Z =
+        // sqrt(b)*X*T*i and b/x = sqrt(b)*Z/X = sqrt(b)*Z^3*i
+        let i = (point.z * point.t).invert();
+        HalfPoint {
+            x: C::mul_sqrt_b(point.x * point.t * i),
+            w: (point.s * point.z * i).sqrt(),
+            xp: C::mul_sqrt_b(point.z.square() * point.z * i),
+        }
+    }
+
+    pub(crate) fn halve<C: CurveParams>(&self) -> Self {
+        let lambda = (self.xp + C::A).halftrace();
+        let sqrt_xp = self.xp.sqrt();
+        let mut x = ((lambda + self.w) * self.xp + C::B).sqrt();
+        let mut w = (lambda + C::A).sqrt();
+
+        let wrong_half = if C::A_IS_ONE {
+            // The curve order is 2*r, so the half is in the group if and only
+            // if it cannot be halved, i.e. Tr(x'' + 1) = 1.
+            x.trace()
+        } else {
+            // The curve order is 4*r, so the half is in the group if and only
+            // if its halves cannot be halved. This is the same check as in
+            // decode_finish.
+            ((x.halftrace() + w) * x).trace()
+        };
+        x = Gf::conditional_select(&x, &(x + sqrt_xp), wrong_half);
+        w = Gf::conditional_select(&w, &(w + Gf::ONE), wrong_half);
+
+        HalfPoint {
+            x,
+            w,
+            xp: x + sqrt_xp,
+        }
+    }
+
+    // Returns the affine coordinates (X, S) with Z = 1, scaled by 1/sqrt(b).
+    pub(crate) fn to_affine<C: CurveParams>(self) -> (Gf, Gf) {
+        let s = self.x * self.w.square();
+        (self.x * C::INV_SQRT_B, s * C::INV_SQRT_B)
+    }
+
+    pub(crate) fn to_extended<C: CurveParams>(self) -> Extended {
+        let (x, s) = self.to_affine::<C>();
+        Extended::from_affine(x, s)
+    }
+}
+
+// Implements Point and the subtle traits for a newtype around Extended.
+#[cfg(feature = "pure-rust")]
+macro_rules! impl_pure_point {
+    ($type:ident, $params:ty) => {
+        impl $crate::Point for $type {
+            type EncodedPoint = [u8; 30];
+
+            fn decode(&mut self, repr: &Self::EncodedPoint) -> ::subtle::Choice {
+                let (point, is_valid) = $crate::pure::Extended::decode::<$params>(repr);
+                self.0 = point;
+                is_valid
+            }
+
+            fn encode(&self, dst: &mut Self::EncodedPoint) {
+                *dst = self.0.encode();
+            }
+
+            fn mulgen<const N: usize>(scalar: &$crate::scalar::Scalar<N>) -> Self {
+                $crate::lookup::mul_signed_window(<Self as $crate::Point>::generator(), scalar)
+            }
+
+            fn neutral() -> &'static Self {
+                static NEUTRAL: $type = $type(<$params>::NEUTRAL);
+                &NEUTRAL
+            }
+
+            fn generator() -> &'static Self {
+                static GENERATOR: $type = $type(<$params>::GENERATOR);
+                &GENERATOR
+            }
+
+            fn is_neutral(&self) -> ::subtle::Choice {
+                self.0.is_neutral()
+            }
+
+            fn mul<const N: usize>(&mut self, point: &Self, scalar: &$crate::scalar::Scalar<N>) {
+                *self = $crate::lookup::mul_signed_window(point, scalar);
+            }
+
+            fn mul_inplace<const N: usize>(&mut self, scalar: &$crate::scalar::Scalar<N>) {
+                *self = $crate::lookup::mul_signed_window(self, scalar);
+            }
+
+            fn add(&mut self, lhs: &Self, rhs: &Self) {
+                self.0 = lhs.0.add::<$params>(&rhs.0);
+            }
+
+            fn add_assign(&mut self, rhs: &Self) {
+                self.0 = self.0.add::<$params>(&rhs.0);
+            }
+
+            fn sub(&mut self, lhs: &Self, rhs: &Self) {
+                self.0 = lhs.0.add::<$params>(&rhs.0.neg());
+            }
+
+            fn sub_assign(&mut self, rhs: &Self) {
+                self.0 = self.0.add::<$params>(&rhs.0.neg());
+            }
+
+            fn neg(&mut self, point: &Self) {
+                self.0 = point.0.neg();
+            }
+
+            fn neg_inplace(&mut self) {
+                self.0 = self.0.neg();
+            }
+
+            fn double(&mut self, point: &Self) {
+                self.0 = point.0.double::<$params>();
+            }
+
+            fn double_inplace(&mut self) {
+                self.0 = self.0.double::<$params>();
+            }
+
+            fn halve(&mut self, point: &Self) {
+                self.0 = point.0.halve::<$params>();
+            }
+
+            fn halve_inplace(&mut self) {
+                self.0 = self.0.halve::<$params>();
+            }
+
+            fn xdouble(&mut self, point: &Self, n: u32) {
+                self.0 = point.0;
+                self.xdouble_inplace(n);
+            }
+
+            fn xdouble_inplace(&mut self, n: u32) {
+                for _ in 0..n {
+                    self.0 = self.0.double::<$params>();
+                }
+            }
+        }
+
+        // the default is the neutral, as for the ffi backend
+        impl ::core::default::Default for $type {
+            fn default() -> Self {
+                $type(<$params>::NEUTRAL)
+            }
+        }
+
+        impl ::subtle::ConstantTimeEq for $type {
+            fn ct_eq(&self, other: &Self) -> ::subtle::Choice {
+                self.0.ct_eq(&other.0)
+            }
+        }
+
+        impl ::subtle::ConditionallySelectable for $type {
+            fn conditional_select(a: &Self, b: &Self, choice: ::subtle::Choice) -> Self {
+                $type($crate::pure::Extended::conditional_select(
+                    &a.0, &b.0, choice,
+                ))
+            }
+        }
+
+        $crate::impl_ops!($type);
+    };
+}
+
+#[cfg(feature = "pure-rust")]
+use impl_pure_point;
+
+// check the two backends against each other
+#[cfg(all(test, feature = "ffi", feature = "pure-rust"))]
+mod tests {
+    use crate::scalar::Scalar;
+    use crate::Point;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    fn encode<P: Point<EncodedPoint = [u8; 30]>>(point: &P) -> [u8; 30] {
+        let mut buf = [0u8; 30];
+        point.encode(&mut buf);
+        buf
+    }
+
+    fn check_backends<Ffi, Pure>(seed: u8)
+    where
+        Ffi: Point<EncodedPoint = [u8; 30]> + 'static,
+        Pure: Point<EncodedPoint = [u8; 30]> + 'static,
+    {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+
+        assert_eq!(encode(Ffi::neutral()), encode(Pure::neutral()));
+        assert_eq!(encode(Ffi::generator()), encode(Pure::generator()));
+
+        // random encodings, most of which are invalid
+        for _ in 0..200 {
+            let mut buf = [0u8; 30];
+            rng.fill(&mut buf);
+            buf[29] &= 1;
+
+            let mut ffi = Ffi::default();
+            let mut pure = Pure::default();
+            let ffi_ok: bool = ffi.decode(&buf).into();
+            let pure_ok: bool = pure.decode(&buf).into();
+            assert_eq!(ffi_ok, pure_ok);
+            assert_eq!(encode(&ffi), encode(&pure));
+        }
+
+        let mut buf = [0u8; 30];
+        rng.fill(&mut buf);
+        let scalar = Scalar::new(buf);
+        let mut ffi = Ffi::mulgen(&scalar);
+        let mut pure = Pure::mulgen(&scalar);
+        assert_eq!(encode(&ffi), encode(&pure));
+
+        for _ in 0..50 {
+            rng.fill(&mut buf);
+            let scalar = Scalar::new(buf);
+            let ffi_other = Ffi::mulgen(&scalar);
+            let pure_other = Pure::mulgen(&scalar);
+            assert_eq!(encode(&ffi_other), encode(&pure_other));
+
+            match rng.gen_range(0..8) {
+                0 => {
+                    ffi.add_assign(&ffi_other);
+                    pure.add_assign(&pure_other);
+                }
+                1 => {
+                    ffi.sub_assign(&ffi_other);
+                    pure.sub_assign(&pure_other);
+                }
+                2 => {
+                    ffi.double_inplace();
+                    pure.double_inplace();
+                }
+                3 => {
+                    let n = rng.gen_range(0..10);
+                    ffi.xdouble_inplace(n);
+                    pure.xdouble_inplace(n);
+                }
+                4 => {
+                    ffi.neg_inplace();
+                    pure.neg_inplace();
+                }
+                5 => {
+                    let short: Scalar<7> = Scalar::new(buf[..7].try_into().unwrap());
+                    ffi.mul_inplace(&short);
+                    pure.mul_inplace(&short);
+                }
+                6 => {
+                    let choice = (buf[0] & 1).into();
+                    ffi.conditional_negate(choice);
+                    pure.conditional_negate(choice);
+                }
+                _ => {
+                    let choice = (buf[0] & 1).into();
+                    ffi = Ffi::conditional_select(&ffi, &ffi_other, choice);
+                    pure = Pure::conditional_select(&pure, &pure_other, choice);
+                }
+            }
+
+            assert_eq!(encode(&ffi), encode(&pure));
+            assert_eq!(bool::from(ffi.is_neutral()), bool::from(pure.is_neutral()));
+            assert_eq!(
+                bool::from(ffi.ct_eq(&ffi_other)),
+                bool::from(pure.ct_eq(&pure_other))
+            );
+        }
+
+        // adding a point to itself and to its negation must work, too
+        let mut ffi_sum = Ffi::default();
+        let mut pure_sum = Pure::default();
+        ffi_sum.add(&ffi, &ffi);
+        pure_sum.add(&pure, &pure);
+        assert_eq!(encode(&ffi_sum), encode(&pure_sum));
+        ffi_sum.sub(&ffi, &ffi);
+        pure_sum.sub(&pure, &pure);
+        assert!(bool::from(ffi_sum.is_neutral()));
+        assert!(bool::from(pure_sum.is_neutral()));
+    }
+
+    #[test]
+    fn xsk233_matches_ffi() {
+        check_backends::<crate::xsk233::Xsk233Point, super::Xsk233Point>(1);
+    }
+
+    #[test]
+    fn xsb233_matches_ffi() {
+        check_backends::<crate::xsb233::Xsb233Point, super::Xsb233Point>(2);
+    }
+}
@@ -0,0 +1,90 @@
+// xsb233 (from random curve B-233): a = 1 and a large b.
+
+#[cfg(feature = "pure-rust")]
+use super::impl_pure_point;
+use super::{CurveParams, Extended};
+use crate::gf233::Gf;
+use subtle::{Choice, ConditionallySelectable};
+
+const B: Gf = Gf::from_be([
+    0x00000187F85627B9,
+    0x7874E747EE31E06D,
+    0x71CAAEEA52F21253,
+    0xE5F946D061DA9138,
+]);
+
+pub(crate) struct Params;
+
+impl CurveParams for Params {
+    const A_IS_ONE: bool = true;
+    const B: Gf = B;
+    const SQRT_B: Gf = Gf::from_be([
+        0x00000007D5EF4389,
+        0xDFF11ECDBA39C309,
+        0x70D3CE35CEBBA584,
+        0x73F64B4DC0F2686C,
+    ]);
+    const INV_SQRT_B: Gf = Gf::from_be([
+        0x000001A66ECA2D9C,
+        0x9A99F70ABB81FA42,
+        0xA7802D45255C81CC,
+        0xB6EE66F66C9A7586,
+    ]);
+
+    const NEUTRAL: Extended = Extended {
+        x: Gf::ZERO,
+        s: Self::SQRT_B,
+        z: Gf::ONE,
+        t: Gf::ZERO,
+    };
+
+    const GENERATOR: Extended = Extended {
+        x: GEN_X,
+        s: Gf::from_be([
+            0x00000184608AAFB7,
+            0x3AEF057D75B25F32,
+            0x1C37AE41375A7126,
+            0xEF3F941AE66B7661,
+        ]),
+        z: Gf::ONE,
+        t: GEN_X,
+    };
+
+    fn decode_denominator(w: &Gf) -> Gf {
+        w.square() + *w + Gf::ONE
+    }
+
+    fn decode_finish(w: &Gf, d: &Gf, inv_dd: &Gf) -> (Gf, Gf, Choice) {
+        // Rebuild x from w, with d = w^2 + w + 1:
+        //   x^2 + d*x + b = 0
+        // Setting x = d*f, this is f^2 + f = b/d^2, which is solvable only
+        // if Tr(b/d^2) = 0. Of the two solutions, we keep the x with trace 0.
+        let w_is_zero = w.is_zero();
+        let e = B * *inv_dd;
+        let ok = !e.trace();
+        let mut x = *d * e.halftrace();
+        x = Gf::conditional_select(&x, &(x + *d), x.trace());
+        let s = x * w.square();
+
+        // w = 0 decodes to the neutral (0, b)
+        let is_neutral = w_is_zero | !ok;
+        let x = Gf::conditional_select(&x, &Gf::ZERO, is_neutral);
+        let s = Gf::conditional_select(&s, &B, is_neutral);
+
+        (x, s, ok | w_is_zero)
+    }
+}
+
+const GEN_X: Gf = Gf::from_be([
+    0x0000017512FF306E,
+    0xA01C3744FBE1B95C,
+    0x002B20344E602BB0,
+    0x4E1F03DEF0CC6851,
+]);
+
+#[cfg(feature = "pure-rust")]
+#[derive(Clone, Copy)]
+pub struct Xsb233Point(pub(crate) Extended);
+
+#[cfg(feature = "pure-rust")]
+impl_pure_point!(Xsb233Point, Params);
@@ -0,0 +1,81 @@
+// xsk233 (from Koblitz curve K-233): a = 0, b = 1, so that sqrt(b) = 1.
+
+#[cfg(feature = "pure-rust")]
+use super::impl_pure_point;
+use super::{CurveParams, Extended};
+use crate::gf233::Gf;
+use subtle::{Choice, ConditionallySelectable};
+
+pub(crate) struct Params;
+
+impl CurveParams for Params {
+    const A_IS_ONE: bool = false;
+    const B: Gf = Gf::ONE;
+    const SQRT_B: Gf = Gf::ONE;
+    const INV_SQRT_B: Gf = Gf::ONE;
+
+    const NEUTRAL: Extended = Extended {
+        x: Gf::ZERO,
+        s: Gf::ONE,
+        z: Gf::ONE,
+        t: Gf::ZERO,
+    };
+
+    const GENERATOR: Extended = Extended {
+        x: GEN_X,
+        s: Gf::from_be([
+            0x000000EDFF3B4D4E,
+            0x5BAA47FCFDF3669D,
+            0xF7193250076F96C1,
+            0x66F9E0BF367D9A99,
+        ]),
+        z: Gf::ONE,
+        t: GEN_X,
+    };
+
+    fn mul_sqrt_b(v: Gf) -> Gf {
+        v
+    }
+
+    fn decode_denominator(w: &Gf) -> Gf {
+        w.square() + *w
+    }
+
+    fn decode_finish(w: &Gf, d: &Gf, inv_dd: &Gf) -> (Gf, Gf, Choice) {
+        // Rebuild x from w, with d = w^2 + w:
+        //   x^2 + d*x + d = 0
+        // Setting x = d*f, this is f^2 + f = 1/d^2, which is solvable only
+        // if Tr(1/d^2) = 0; solutions are f = halftrace(1/d^2) and f + 1.
+        // Only one of the two x has trace 0 and, of the two points, we
+        // want the one which is not the sum of a point of order 2.
+        let w_is_zero = w.is_zero();
+        let mut ok = !d.is_zero();
+        ok &= !inv_dd.trace();
+        let mut x = *d * inv_dd.halftrace();
+        ok &= !x.trace();
+        let g = (x.halftrace() + *w) * x;
+        x = Gf::conditional_select(&x, &(x + *d), g.trace());
+        let s = x * w.square();
+
+        // w = 0 decodes to the neutral (0, 1)
+        let is_neutral = w_is_zero | !ok;
+        let x = Gf::conditional_select(&x, &Gf::ZERO, is_neutral);
+        let s = Gf::conditional_select(&s, &Gf::ONE, is_neutral);
+
+        (x, s, ok | w_is_zero)
+    }
+}
+
+const GEN_X: Gf = Gf::from_be([
+    0x000001ECB92776D0,
+    0xFB3DEC476585B906,
+    0x5724EF7E1966BF54,
+    0xA850E5CBDDAA1BE6,
+]);
+
+#[cfg(feature = "pure-rust")]
+#[derive(Clone, Copy)]
+pub struct Xsk233Point(pub(crate) Extended);
+
+#[cfg(feature = "pure-rust")]
+impl_pure_point!(Xsk233Point, Params);
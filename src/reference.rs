@@ -0,0 +1,422 @@
+// A slow and simple reference implementation of the two groups, used to check
+// the backends. It works in affine coordinates on the standard curves K-233 and
+// B-233, i.e. y^2 + x*y = x^3 + a*x^2 + b, with bit-by-bit field arithmetic. It
+// makes no attempt at being fast or constant time, and shares no code with
+// either backend.
+//
+// The xs233 groups are isomorphic to the prime order subgroups of these curves,
+// and an element P of the subgroup maps to the encoding
+//   w = 0                      if P is the point at infinity
+//   w = (y + sqrt(b))/x + 1    otherwise.
+// The standard generators map to the xs233 generators.
+
+use crate::scalar::Scalar;
+use crate::Point;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// z^233 + z^74 + 1
+const MODULUS: [u64; 4] = [1, 1 << 10, 0, 1 << 41];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Fe([u64; 4]);
+
+impl Fe {
+    const ZERO: Fe = Fe([0; 4]);
+    const ONE: Fe = Fe([1, 0, 0, 0]);
+
+    fn from_hex(s: &str) -> Fe {
+        let s = format!("{s:0>64}");
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = 64 - 16 * (i + 1);
+            *limb = u64::from_str_radix(&s[start..start + 16], 16).unwrap();
+        }
+        Fe(limbs)
+    }
+
+    fn from_bytes(src: &[u8; 30]) -> Option<Fe> {
+        let mut limbs = [0u64; 4];
+        for (i, byte) in src.iter().enumerate() {
+            limbs[i / 8] |= (*byte as u64) << (8 * (i % 8));
+        }
+        (limbs[3] >> 41 == 0).then_some(Fe(limbs))
+    }
+
+    fn to_bytes(self) -> [u8; 30] {
+        let mut out = [0u8; 30];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = (self.0[i / 8] >> (8 * (i % 8))) as u8;
+        }
+        out
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.0[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    fn degree(&self) -> Option<usize> {
+        (0..4)
+            .rev()
+            .find(|&i| self.0[i] != 0)
+            .map(|i| 64 * i + 63 - self.0[i].leading_zeros() as usize)
+    }
+
+    fn shl(self, n: usize) -> Fe {
+        let (words, bits) = (n / 64, n % 64);
+        let mut out = [0u64; 4];
+        for (i, limb) in out.iter_mut().enumerate().skip(words) {
+            *limb = self.0[i - words] << bits;
+            if bits > 0 && i > words {
+                *limb |= self.0[i - words - 1] >> (64 - bits);
+            }
+        }
+        Fe(out)
+    }
+
+    fn add(self, rhs: Fe) -> Fe {
+        Fe([
+            self.0[0] ^ rhs.0[0],
+            self.0[1] ^ rhs.0[1],
+            self.0[2] ^ rhs.0[2],
+            self.0[3] ^ rhs.0[3],
+        ])
+    }
+
+    fn mul(self, rhs: Fe) -> Fe {
+        let mut out = Fe::ZERO;
+        let mut a = self;
+        for i in 0..233 {
+            if rhs.bit(i) {
+                out = out.add(a);
+            }
+            a = a.shl(1);
+            if a.bit(233) {
+                a = a.add(Fe(MODULUS));
+            }
+        }
+        out
+    }
+
+    fn square(self) -> Fe {
+        self.mul(self)
+    }
+
+    // extended euclidean algorithm, see Hankerson et al. Algorithm 2.48
+    fn invert(self) -> Fe {
+        assert_ne!(self, Fe::ZERO);
+        let (mut u, mut v) = (self, Fe(MODULUS));
+        let (mut g1, mut g2) = (Fe::ONE, Fe::ZERO);
+        while u != Fe::ONE {
+            let (du, dv) = (u.degree().unwrap(), v.degree().unwrap());
+            if du < dv {
+                core::mem::swap(&mut u, &mut v);
+                core::mem::swap(&mut g1, &mut g2);
+            }
+            let j = du.abs_diff(dv);
+            u = u.add(v.shl(j));
+            g1 = g1.add(g2.shl(j));
+        }
+        g1
+    }
+
+    fn sqrt(self) -> Fe {
+        (0..232).fold(self, |acc, _| acc.square())
+    }
+
+    fn trace(self) -> bool {
+        let mut t = self;
+        let mut sum = self;
+        for _ in 1..233 {
+            t = t.square();
+            sum = sum.add(t);
+        }
+        assert!(sum == Fe::ZERO || sum == Fe::ONE);
+        sum == Fe::ONE
+    }
+
+    fn halftrace(self) -> Fe {
+        let mut t = self;
+        let mut sum = self;
+        for _ in 0..116 {
+            t = t.square().square();
+            sum = sum.add(t);
+        }
+        sum
+    }
+}
+
+struct Curve {
+    a: Fe,
+    b: Fe,
+    sqrt_b: Fe,
+    generator: Affine,
+    order: [u8; 30],
+}
+
+// None is the point at infinity
+type Affine = Option<(Fe, Fe)>;
+
+impl Curve {
+    fn new(a: Fe, b: &str, gx: &str, gy: &str, order: &str) -> Curve {
+        let b = Fe::from_hex(b);
+        let mut order = hex::decode(format!("{order:0>60}")).unwrap();
+        order.reverse();
+
+        let curve = Curve {
+            a,
+            b,
+            sqrt_b: b.sqrt(),
+            generator: Some((Fe::from_hex(gx), Fe::from_hex(gy))),
+            order: order.try_into().unwrap(),
+        };
+        assert!(curve.is_on_curve(&curve.generator));
+        curve
+    }
+
+    fn k233() -> Curve {
+        Curve::new(
+            Fe::ZERO,
+            "1",
+            "017232ba853a7e731af129f22ff4149563a419c26bf50a4c9d6eefad6126",
+            "01db537dece819b7f70f555a67c427a8cd9bf18aeb9b56e0c11056fae6a3",
+            "8000000000000000000000000000069d5bb915bcd46efb1ad5f173abdf",
+        )
+    }
+
+    fn b233() -> Curve {
+        Curve::new(
+            Fe::ONE,
+            "0066647ede6c332c7f8c0923bb58213b333b20e9ce4281fe115f7d8f90ad",
+            "00fac9dfcbac8313bb2139f1bb755fef65bc391f8b36f8f8eb7371fd558b",
+            "01006a08a41903350678e58528bebf8a0beff867a7ca36716f7e01f81052",
+            "01000000000000000000000000000013e974e72f8a6922031d2603cfe0d7",
+        )
+    }
+
+    fn is_on_curve(&self, p: &Affine) -> bool {
+        match *p {
+            None => true,
+            Some((x, y)) => {
+                let lhs = y.square().add(x.mul(y));
+                let rhs = x.square().mul(x).add(self.a.mul(x.square())).add(self.b);
+                lhs == rhs
+            }
+        }
+    }
+
+    fn neg(&self, p: &Affine) -> Affine {
+        p.map(|(x, y)| (x, x.add(y)))
+    }
+
+    fn add(&self, p: &Affine, q: &Affine) -> Affine {
+        let ((x1, y1), (x2, y2)) = match (*p, *q) {
+            (None, _) => return *q,
+            (_, None) => return *p,
+            (Some(p), Some(q)) => (p, q),
+        };
+
+        if x1 == x2 && y2 == x1.add(y1) {
+            return None;
+        }
+
+        let (x3, y3) = if x1 == x2 {
+            let l = x1.add(y1.mul(x1.invert()));
+            let x3 = l.square().add(l).add(self.a);
+            (x3, x1.square().add(l.add(Fe::ONE).mul(x3)))
+        } else {
+            let l = y1.add(y2).mul(x1.add(x2).invert());
+            let x3 = l.square().add(l).add(x1).add(x2).add(self.a);
+            (x3, l.mul(x1.add(x3)).add(x3).add(y1))
+        };
+
+        Some((x3, y3))
+    }
+
+    fn mul(&self, p: &Affine, scalar: &[u8]) -> Affine {
+        let mut acc = None;
+        for byte in scalar.iter().rev() {
+            for i in (0..8).rev() {
+                acc = self.add(&acc, &acc);
+                if byte >> i & 1 == 1 {
+                    acc = self.add(&acc, p);
+                }
+            }
+        }
+        acc
+    }
+
+    fn encode(&self, p: &Affine) -> [u8; 30] {
+        match *p {
+            None => [0u8; 30],
+            Some((x, y)) => y.add(self.sqrt_b).mul(x.invert()).add(Fe::ONE).to_bytes(),
+        }
+    }
+
+    // Inverts the encoding by solving for the point directly: with u = w + 1
+    // and y = u*x + sqrt(b), the curve equation becomes
+    //   x^2 + (u^2 + u + a)*x + sqrt(b) = 0.
+    // We try both roots and keep the one in the prime order subgroup.
+    fn decode(&self, repr: &[u8; 30]) -> Option<Affine> {
+        let w = Fe::from_bytes(repr)?;
+        if w == Fe::ZERO {
+            return Some(None);
+        }
+
+        let u = w.add(Fe::ONE);
+        let c = u.square().add(u).add(self.a);
+        if c == Fe::ZERO {
+            return None;
+        }
+
+        // x = c*f with f^2 + f = sqrt(b)/c^2
+        let e = self.sqrt_b.mul(c.square().invert());
+        if e.trace() {
+            return None;
+        }
+
+        let x = c.mul(e.halftrace());
+        [x, x.add(c)]
+            .into_iter()
+            .map(|x| Some((x, u.mul(x).add(self.sqrt_b))))
+            .find(|p| self.mul(p, &self.order).is_none())
+    }
+}
+
+fn check_generator<P: Point<EncodedPoint = [u8; 30]> + 'static>(curve: &Curve) {
+    assert!(curve.mul(&curve.generator, &curve.order).is_none());
+    assert_eq!(P::generator().to_encoded(), curve.encode(&curve.generator));
+    assert_eq!(P::neutral().to_encoded(), curve.encode(&None));
+
+    let mut order_minus_one = curve.order;
+    order_minus_one[0] -= 1;
+    let scalar = Scalar::new(order_minus_one);
+    assert_eq!(
+        P::mulgen(&scalar).to_encoded(),
+        curve.encode(&curve.neg(&curve.generator))
+    );
+}
+
+fn check_decode<P: Point<EncodedPoint = [u8; 30]> + 'static>(curve: &Curve, seed: u8) {
+    let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+
+    for i in 0..60 {
+        let mut buf = [0u8; 30];
+        rng.fill(&mut buf);
+        // most encodings with the high bits set are not canonical, but
+        // test some of them anyway
+        if i % 10 != 0 {
+            buf[29] &= 1;
+        }
+
+        let mut point = P::default();
+        let is_valid: bool = point.decode(&buf).into();
+        match curve.decode(&buf) {
+            Some(expected) => {
+                assert!(is_valid, "{}", hex::encode(buf));
+                assert_eq!(point.to_encoded(), curve.encode(&expected));
+                assert_eq!(point.to_encoded(), buf);
+            }
+            None => {
+                assert!(!is_valid, "{}", hex::encode(buf));
+                assert!(bool::from(point.is_neutral()));
+            }
+        }
+    }
+}
+
+fn check_sequence<P: Point<EncodedPoint = [u8; 30]> + 'static>(curve: &Curve, seed: u8) {
+    let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+
+    let mut point = *P::generator();
+    let mut expected = curve.generator;
+
+    for _ in 0..40 {
+        let mut buf = [0u8; 30];
+        rng.fill(&mut buf);
+        let scalar = Scalar::new(buf);
+
+        match rng.gen_range(0..8) {
+            0 | 1 => {
+                let other = P::mulgen(&scalar);
+                let other_expected = curve.mul(&curve.generator, &buf);
+                assert_eq!(other.to_encoded(), curve.encode(&other_expected));
+
+                if rng.gen() {
+                    point.add_assign(&other);
+                    expected = curve.add(&expected, &other_expected);
+                } else {
+                    point.sub_assign(&other);
+                    expected = curve.add(&expected, &curve.neg(&other_expected));
+                }
+            }
+            2 => {
+                point.double_inplace();
+                expected = curve.add(&expected, &expected);
+            }
+            3 => {
+                let n = rng.gen_range(0..6);
+                point.xdouble_inplace(n);
+                for _ in 0..n {
+                    expected = curve.add(&expected, &expected);
+                }
+            }
+            4 => {
+                point.mul_inplace(&scalar);
+                expected = curve.mul(&expected, &buf);
+            }
+            5 => {
+                let short: Scalar<3> = Scalar::new(buf[..3].try_into().unwrap());
+                point.mul_inplace(&short);
+                expected = curve.mul(&expected, &buf[..3]);
+            }
+            6 => {
+                point.neg_inplace();
+                expected = curve.neg(&expected);
+            }
+            _ => {
+                // adding a point to itself and to its negation
+                let copy = point;
+                point.add_assign(&copy);
+                expected = curve.add(&expected, &expected);
+                let mut zero = P::default();
+                zero.sub(&point, &point);
+                assert!(bool::from(zero.is_neutral()));
+            }
+        }
+
+        assert!(curve.is_on_curve(&expected));
+        assert_eq!(point.to_encoded(), curve.encode(&expected));
+        assert_eq!(bool::from(point.is_neutral()), expected.is_none());
+    }
+}
+
+#[test]
+fn xsk233_matches_reference() {
+    type P = crate::xsk233::Xsk233Point;
+    let curve = Curve::k233();
+    check_generator::<P>(&curve);
+    check_decode::<P>(&curve, 1);
+    check_sequence::<P>(&curve, 2);
+}
+
+#[test]
+fn xsb233_matches_reference() {
+    type P = crate::xsb233::Xsb233Point;
+    let curve = Curve::b233();
+    check_generator::<P>(&curve);
+    check_decode::<P>(&curve, 3);
+    check_sequence::<P>(&curve, 4);
+}
+
+#[cfg(all(feature = "ffi", feature = "pure-rust"))]
+#[test]
+fn pure_matches_reference() {
+    let curve = Curve::k233();
+    check_decode::<crate::pure::Xsk233Point>(&curve, 5);
+    check_sequence::<crate::pure::Xsk233Point>(&curve, 6);
+
+    let curve = Curve::b233();
+    check_decode::<crate::pure::Xsb233Point>(&curve, 7);
+    check_sequence::<crate::pure::Xsb233Point>(&curve, 8);
+}
@@ -1,4 +1,10 @@
-use std::ffi::c_void;
+use crate::curve::Curve;
+use crate::Error;
+#[cfg(feature = "ffi")]
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub struct Scalar<const N: usize>([u8; N]);
@@ -12,12 +18,402 @@ impl<const N: usize> Scalar<N> {
         N
     }
 
-    pub fn as_void_ptr(&self) -> *const c_void {
-        let scalar_ptr: *const Self = self;
-        scalar_ptr.cast()
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    // Returns a pointer to the N bytes of the scalar, for c-xs233. c-xs233 accepts
+    // at most 30 bytes, which is checked at compile time.
+    #[cfg(feature = "ffi")]
+    pub(crate) fn as_void_ptr(&self) -> *const c_void {
+        const { assert!(N <= 30, "c-xs233 accepts scalars of at most 30 bytes") };
+        self.0.as_ptr().cast()
     }
 
     pub fn as_bytes(&self) -> &[u8; N] {
         &self.0
     }
 }
+
+/// An integer modulo the order of the group described by `C`, e.g.
+/// `ModScalar<Xsk233>`. All operations are constant time.
+///
+/// The canonical encoding is 30 bytes little-endian, and values must be smaller
+/// than the order.
+pub struct ModScalar<C: Curve>([u64; 4], PhantomData<C>);
+
+impl<C: Curve> ModScalar<C> {
+    pub const ZERO: Self = ModScalar([0; 4], PhantomData);
+    pub const ONE: Self = ModScalar([1, 0, 0, 0], PhantomData);
+
+    /// Decodes a canonical scalar, i.e. one that is smaller than the order.
+    pub fn from_canonical_bytes(le_bytes: &[u8; 30]) -> CtOption<Self> {
+        let mut limbs = [0u64; 4];
+        for (i, byte) in le_bytes.iter().enumerate() {
+            limbs[i / 8] |= (*byte as u64) << (8 * (i % 8));
+        }
+
+        let (_, borrow) = sub_with_borrow(&limbs, &C::ORDER);
+        CtOption::new(ModScalar(limbs, PhantomData), Choice::from(borrow as u8))
+    }
+
+    /// Decodes a canonical scalar that is not zero, as needed for secret keys.
+    /// Fails with [`Error::NonCanonicalScalar`] or [`Error::ZeroScalar`].
+    pub fn from_bytes_non_zero(le_bytes: &[u8; 30]) -> Result<Self, Error> {
+        let scalar: Self =
+            Option::from(Self::from_canonical_bytes(le_bytes)).ok_or(Error::NonCanonicalScalar)?;
+        if scalar.is_zero().into() {
+            return Err(Error::ZeroScalar);
+        }
+        Ok(scalar)
+    }
+
+    /// Interprets the little-endian bytes as an integer of any size and reduces it
+    /// modulo the order. For uniformly random input, the output is close to uniform
+    /// if the input is at least 16 bytes longer than the order.
+    pub fn from_bytes_mod_order(le_bytes: &[u8]) -> Self {
+        let mut acc = Self::ZERO;
+        for byte in le_bytes.iter().rev() {
+            for i in (0..8).rev() {
+                acc = acc.double_and_add_bit(byte >> i & 1);
+            }
+        }
+        acc
+    }
+
+    /// Samples a uniformly random non-zero scalar.
+    #[cfg(feature = "rand_core")]
+    pub fn random(rng: &mut impl rand_core::CryptoRngCore) -> Self {
+        loop {
+            let mut buf = [0u8; 48];
+            rng.fill_bytes(&mut buf);
+            let scalar = Self::from_bytes_mod_order(&buf);
+            #[cfg(feature = "zeroize")]
+            zeroize::Zeroize::zeroize(&mut buf);
+
+            // this only loops with negligible probability
+            if !bool::from(scalar.is_zero()) {
+                return scalar;
+            }
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        let mut out = [0u8; 30];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = (self.0[i / 8] >> (8 * (i % 8))) as u8;
+        }
+        out
+    }
+
+    pub fn to_scalar(&self) -> Scalar<30> {
+        Scalar::new(self.to_bytes())
+    }
+
+    pub fn is_zero(&self) -> Choice {
+        self.ct_eq(&Self::ZERO)
+    }
+
+    /// Returns the multiplicative inverse, or zero if the scalar is zero.
+    pub fn invert(&self) -> Self {
+        // Fermat's little theorem: x^(r - 2). The exponent is public.
+        let (exp, _) = sub_with_borrow(&C::ORDER, &[2, 0, 0, 0]);
+        let mut acc = Self::ONE;
+        for i in (0..256).rev() {
+            acc = acc * acc;
+            if exp[i / 64] >> (i % 64) & 1 == 1 {
+                acc *= *self;
+            }
+        }
+        acc
+    }
+
+    // returns 2*self + bit mod r
+    fn double_and_add_bit(&self, bit: u8) -> Self {
+        let mut doubled = [0u64; 4];
+        let mut carry = bit as u64;
+        for (out, limb) in doubled.iter_mut().zip(self.0) {
+            *out = (limb << 1) | carry;
+            carry = limb >> 63;
+        }
+        Self::reduce_once(doubled)
+    }
+
+    // subtracts the order if the value is at least the order. the value must be
+    // smaller than twice the order.
+    fn reduce_once(limbs: [u64; 4]) -> Self {
+        let (reduced, borrow) = sub_with_borrow(&limbs, &C::ORDER);
+        let mut out = limbs;
+        for (out, reduced) in out.iter_mut().zip(reduced) {
+            out.conditional_assign(&reduced, Choice::from(!borrow as u8));
+        }
+        ModScalar(out, PhantomData)
+    }
+}
+
+// returns (a - b, borrow)
+fn sub_with_borrow(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], bool) {
+    let mut out = [0u64; 4];
+    let mut borrow = false;
+    for i in 0..4 {
+        let (d, b1) = a[i].overflowing_sub(b[i]);
+        let (d, b2) = d.overflowing_sub(borrow as u64);
+        out[i] = d;
+        borrow = b1 | b2;
+    }
+    (out, borrow)
+}
+
+// returns a + b, ignoring the final carry
+fn add_with_carry(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut out = [0u64; 4];
+    let mut carry = false;
+    for i in 0..4 {
+        let (s, c1) = a[i].overflowing_add(b[i]);
+        let (s, c2) = s.overflowing_add(carry as u64);
+        out[i] = s;
+        carry = c1 | c2;
+    }
+    out
+}
+
+impl<C: Curve> Clone for ModScalar<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Curve> Copy for ModScalar<C> {}
+
+impl<C: Curve> Default for ModScalar<C> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<C: Curve> core::fmt::Debug for ModScalar<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ModScalar<{}>(", C::NAME)?;
+        for byte in self.to_bytes().iter().rev() {
+            write!(f, "{byte:02x}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl<C: Curve> ConstantTimeEq for ModScalar<C> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl<C: Curve> ConditionallySelectable for ModScalar<C> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut out = a.0;
+        for (out, b) in out.iter_mut().zip(b.0) {
+            out.conditional_assign(&b, choice);
+        }
+        ModScalar(out, PhantomData)
+    }
+}
+
+impl<C: Curve> PartialEq for ModScalar<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<C: Curve> Eq for ModScalar<C> {}
+
+impl<C: Curve> Add for ModScalar<C> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        // both values are below 2^240, so this does not overflow
+        Self::reduce_once(add_with_carry(&self.0, &rhs.0))
+    }
+}
+
+impl<C: Curve> Sub for ModScalar<C> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl<C: Curve> Neg for ModScalar<C> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        // r - x is in [1, r] and needs to be reduced for x = 0
+        let (diff, _) = sub_with_borrow(&C::ORDER, &self.0);
+        Self::reduce_once(diff)
+    }
+}
+
+impl<C: Curve> Mul for ModScalar<C> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let mut wide = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let t = wide[i + j] as u128 + (self.0[i] as u128) * (rhs.0[j] as u128) + carry;
+                wide[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            wide[i + 4] = carry as u64;
+        }
+
+        // reduce bit by bit, from the top
+        let mut acc = Self::ZERO;
+        for i in (0..512).rev() {
+            acc = acc.double_and_add_bit((wide[i / 64] >> (i % 64) & 1) as u8);
+        }
+        acc
+    }
+}
+
+impl<C: Curve> AddAssign for ModScalar<C> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<C: Curve> SubAssign for ModScalar<C> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<C: Curve> MulAssign for ModScalar<C> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const N: usize> zeroize::Zeroize for Scalar<N> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<C: Curve> zeroize::Zeroize for ModScalar<C> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Decodes a canonical scalar, rejecting input that is not 30 bytes long or not
+/// smaller than the order.
+impl<C: Curve> TryFrom<&[u8]> for ModScalar<C> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: &[u8; 30] = bytes.try_into().map_err(|_| Error::InvalidLength {
+            expected: 30,
+            actual: bytes.len(),
+        })?;
+        Option::from(Self::from_canonical_bytes(bytes)).ok_or(Error::NonCanonicalScalar)
+    }
+}
+
+impl<C: Curve> From<ModScalar<C>> for Scalar<30> {
+    fn from(scalar: ModScalar<C>) -> Self {
+        scalar.to_scalar()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use crate::Point;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    fn check_arithmetic<C: Curve<Scalar = ModScalar<C>>>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+
+        let mut order = [0u8; 32];
+        for (i, limb) in C::ORDER.iter().enumerate() {
+            order[8 * i..8 * i + 8].copy_from_slice(&limb.to_le_bytes());
+        }
+        let order_bytes: [u8; 30] = order[..30].try_into().unwrap();
+        assert!(bool::from(
+            ModScalar::<C>::from_canonical_bytes(&order_bytes).is_none()
+        ));
+        assert_eq!(
+            ModScalar::<C>::from_bytes_mod_order(&order),
+            ModScalar::ZERO
+        );
+
+        let minus_one = -ModScalar::<C>::ONE;
+        assert_eq!(minus_one + ModScalar::ONE, ModScalar::ZERO);
+        assert_eq!(minus_one * minus_one, ModScalar::ONE);
+        assert_eq!(-ModScalar::<C>::ZERO, ModScalar::ZERO);
+        assert_eq!(ModScalar::<C>::ZERO.invert(), ModScalar::ZERO);
+
+        assert_eq!(
+            ModScalar::<C>::try_from(&order_bytes[..]),
+            Err(Error::NonCanonicalScalar)
+        );
+        assert_eq!(
+            ModScalar::<C>::from_bytes_non_zero(&order_bytes),
+            Err(Error::NonCanonicalScalar)
+        );
+        assert_eq!(
+            ModScalar::<C>::from_bytes_non_zero(&[0; 30]),
+            Err(Error::ZeroScalar)
+        );
+        assert_eq!(
+            ModScalar::<C>::from_bytes_non_zero(&minus_one.to_bytes()),
+            Ok(minus_one)
+        );
+        assert_eq!(
+            ModScalar::<C>::try_from(&order[..]),
+            Err(Error::InvalidLength {
+                expected: 30,
+                actual: 32
+            })
+        );
+        assert_eq!(
+            ModScalar::<C>::try_from(&minus_one.to_bytes()[..]),
+            Ok(minus_one)
+        );
+
+        for _ in 0..10 {
+            let mut buf = [0u8; 64];
+            rng.fill(&mut buf);
+            let a = ModScalar::<C>::from_bytes_mod_order(&buf);
+            rng.fill(&mut buf);
+            let b = ModScalar::<C>::from_bytes_mod_order(&buf);
+
+            let decoded = ModScalar::<C>::from_canonical_bytes(&a.to_bytes()).unwrap();
+            assert_eq!(decoded, a);
+            assert_eq!(a * a.invert(), ModScalar::ONE);
+            assert_eq!((a + b) - b, a);
+            assert_eq!(a * (b + ModScalar::ONE), a * b + a);
+
+            // the scalars agree with the group
+            let mut lhs = C::Point::mulgen(&a.into());
+            lhs.add_assign(&C::Point::mulgen(&b.into()));
+            let rhs = C::Point::mulgen(&(a + b).into());
+            assert!(bool::from(lhs.ct_eq(&rhs)));
+
+            let mut lhs = C::Point::default();
+            lhs.mul(&C::Point::mulgen(&a.into()), &b.to_scalar());
+            let rhs = C::Point::mulgen(&(a * b).into());
+            assert!(bool::from(lhs.ct_eq(&rhs)));
+        }
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn arithmetic() { check_arithmetic::<C>(seed) }
+    }
+}
@@ -0,0 +1,591 @@
+//! Schnorr signatures.
+//!
+//! A signature is the 30-byte encoding of the commitment `R = k*G`, followed by the
+//! 30-byte canonical encoding of `s = k + e*x`, where `x` is the secret key and
+//! `e = H(R || A || msg)` is the challenge for the public key `A = x*G`. The nonce
+//! `k` is derived deterministically from the secret key and the message, so signing
+//! needs no randomness. `H` is SHA-512, reduced modulo the group order, and
+//! domain-separated by purpose and curve.
+//!
+//! Verification decodes `R` and `s` strictly: `R` must be a valid point encoding
+//! and `s` must be smaller than the order, so every signature has exactly one valid
+//! encoding.
+
+use crate::curve::Curve;
+use crate::scalar::ModScalar;
+use crate::{Error, Point};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha512};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// The length of an encoded signature in bytes.
+pub const SIGNATURE_LEN: usize = 60;
+
+const NONCE_TAG: &[u8] = b"xs233-schnorr-nonce";
+const HEDGED_NONCE_TAG: &[u8] = b"xs233-schnorr-hedged-nonce";
+const CHALLENGE_TAG: &[u8] = b"xs233-schnorr-challenge";
+
+// hashes the domain tag, the curve name and the parts to a scalar. all parts except
+// the last one have a fixed length, so the input is unambiguous.
+fn hash_to_scalar<C: Curve>(tag: &[u8], parts: &[&[u8]]) -> ModScalar<C> {
+    let mut hasher = Sha512::new();
+    hasher.update([tag.len() as u8]);
+    hasher.update(tag);
+    hasher.update([C::NAME.len() as u8]);
+    hasher.update(C::NAME.as_bytes());
+    for part in parts {
+        hasher.update(part);
+    }
+    ModScalar::from_bytes_mod_order(&hasher.finalize())
+}
+
+pub(crate) fn challenge<C: Curve>(r: &[u8; 30], public: &[u8; 30], msg: &[u8]) -> ModScalar<C> {
+    hash_to_scalar(CHALLENGE_TAG, &[r, public, msg])
+}
+
+/// A secret key for signing messages.
+#[derive(Clone)]
+pub struct SigningKey<C: Curve> {
+    secret: ModScalar<C>,
+    verifying_key: VerifyingKey<C>,
+}
+
+/// A public key for verifying signatures, which is never the neutral.
+#[derive(Clone, Copy, Debug)]
+pub struct VerifyingKey<C: Curve> {
+    point: C::Point,
+    bytes: [u8; 30],
+}
+
+/// A strictly decoded signature.
+#[derive(Clone, Copy, Debug)]
+pub struct Signature<C: Curve> {
+    r: C::Point,
+    r_bytes: [u8; 30],
+    s: ModScalar<C>,
+}
+
+impl<C: Curve> SigningKey<C> {
+    pub fn random_from_rng(rng: &mut impl CryptoRngCore) -> Self {
+        Self::from_scalar(ModScalar::random(rng))
+    }
+
+    /// Decodes a secret key from its canonical 30-byte encoding. Zero is rejected.
+    pub fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+        let secret = ModScalar::from_bytes_non_zero(bytes)?;
+        Ok(Self::from_scalar(secret))
+    }
+
+    fn from_scalar(secret: ModScalar<C>) -> Self {
+        let mut scalar = secret.to_scalar();
+        let point = C::Point::mulgen(&scalar);
+        scalar.zeroize();
+        SigningKey {
+            secret,
+            verifying_key: VerifyingKey::from_point(point),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        self.secret.to_bytes()
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey<C> {
+        &self.verifying_key
+    }
+
+    /// Signs the message with a nonce derived from the secret key and the message.
+    pub fn sign(&self, msg: &[u8]) -> Signature<C> {
+        self.sign_with_nonce_input(NONCE_TAG, &[], msg)
+    }
+
+    /// Signs the message with a nonce derived from the secret key, fresh randomness
+    /// and the message. The signatures are not deterministic, but the nonces stay
+    /// secret even if the randomness is bad.
+    pub fn sign_with_rng(&self, rng: &mut impl CryptoRngCore, msg: &[u8]) -> Signature<C> {
+        let mut randomness = [0u8; 32];
+        rng.fill_bytes(&mut randomness);
+        let signature = self.sign_with_nonce_input(HEDGED_NONCE_TAG, &randomness, msg);
+        randomness.zeroize();
+        signature
+    }
+
+    fn sign_with_nonce_input(&self, tag: &[u8], randomness: &[u8], msg: &[u8]) -> Signature<C> {
+        let mut secret_bytes = self.secret.to_bytes();
+        let mut nonce: ModScalar<C> = hash_to_scalar(tag, &[&secret_bytes, randomness, msg]);
+        secret_bytes.zeroize();
+
+        let mut nonce_scalar = nonce.to_scalar();
+        let r = C::Point::mulgen(&nonce_scalar);
+        nonce_scalar.zeroize();
+        let r_bytes = r.to_encoded();
+
+        let e = challenge::<C>(&r_bytes, &self.verifying_key.bytes, msg);
+        let s = nonce + e * self.secret;
+        nonce.zeroize();
+
+        Signature { r, r_bytes, s }
+    }
+}
+
+impl<C: Curve> VerifyingKey<C> {
+    fn from_point(point: C::Point) -> Self {
+        let bytes = point.to_encoded();
+        VerifyingKey { point, bytes }
+    }
+
+    /// Decodes a public key, rejecting invalid encodings and the neutral.
+    pub fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+        let point = C::Point::try_decode_non_neutral(bytes)?;
+        Ok(VerifyingKey {
+            point,
+            bytes: *bytes,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        self.bytes
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 30] {
+        &self.bytes
+    }
+
+    pub fn as_point(&self) -> &C::Point {
+        &self.point
+    }
+
+    /// Checks that `s*G = R + e*A`.
+    pub fn verify(&self, msg: &[u8], signature: &Signature<C>) -> Result<(), Error> {
+        let e = challenge::<C>(&signature.r_bytes, &self.bytes, msg);
+
+        let mut check = C::Point::default();
+        check.mul(&self.point, &(-e).to_scalar());
+        check.add_assign(&C::Point::mulgen(&signature.s.to_scalar()));
+
+        if check.ct_eq(&signature.r).into() {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
+
+impl<C: Curve> Signature<C> {
+    /// Decodes a signature, rejecting invalid encodings of `R` and non-canonical `s`.
+    pub fn from_bytes(bytes: &[u8; SIGNATURE_LEN]) -> Result<Self, Error> {
+        let r_bytes: [u8; 30] = bytes[..30].try_into().unwrap();
+        let s_bytes: &[u8; 30] = bytes[30..].try_into().unwrap();
+
+        let r = C::Point::try_decode(&r_bytes)?;
+        let s = Option::from(ModScalar::from_canonical_bytes(s_bytes))
+            .ok_or(Error::NonCanonicalScalar)?;
+        Ok(Signature { r, r_bytes, s })
+    }
+
+    pub fn to_bytes(&self) -> [u8; SIGNATURE_LEN] {
+        let mut out = [0u8; SIGNATURE_LEN];
+        out[..30].copy_from_slice(&self.r_bytes);
+        out[30..].copy_from_slice(&self.s.to_bytes());
+        out
+    }
+}
+
+/// Verifies many signatures at once, which is considerably faster than verifying
+/// them one by one.
+///
+/// The equations `s_i*G = R_i + e_i*A_i` are combined with random 128-bit weights
+/// `z_i` into the single equation
+/// `(sum z_i*s_i)*G - sum z_i*R_i - sum (z_i*e_i)*A_i = 0`, which is checked with
+/// one variable-time multi-scalar multiplication. If it does not hold, the
+/// signatures are verified one by one, and the index of the first invalid one is
+/// returned in [`Error::BatchVerificationFailed`].
+#[cfg(feature = "alloc")]
+pub fn verify_batch<C: Curve>(
+    items: &[(&VerifyingKey<C>, &[u8], &Signature<C>)],
+    rng: &mut impl CryptoRngCore,
+) -> Result<(), Error> {
+    let mut pairs = Vec::with_capacity(2 * items.len() + 1);
+    let mut s_sum = ModScalar::ZERO;
+    for (key, msg, signature) in items {
+        let mut weight = [0u8; 16];
+        rng.fill_bytes(&mut weight);
+        let z = ModScalar::<C>::from_bytes_mod_order(&weight);
+        let e = challenge::<C>(&signature.r_bytes, &key.bytes, msg);
+
+        s_sum += z * signature.s;
+        pairs.push((signature.r, -z));
+        pairs.push((key.point, -(z * e)));
+    }
+    pairs.push((*C::Point::generator(), s_sum));
+
+    if crate::vartime::msm::<C>(&pairs).is_neutral().into() {
+        return Ok(());
+    }
+    for (index, (key, msg, signature)) in items.iter().enumerate() {
+        if key.verify(msg, signature).is_err() {
+            return Err(Error::BatchVerificationFailed { index });
+        }
+    }
+    // only reachable with negligible probability
+    Ok(())
+}
+
+impl<C: Curve> TryFrom<&[u8]> for VerifyingKey<C> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: &[u8; 30] = bytes.try_into().map_err(|_| Error::InvalidLength {
+            expected: 30,
+            actual: bytes.len(),
+        })?;
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<C: Curve> TryFrom<&[u8]> for Signature<C> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: &[u8; SIGNATURE_LEN] = bytes.try_into().map_err(|_| Error::InvalidLength {
+            expected: SIGNATURE_LEN,
+            actual: bytes.len(),
+        })?;
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<C: Curve> PartialEq for VerifyingKey<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<C: Curve> Eq for VerifyingKey<C> {}
+
+// encodings are unique, so comparing them is the same as comparing the values
+impl<C: Curve> PartialEq for Signature<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl<C: Curve> Eq for Signature<C> {}
+
+#[cfg(feature = "signature")]
+impl<C: Curve> signature::Signer<Signature<C>> for SigningKey<C> {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature<C>, signature::Error> {
+        Ok(self.sign(msg))
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: Curve> signature::RandomizedSigner<Signature<C>> for SigningKey<C> {
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> Result<Signature<C>, signature::Error> {
+        Ok(self.sign_with_rng(rng, msg))
+    }
+}
+
+// the digest variants sign the hash of the message as the message
+#[cfg(feature = "signature")]
+impl<C: Curve, D: signature::digest::Digest> signature::DigestSigner<D, Signature<C>>
+    for SigningKey<C>
+{
+    fn try_sign_digest(&self, digest: D) -> Result<Signature<C>, signature::Error> {
+        Ok(self.sign(&digest.finalize()))
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: Curve> signature::Keypair for SigningKey<C> {
+    type VerifyingKey = VerifyingKey<C>;
+
+    fn verifying_key(&self) -> VerifyingKey<C> {
+        self.verifying_key
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: Curve> signature::Verifier<Signature<C>> for VerifyingKey<C> {
+    fn verify(&self, msg: &[u8], signature: &Signature<C>) -> Result<(), signature::Error> {
+        Ok(VerifyingKey::verify(self, msg, signature)?)
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: Curve, D: signature::digest::Digest> signature::DigestVerifier<D, Signature<C>>
+    for VerifyingKey<C>
+{
+    fn verify_digest(&self, digest: D, signature: &Signature<C>) -> Result<(), signature::Error> {
+        Ok(self.verify(&digest.finalize(), signature)?)
+    }
+}
+
+#[cfg(feature = "signature")]
+impl<C: Curve> signature::SignatureEncoding for Signature<C> {
+    type Repr = [u8; SIGNATURE_LEN];
+}
+
+impl<C: Curve> From<Signature<C>> for [u8; SIGNATURE_LEN] {
+    fn from(signature: Signature<C>) -> Self {
+        signature.to_bytes()
+    }
+}
+
+impl<C: Curve> Drop for SigningKey<C> {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl<C: Curve> ZeroizeOnDrop for SigningKey<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use crate::xsb233::Xsb233;
+    use crate::xsk233::Xsk233;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // (secret key, message, signature), generated with this implementation
+    const XSK233_VECTORS: &[(&str, &[u8], &str)] = &[
+        (
+            "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d00",
+            b"",
+            "ed5ec951b42b92f9ebdafe3165cb3ccfb574d692a33d41c5ec82331c8c00c4d88e9aa0dea95d4cf397a85aeede97ae98be8e13bc6006c5788a692e00",
+        ),
+        (
+            "010000000000000000000000000000000000000000000000000000000000",
+            b"abc",
+            "1ae915891d7bee6bca26db34fa35876db4977120fbe7952d690e658f6f01b76b034cb51b35309d7d7f636fc22b01d39a52c288ae9b1ddbd392282600",
+        ),
+        (
+            "ffeeddccbbaa99887766554433221100ffeeddccbbaa9988776655443300",
+            b"The quick brown fox jumps over the lazy dog",
+            "63fb092b706d1ab95f130f4ed5f3e5adc0aa24e06c06a9b7f9ee5d508e011e50743a1110e15632154d0a3f25274cda0ceb43d4e9f19be7fff9482000",
+        ),
+    ];
+    const XSB233_VECTORS: &[(&str, &[u8], &str)] = &[
+        (
+            "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d00",
+            b"",
+            "dfd1d244ee6d50c9e5c953372ca0ac50833ee1d2871e27fa1ee2404b2101b98d25e92f36e78b8d25747b5b1e093d311e9b4d7d4cc5542a265c1f6200",
+        ),
+        (
+            "010000000000000000000000000000000000000000000000000000000000",
+            b"abc",
+            "3160e425e276b49fbb549128f4bf21565c8ee8c15bbe93a57ebccb0ed6006321ad398ada83fddae6687e7282a1a8f7d2ad6ad602e1c75406fcd62800",
+        ),
+        (
+            "ffeeddccbbaa99887766554433221100ffeeddccbbaa9988776655443300",
+            b"The quick brown fox jumps over the lazy dog",
+            "1ae722e31e1f7de5fea8d531c1de79e37265ef118ce4ce25d18f2e78d900e2df886dc028039a11b19c9f3a88eee71428bfb1240d9d2e6fe6b218cc00",
+        ),
+    ];
+
+    fn check_vectors<C: Curve>(vectors: &[(&str, &[u8], &str)]) {
+        for (secret, msg, expected) in vectors {
+            let key =
+                SigningKey::<C>::from_bytes(&hex::decode(secret).unwrap().try_into().unwrap())
+                    .unwrap();
+            let signature = key.sign(msg);
+            assert_eq!(hex::encode(signature.to_bytes()), *expected);
+
+            let decoded = Signature::<C>::try_from(&hex::decode(expected).unwrap()[..]).unwrap();
+            assert_eq!(decoded, signature);
+            assert_eq!(key.verifying_key().verify(msg, &decoded), Ok(()));
+        }
+    }
+
+    fn check_sign_verify<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SigningKey::<C>::random_from_rng(&mut rng);
+        let public = *key.verifying_key();
+        let msg = b"xs233 schnorr";
+
+        // signing is deterministic and verifies
+        let signature = key.sign(msg);
+        assert_eq!(key.sign(msg), signature);
+        assert_eq!(public.verify(msg, &signature), Ok(()));
+        assert_ne!(key.sign(b"other"), signature);
+
+        // wrong message, key or signature
+        assert_eq!(
+            public.verify(b"xs233 schnorR", &signature),
+            Err(Error::VerificationFailed)
+        );
+        let other = SigningKey::<C>::random_from_rng(&mut rng);
+        assert_eq!(
+            other.verifying_key().verify(msg, &signature),
+            Err(Error::VerificationFailed)
+        );
+        let mut bytes = signature.to_bytes();
+        bytes[30] ^= 1;
+        let tampered = Signature::<C>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            public.verify(msg, &tampered),
+            Err(Error::VerificationFailed)
+        );
+    }
+
+    fn check_roundtrip<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SigningKey::<C>::random_from_rng(&mut rng);
+        let public = *key.verifying_key();
+        let msg = b"xs233 schnorr";
+        let signature = key.sign(msg);
+
+        // round trips
+        let decoded = SigningKey::<C>::from_bytes(&key.to_bytes()).unwrap();
+        assert_eq!(*decoded.verifying_key(), public);
+        assert_eq!(
+            VerifyingKey::<C>::from_bytes(&public.to_bytes()),
+            Ok(public)
+        );
+        assert_eq!(
+            Signature::<C>::from_bytes(&signature.to_bytes()),
+            Ok(signature)
+        );
+    }
+
+    fn check_strict_decoding<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SigningKey::<C>::random_from_rng(&mut rng);
+        let signature = key.sign(b"xs233 schnorr");
+
+        // strict decoding
+        let mut bytes = signature.to_bytes();
+        bytes[30..].copy_from_slice(&[0xff; 30]);
+        assert_eq!(
+            Signature::<C>::from_bytes(&bytes),
+            Err(Error::NonCanonicalScalar)
+        );
+        let mut bytes = signature.to_bytes();
+        bytes[..30].copy_from_slice(&[0xff; 30]);
+        assert_eq!(
+            Signature::<C>::from_bytes(&bytes),
+            Err(Error::InvalidEncoding)
+        );
+        assert_eq!(
+            Signature::<C>::try_from(&bytes[..59]),
+            Err(Error::InvalidLength {
+                expected: 60,
+                actual: 59
+            })
+        );
+        assert_eq!(
+            VerifyingKey::<C>::from_bytes(&[0; 30]),
+            Err(Error::IdentityPoint)
+        );
+        assert!(matches!(
+            SigningKey::<C>::from_bytes(&[0; 30]),
+            Err(Error::ZeroScalar)
+        ));
+    }
+
+    #[cfg(feature = "alloc")]
+    fn check_batch<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let keys: Vec<SigningKey<C>> = (0..32)
+            .map(|_| SigningKey::random_from_rng(&mut rng))
+            .collect();
+        let msgs: Vec<[u8; 4]> = (0..32u32).map(|i| i.to_le_bytes()).collect();
+        let signatures: Vec<Signature<C>> = keys
+            .iter()
+            .zip(msgs.iter())
+            .map(|(key, msg)| key.sign(msg))
+            .collect();
+
+        let mut items: Vec<(&VerifyingKey<C>, &[u8], &Signature<C>)> = keys
+            .iter()
+            .zip(msgs.iter())
+            .zip(signatures.iter())
+            .map(|((key, msg), signature)| (key.verifying_key(), &msg[..], signature))
+            .collect();
+        assert_eq!(verify_batch(&items, &mut rng), Ok(()));
+        assert_eq!(verify_batch::<C>(&[], &mut rng), Ok(()));
+
+        // a wrong message, and swapped signatures
+        items[7].1 = b"oops";
+        assert_eq!(
+            verify_batch(&items, &mut rng),
+            Err(Error::BatchVerificationFailed { index: 7 })
+        );
+        items[7].1 = &msgs[7];
+        items.swap(20, 21);
+        items[20].2 = &signatures[20];
+        items[21].2 = &signatures[21];
+        assert_eq!(
+            verify_batch(&items, &mut rng),
+            Err(Error::BatchVerificationFailed { index: 20 })
+        );
+    }
+
+    // uses the keys only through the traits of the signature crate
+    #[cfg(feature = "signature")]
+    fn check_signature_traits<C: Curve>(seed: u8) {
+        use signature::{
+            DigestSigner, DigestVerifier, Keypair, RandomizedSigner, SignatureEncoding, Signer,
+            Verifier,
+        };
+
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SigningKey::<C>::random_from_rng(&mut rng);
+        let public = Keypair::verifying_key(&key);
+        let msg = b"message";
+
+        let signature: Signature<C> = Signer::sign(&key, msg);
+        assert!(signature == key.sign(msg));
+        assert!(Verifier::verify(&public, msg, &signature).is_ok());
+        assert!(Verifier::verify(&public, b"other", &signature).is_err());
+
+        // the hedged signatures are fresh, and still valid
+        let first: Signature<C> = RandomizedSigner::sign_with_rng(&key, &mut rng, msg);
+        let second: Signature<C> = RandomizedSigner::sign_with_rng(&key, &mut rng, msg);
+        assert!(first != second && first != signature);
+        assert!(Verifier::verify(&public, msg, &first).is_ok());
+        assert!(Verifier::verify(&public, msg, &second).is_ok());
+
+        // the digest variants sign the hash as the message
+        let digest_signature: Signature<C> =
+            DigestSigner::sign_digest(&key, Sha512::new_with_prefix(msg));
+        assert!(digest_signature == key.sign(&Sha512::digest(msg)));
+        assert!(DigestVerifier::verify_digest(
+            &public,
+            Sha512::new_with_prefix(msg),
+            &digest_signature
+        )
+        .is_ok());
+        assert!(DigestVerifier::verify_digest(&public, Sha512::new(), &digest_signature).is_err());
+
+        let bytes = SignatureEncoding::to_bytes(&signature);
+        assert_eq!(bytes, signature.to_bytes());
+        assert!(Signature::<C>::try_from(&bytes[..]).unwrap() == signature);
+    }
+
+    #[test]
+    fn vectors() {
+        check_vectors::<Xsk233>(XSK233_VECTORS);
+        check_vectors::<Xsb233>(XSB233_VECTORS);
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn sign_verify() { check_sign_verify::<C>(seed) }
+        fn roundtrip() { check_roundtrip::<C>(seed) }
+        fn strict_decoding() { check_strict_decoding::<C>(seed) }
+        #[cfg(feature = "alloc")]
+        fn batch_verify() { check_batch::<C>(seed) }
+        #[cfg(feature = "signature")]
+        fn signature_traits() { check_signature_traits::<C>(seed) }
+    }
+}
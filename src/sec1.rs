@@ -0,0 +1,129 @@
+// Conversions between the points of the standard curves K-233 and B-233, in the
+// SEC1 encoding, and the elements of the xs233 groups.
+//
+// The standard curve is y^2 + x*y = x^3 + a*x^2 + b^2, where b is the constant of
+// the curve used by xs233 (see xs233_common.h). The change of variable y -> y + b
+// maps it to y^2 + x*y = x^3 + a*x^2 + b*x, and the group element corresponding to
+// the r-torsion point P is P + N, with N = (0, 0). Adding N is an involution that
+// maps x to b/x, and the standard generator maps to the xs233 generator.
+//
+// These conversions only handle public points, so they are not constant-time.
+
+use crate::gf233::Gf;
+use crate::pure::CurveParams;
+use crate::Error;
+use subtle::ConstantTimeEq;
+
+// The length of an uncompressed and of a compressed SEC1 point encoding.
+pub(crate) const UNCOMPRESSED_LEN: usize = 61;
+pub(crate) const COMPRESSED_LEN: usize = 31;
+
+fn decode_be(bytes: &[u8]) -> Option<Gf> {
+    let mut le: [u8; 30] = bytes.try_into().ok()?;
+    le.reverse();
+    let (value, is_canonical) = Gf::decode(&le);
+    Option::from(is_canonical).map(|_| value)
+}
+
+fn encode_be(value: &Gf) -> [u8; 30] {
+    let mut out = value.encode();
+    out.reverse();
+    out
+}
+
+// Returns the affine coordinates of the standard point for a valid encoding of a
+// group element other than the neutral.
+fn to_standard<C: CurveParams>(repr: &[u8; 30]) -> (Gf, Gf) {
+    let (x, _, _) = C::decode_affine(repr);
+    let (w, _) = Gf::decode(repr);
+    let (x, y) = C::add_n(&x, &(w * x));
+    (x, y + C::B)
+}
+
+// Returns the x coordinate of the standard point, as a little-endian integer.
+pub(crate) fn x_coordinate<C: CurveParams>(repr: &[u8; 30]) -> [u8; 30] {
+    let (x, _, _) = C::decode_affine(repr);
+    (C::B * x.invert()).encode()
+}
+
+// Returns the uncompressed SEC1 encoding `0x04 || x || y` of a group element other
+// than the neutral.
+pub(crate) fn encode_uncompressed<C: CurveParams>(repr: &[u8; 30]) -> [u8; UNCOMPRESSED_LEN] {
+    let (x, y) = to_standard::<C>(repr);
+    let mut out = [0u8; UNCOMPRESSED_LEN];
+    out[0] = 0x04;
+    out[1..31].copy_from_slice(&encode_be(&x));
+    out[31..].copy_from_slice(&encode_be(&y));
+    out
+}
+
+// Returns the compressed SEC1 encoding `0x02 || x` or `0x03 || x` of a group element
+// other than the neutral. The tag holds the least significant bit of y/x.
+pub(crate) fn encode_compressed<C: CurveParams>(repr: &[u8; 30]) -> [u8; COMPRESSED_LEN] {
+    let (x, y) = to_standard::<C>(repr);
+    let mut out = [0u8; COMPRESSED_LEN];
+    out[0] = 0x02 | ((y * x.invert()).encode()[0] & 1);
+    out[1..].copy_from_slice(&encode_be(&x));
+    out
+}
+
+// Decodes a compressed or uncompressed SEC1 point encoding, and returns the
+// encoding of the corresponding group element. Points that are not on the curve or
+// not in the prime order subgroup are rejected.
+pub(crate) fn decode<C: CurveParams>(bytes: &[u8]) -> Result<[u8; 30], Error> {
+    let b = C::B;
+    let (x, y) = match (bytes.first(), bytes.len()) {
+        (Some(0x00), 1) => return Err(Error::IdentityPoint),
+        (Some(0x04), UNCOMPRESSED_LEN) => {
+            let x = decode_be(&bytes[1..31]).ok_or(Error::InvalidEncoding)?;
+            let y = decode_be(&bytes[31..]).ok_or(Error::InvalidEncoding)?;
+            (x, y)
+        }
+        (Some(tag @ (0x02 | 0x03)), COMPRESSED_LEN) => {
+            // y = x*z with z^2 + z = x + a + b^2/x^2
+            let x = decode_be(&bytes[1..]).ok_or(Error::InvalidEncoding)?;
+            if bool::from(x.is_zero()) {
+                return Err(Error::InvalidEncoding);
+            }
+            let beta = x + C::A + b.square() * x.square().invert();
+            if bool::from(beta.trace()) {
+                return Err(Error::InvalidEncoding);
+            }
+            let mut z = beta.halftrace();
+            if z.encode()[0] & 1 != tag & 1 {
+                z += Gf::ONE;
+            }
+            (x, x * z)
+        }
+        (Some(0x02..=0x04), len) => {
+            let expected = if bytes[0] == 0x04 {
+                UNCOMPRESSED_LEN
+            } else {
+                COMPRESSED_LEN
+            };
+            return Err(Error::InvalidLength {
+                expected,
+                actual: len,
+            });
+        }
+        _ => return Err(Error::InvalidEncoding),
+    };
+
+    // x = 0 is the point of order 2, which is not in the subgroup
+    let lhs = y.square() + x * y;
+    let rhs = x.square() * x + C::A * x.square() + b.square();
+    if bool::from(x.is_zero() | !lhs.ct_eq(&rhs)) {
+        return Err(Error::InvalidEncoding);
+    }
+
+    // the group element P + N is determined by its x coordinate and w = y/x. it
+    // decodes to itself if and only if P is in the subgroup.
+    let (x_n, y_n) = C::add_n(&x, &(y + b));
+    let repr = (y_n * x_n.invert()).encode();
+    let (x_decoded, _, is_valid) = C::decode_affine(&repr);
+    if bool::from(is_valid & x_decoded.ct_eq(&x_n)) {
+        Ok(repr)
+    } else {
+        Err(Error::InvalidEncoding)
+    }
+}
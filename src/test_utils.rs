@@ -0,0 +1,366 @@
+//! A generic conformance test suite for [`Point`] implementations.
+//!
+//! Enable the `test-utils` feature and call [`check_point`] from a test to run all
+//! checks, e.g. `xs233::test_utils::check_point::<MyPoint>()`. The individual checks
+//! are also exported. They panic on failure, so they are meant to be called from
+//! `#[test]` functions. Points are derived from a fixed seed, so failures are
+//! reproducible.
+
+use crate::scalar::Scalar;
+use crate::Point;
+use subtle::Choice;
+
+const ROUNDS: usize = 16;
+
+// splitmix64, to avoid depending on rand outside of tests
+struct Seq(u64);
+
+impl Seq {
+    fn new() -> Self {
+        Seq(0x0123_4567_89ab_cdef)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn scalar<const N: usize>(&mut self) -> Scalar<N> {
+        let mut buf = [0u8; N];
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+        Scalar::new(buf)
+    }
+
+    fn point<P: Point>(&mut self) -> P {
+        P::mulgen(&self.scalar::<30>())
+    }
+}
+
+fn eq<P: Point>(lhs: &P, rhs: &P) -> bool {
+    lhs.ct_eq(rhs).into()
+}
+
+fn sum<P: Point>(lhs: &P, rhs: &P) -> P {
+    let mut out = P::default();
+    out.add(lhs, rhs);
+    out
+}
+
+/// Runs all checks of the suite.
+pub fn check_point<P>()
+where
+    P: Point + 'static,
+    P::EncodedPoint: Default + AsRef<[u8]>,
+{
+    check_group_laws::<P>();
+    check_encoding_roundtrip::<P>();
+    check_conditional_ops::<P>();
+    check_xdouble::<P>();
+    check_halve::<P>();
+    check_mul::<P>();
+}
+
+/// Checks the neutral element, inverses, commutativity and associativity, and that
+/// `sub`, `neg` and `double` agree with `add`.
+pub fn check_group_laws<P: Point + 'static>() {
+    let mut seq = Seq::new();
+    let neutral = P::neutral();
+
+    assert!(bool::from(neutral.is_neutral()));
+    assert!(!bool::from(P::generator().is_neutral()));
+    assert!(eq(&sum(neutral, neutral), neutral));
+
+    for _ in 0..ROUNDS {
+        let a: P = seq.point();
+        let b: P = seq.point();
+        let c: P = seq.point();
+
+        assert!(eq(&sum(&a, neutral), &a));
+        assert!(eq(&sum(neutral, &a), &a));
+        assert!(eq(&sum(&a, &b), &sum(&b, &a)));
+        assert!(eq(&sum(&sum(&a, &b), &c), &sum(&a, &sum(&b, &c))));
+
+        let mut neg_a = P::default();
+        neg_a.neg(&a);
+        assert!(bool::from(sum(&a, &neg_a).is_neutral()));
+
+        let mut diff = P::default();
+        diff.sub(&a, &b);
+        let mut neg_b = b;
+        neg_b.neg_inplace();
+        assert!(eq(&diff, &sum(&a, &neg_b)));
+        assert!(eq(&sum(&diff, &b), &a));
+
+        diff.sub(&a, &a);
+        assert!(bool::from(diff.is_neutral()));
+
+        let mut assigned = a;
+        assigned.add_assign(&b);
+        assert!(eq(&assigned, &sum(&a, &b)));
+        assigned.sub_assign(&b);
+        assert!(eq(&assigned, &a));
+
+        let mut doubled = P::default();
+        doubled.double(&a);
+        assert!(eq(&doubled, &sum(&a, &a)));
+        let mut doubled_inplace = a;
+        doubled_inplace.double_inplace();
+        assert!(eq(&doubled, &doubled_inplace));
+    }
+}
+
+/// Checks that points survive encoding and decoding, including the neutral.
+pub fn check_encoding_roundtrip<P>()
+where
+    P: Point + 'static,
+    P::EncodedPoint: Default + AsRef<[u8]>,
+{
+    let mut seq = Seq::new();
+
+    let points = core::iter::once(*P::neutral())
+        .chain(core::iter::once(*P::generator()))
+        .chain((0..ROUNDS).map(|_| seq.point::<P>()));
+
+    for point in points {
+        let mut buf = P::EncodedPoint::default();
+        point.encode(&mut buf);
+
+        let mut decoded = P::default();
+        assert!(bool::from(decoded.decode(&buf)));
+        assert!(eq(&decoded, &point));
+        assert_eq!(
+            bool::from(decoded.is_neutral()),
+            bool::from(point.is_neutral())
+        );
+
+        let mut reencoded = P::EncodedPoint::default();
+        decoded.encode(&mut reencoded);
+        assert_eq!(buf.as_ref(), reencoded.as_ref());
+    }
+}
+
+/// Checks `ConditionallySelectable`, `ConditionallyNegatable`, `condneg` and
+/// `condneg_inplace` for both values of the choice.
+pub fn check_conditional_ops<P: Point + 'static>() {
+    let mut seq = Seq::new();
+
+    for _ in 0..ROUNDS {
+        let a: P = seq.point();
+        let b: P = seq.point();
+        let mut neg_a = a;
+        neg_a.neg_inplace();
+
+        assert!(eq(&P::conditional_select(&a, &b, Choice::from(0)), &a));
+        assert!(eq(&P::conditional_select(&a, &b, Choice::from(1)), &b));
+
+        let mut assigned = a;
+        assigned.conditional_assign(&b, Choice::from(0));
+        assert!(eq(&assigned, &a));
+        assigned.conditional_assign(&b, Choice::from(1));
+        assert!(eq(&assigned, &b));
+
+        let mut negated = a;
+        negated.conditional_negate(Choice::from(0));
+        assert!(eq(&negated, &a));
+        negated.conditional_negate(Choice::from(1));
+        assert!(eq(&negated, &neg_a));
+
+        for (choice, expected) in [(0, &a), (1, &neg_a)] {
+            let mut negated = P::default();
+            negated.condneg(&a, Choice::from(choice));
+            assert!(eq(&negated, expected));
+            let mut negated = a;
+            negated.condneg_inplace(Choice::from(choice));
+            assert!(eq(&negated, expected));
+        }
+
+        let mut swapped_a = a;
+        let mut swapped_b = b;
+        P::conditional_swap(&mut swapped_a, &mut swapped_b, Choice::from(1));
+        assert!(eq(&swapped_a, &b) && eq(&swapped_b, &a));
+
+        assert!(bool::from(a.ct_eq(&a)));
+        assert!(!bool::from(a.ct_eq(&b)));
+    }
+
+    let mut neutral = *P::neutral();
+    neutral.conditional_negate(Choice::from(1));
+    assert!(bool::from(neutral.is_neutral()));
+}
+
+/// Checks that `xdouble` agrees with repeated `double`.
+pub fn check_xdouble<P: Point + 'static>() {
+    let mut seq = Seq::new();
+
+    for _ in 0..ROUNDS {
+        let a: P = seq.point();
+        let mut expected = a;
+
+        for n in 0..8 {
+            let mut xdoubled = P::default();
+            xdoubled.xdouble(&a, n);
+            assert!(eq(&xdoubled, &expected));
+
+            let mut xdoubled_inplace = a;
+            xdoubled_inplace.xdouble_inplace(n);
+            assert!(eq(&xdoubled_inplace, &expected));
+
+            expected.double_inplace();
+        }
+    }
+}
+
+/// Checks that `halve` is the inverse of `double`.
+pub fn check_halve<P: Point + 'static>() {
+    let mut seq = Seq::new();
+
+    let mut half = P::default();
+    half.halve(P::neutral());
+    assert!(bool::from(half.is_neutral()));
+
+    for _ in 0..ROUNDS {
+        let a: P = seq.point();
+
+        half.halve(&a);
+        let mut doubled = P::default();
+        doubled.double(&half);
+        assert!(eq(&doubled, &a));
+
+        let mut half_inplace = a;
+        half_inplace.halve_inplace();
+        assert!(eq(&half_inplace, &half));
+
+        doubled.double(&a);
+        half.halve(&doubled);
+        assert!(eq(&half, &a));
+    }
+}
+
+/// Checks that `mul` agrees with `mulgen` and with repeated addition, for several
+/// scalar lengths.
+pub fn check_mul<P: Point + 'static>() {
+    let mut seq = Seq::new();
+
+    fn check_mulgen<P: Point + 'static, const N: usize>(seq: &mut Seq) {
+        let scalar = seq.scalar::<N>();
+        let mut product = P::default();
+        product.mul(P::generator(), &scalar);
+        assert!(eq(&product, &P::mulgen(&scalar)));
+    }
+
+    for _ in 0..ROUNDS {
+        check_mulgen::<P, 1>(&mut seq);
+        check_mulgen::<P, 8>(&mut seq);
+        check_mulgen::<P, 16>(&mut seq);
+        check_mulgen::<P, 29>(&mut seq);
+        check_mulgen::<P, 30>(&mut seq);
+
+        let a: P = seq.point();
+        let scalar = seq.scalar::<30>();
+        let mut product = P::default();
+        product.mul(&a, &scalar);
+        let mut product_inplace = a;
+        product_inplace.mul_inplace(&scalar);
+        assert!(eq(&product, &product_inplace));
+    }
+
+    let a: P = seq.point();
+    let mut expected = *P::neutral();
+    for k in 0u8..20 {
+        let mut product = P::default();
+        product.mul(&a, &Scalar::new([k]));
+        assert!(eq(&product, &expected));
+        expected.add_assign(&a);
+    }
+
+    // (k1 + k2)*G = k1*G + k2*G for small scalars, so that there is no carry
+    let k1 = seq.scalar::<16>();
+    let k2 = seq.scalar::<16>();
+    let mut k = [0u8; 17];
+    let mut carry = 0u16;
+    for (out, (b1, b2)) in k.iter_mut().zip(k1.as_bytes().iter().zip(k2.as_bytes())) {
+        let s = *b1 as u16 + *b2 as u16 + carry;
+        *out = s as u8;
+        carry = s >> 8;
+    }
+    k[16] = carry as u8;
+    assert!(eq(
+        &P::mulgen(&Scalar::new(k)),
+        &sum(&P::mulgen(&k1), &P::mulgen(&k2))
+    ));
+}
+
+// Defines a test for each body, which runs it once per curve. In the body, the
+// first name is the curve type, the optional second one is the other curve, and
+// the last one is a seed that differs between the curves:
+//
+//     curve_tests! {
+//         |C, seed|
+//         fn sign() { check_sign::<C>(seed) }
+//     }
+#[cfg(test)]
+macro_rules! curve_tests {
+    (|$curve:ident, $other:ident, $seed:ident| $($tests:tt)*) => {
+        $crate::test_utils::curve_tests!(@tests [$curve] [$other] $seed $($tests)*);
+    };
+    (|$curve:ident, $seed:ident| $($tests:tt)*) => {
+        $crate::test_utils::curve_tests!(@tests [$curve] [] $seed $($tests)*);
+    };
+    (@other [$other:ident] $type:ty) => {
+        #[allow(dead_code)]
+        type $other = $type;
+    };
+    (@other [] $type:ty) => {};
+    (
+        @tests [$curve:ident] $other:tt $seed:ident
+        $($(#[$attr:meta])* fn $name:ident() $body:block)*
+    ) => {
+        $(
+            $(#[$attr])*
+            #[test]
+            fn $name() {
+                {
+                    type $curve = $crate::xsk233::Xsk233;
+                    $crate::test_utils::curve_tests!(@other $other $crate::xsb233::Xsb233);
+                    #[allow(unused_variables)]
+                    let $seed: u8 = 1;
+                    $body
+                }
+                {
+                    type $curve = $crate::xsb233::Xsb233;
+                    $crate::test_utils::curve_tests!(@other $other $crate::xsk233::Xsk233);
+                    #[allow(unused_variables)]
+                    let $seed: u8 = 2;
+                    $body
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(test)]
+pub(crate) use curve_tests;
+
+#[cfg(test)]
+mod tests {
+    use super::check_point;
+    use crate::Curve;
+
+    curve_tests! {
+        |C, seed|
+        fn conformance() { check_point::<<C as Curve>::Point>() }
+    }
+
+    #[cfg(all(feature = "ffi", feature = "pure-rust"))]
+    #[test]
+    fn pure_conformance() {
+        check_point::<crate::pure::Xsk233Point>();
+        check_point::<crate::pure::Xsb233Point>();
+    }
+}
@@ -0,0 +1,279 @@
+//! Variable-time algorithms. Their running time depends on the scalars, so they
+//! must only be used with public scalars, e.g. for signature verification.
+
+use crate::affine::Backend;
+use crate::curve::Curve;
+use crate::pure::{CurveParams, HalfPoint};
+use crate::scalar::ModScalar;
+use crate::xsb233::Xsb233;
+use crate::xsk233::Xsk233;
+#[cfg(feature = "alloc")]
+use crate::Point;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+// Returns the width-`w` non-adjacent form of the little-endian 30-byte integer,
+// least significant digit first, and the number of digits. Non-zero digits are odd
+// and smaller than 2^(w-1) in absolute value, and at most one of any w consecutive
+// digits is non-zero. The plain NAF has width 2.
+fn wnaf(le_bytes: &[u8; 30], w: u32) -> ([i8; 242], usize) {
+    debug_assert!((2..=8).contains(&w));
+    let mut limbs = [0u64; 4];
+    for (i, byte) in le_bytes.iter().enumerate() {
+        limbs[i / 8] |= (*byte as u64) << (8 * (i % 8));
+    }
+
+    let mut digits = [0i8; 242];
+    let mut len = 0;
+    while limbs != [0; 4] {
+        if limbs[0] & 1 == 1 {
+            // the digit is the signed residue mod 2^w, so that the remainder is
+            // divisible by 2^w
+            let mut digit = (limbs[0] & ((1 << w) - 1)) as i32;
+            if digit >= 1 << (w - 1) {
+                digit -= 1 << w;
+            }
+            digits[len] = digit as i8;
+            if digit > 0 {
+                limbs[0] -= digit as u64;
+            } else {
+                // add -digit, the value has at most 233 bits so this does not overflow
+                let mut carry = (-digit) as u64;
+                for limb in limbs.iter_mut() {
+                    let (sum, overflow) = limb.overflowing_add(carry);
+                    *limb = sum;
+                    if !overflow {
+                        break;
+                    }
+                    carry = 1;
+                }
+            }
+        }
+        for i in 0..4 {
+            limbs[i] >>= 1;
+            if i < 3 {
+                limbs[i] |= limbs[i + 1] << 63;
+            }
+        }
+        len += 1;
+    }
+    (digits, len)
+}
+
+/// The groups that [`mul_halve_and_add`] works on, [`Xsk233`] and [`Xsb233`].
+pub trait HalveAndAdd: Curve + sealed::Sealed {}
+
+mod sealed {
+    use crate::curve::Curve;
+    use crate::scalar::ModScalar;
+
+    pub trait Sealed: Curve {
+        fn mul_halve_and_add(point: &Self::Point, scalar: &ModScalar<Self>) -> Self::Point;
+    }
+}
+
+impl sealed::Sealed for Xsk233 {
+    fn mul_halve_and_add(point: &Self::Point, scalar: &ModScalar<Self>) -> Self::Point {
+        halve_and_add(point, scalar)
+    }
+}
+
+impl sealed::Sealed for Xsb233 {
+    fn mul_halve_and_add(point: &Self::Point, scalar: &ModScalar<Self>) -> Self::Point {
+        halve_and_add(point, scalar)
+    }
+}
+
+impl HalveAndAdd for Xsk233 {}
+impl HalveAndAdd for Xsb233 {}
+
+/// Computes `scalar*point` with halve-and-add, in variable time.
+///
+/// This is slower than [`mul`](crate::Point::mul) of the c-xs233 backend, which
+/// should be used instead. In release builds, it takes about 230us on xsk233 and
+/// 214us on xsb233, compared to 7us and 14us for `Point::mul` with c-xs233, and
+/// 175us and 331us with the pure Rust backend, whose multiplication is constant
+/// time. It is mostly useful as an independent check of the other multipliers.
+///
+/// With `t` the bit length of the order, the scalar is replaced by
+/// `k' = 2^(t-1)*scalar mod r`, and for each non-zero digit `i < t-1` of the NAF of
+/// `k'`, the half `point/2^(t-1-i)` is added. The halves are computed in affine
+/// coordinates, where halving needs no inversion, and added with mixed additions.
+pub fn mul_halve_and_add<C: HalveAndAdd>(point: &C::Point, scalar: &ModScalar<C>) -> C::Point {
+    C::mul_halve_and_add(point, scalar)
+}
+
+// Halving costs a half-trace (two on xsk233) and three square roots, which is
+// more than a doubling, so this is not faster than the doubling-based multipliers.
+fn halve_and_add<C: Curve, P: Backend>(point: &P, scalar: &ModScalar<C>) -> P {
+    let t = 256 - C::ORDER[3].leading_zeros() as usize;
+    let mut power = [0u8; 32];
+    power[(t - 1) / 8] = 1 << ((t - 1) % 8);
+    let scaled = *scalar * ModScalar::from_bytes_mod_order(&power);
+    let (digits, len) = wnaf(&scaled.to_bytes(), 2);
+
+    let point = point.to_extended();
+    if point.is_neutral().into() {
+        return *P::neutral();
+    }
+
+    // from digit t-1 on, the multiples are 2^(i-(t-1))*point
+    let mut acc = P::Params::NEUTRAL;
+    let mut multiple = point;
+    for digit in &digits[t - 1..len.max(t)] {
+        match digit {
+            1 => acc = acc.add::<P::Params>(&multiple),
+            -1 => acc = acc.add::<P::Params>(&multiple.neg()),
+            _ => {}
+        }
+        multiple = multiple.double::<P::Params>();
+    }
+
+    let mut half = HalfPoint::new::<P::Params>(&point);
+    for digit in digits[..t - 1].iter().rev() {
+        half = half.halve::<P::Params>();
+        if *digit != 0 {
+            let (x, mut s) = half.to_affine::<P::Params>();
+            if *digit < 0 {
+                s += x;
+            }
+            acc = acc.add_affine::<P::Params>(&x, &s);
+        }
+    }
+    P::from_extended(&acc)
+}
+
+// the window width of the multi-scalar multiplication. the tables hold the odd
+// multiples up to 15*P.
+#[cfg(feature = "alloc")]
+const MSM_WINDOW: u32 = 5;
+
+/// Computes the sum of `k*P` over all pairs `(P, k)`, in variable time.
+///
+/// This uses interleaved wNAF with shared doublings, which is considerably faster
+/// than the constant-time [`batch::msm`](crate::batch::msm), and is meant for
+/// verifying many signatures at once.
+#[cfg(feature = "alloc")]
+pub fn msm<C: Curve>(pairs: &[(C::Point, ModScalar<C>)]) -> C::Point {
+    let mut tables = Vec::with_capacity(pairs.len());
+    let mut digits = Vec::with_capacity(pairs.len());
+    let mut len = 0;
+    for (point, scalar) in pairs {
+        let (scalar_digits, scalar_len) = wnaf(&scalar.to_bytes(), MSM_WINDOW);
+        len = len.max(scalar_len);
+        digits.push(scalar_digits);
+
+        // table[i] = (2i+1)*point
+        let mut double = C::Point::default();
+        double.double(point);
+        let mut table = [*point; 1 << (MSM_WINDOW - 2)];
+        for i in 1..table.len() {
+            let previous = table[i - 1];
+            table[i].add(&previous, &double);
+        }
+        tables.push(table);
+    }
+
+    let mut acc = *C::Point::neutral();
+    for i in (0..len).rev() {
+        acc.double_inplace();
+        for (table, digits) in tables.iter().zip(digits.iter()) {
+            let digit = digits[i];
+            if digit > 0 {
+                acc.add_assign(&table[(digit / 2) as usize]);
+            } else if digit < 0 {
+                acc.sub_assign(&table[(-digit / 2) as usize]);
+            }
+        }
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use crate::Point;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use subtle::ConstantTimeEq;
+
+    #[test]
+    fn naf_digits() {
+        let mut rng = ChaCha8Rng::from_seed([5; 32]);
+        for w in [2, 5] {
+            for _ in 0..100 {
+                let value: u64 = rng.gen();
+                let mut bytes = [0u8; 30];
+                bytes[..8].copy_from_slice(&value.to_le_bytes());
+                let (digits, len) = wnaf(&bytes, w);
+
+                let mut recoded = 0i128;
+                for (i, digit) in digits[..len].iter().enumerate() {
+                    recoded += (*digit as i128) << i;
+                    if *digit != 0 {
+                        assert!(digit % 2 != 0 && digit.abs() < 1 << (w - 1));
+                        let start = i.saturating_sub(w as usize - 1);
+                        assert!(digits[start..i].iter().all(|d| *d == 0));
+                    }
+                }
+                assert_eq!(recoded, value as i128);
+            }
+        }
+    }
+
+    fn check_halve_and_add<C: HalveAndAdd<Scalar = ModScalar<C>>>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let mut buf = [0u8; 64];
+        rng.fill(&mut buf);
+        let point = C::Point::mulgen(&ModScalar::<C>::from_bytes_mod_order(&buf).into());
+
+        let mut scalars = vec![ModScalar::ZERO, ModScalar::ONE, -ModScalar::<C>::ONE];
+        for _ in 0..5 {
+            rng.fill(&mut buf);
+            scalars.push(ModScalar::from_bytes_mod_order(&buf));
+        }
+
+        for scalar in scalars {
+            let mut expected = C::Point::default();
+            expected.mul(&point, &scalar.to_scalar());
+            let product = mul_halve_and_add::<C>(&point, &scalar);
+            assert!(bool::from(product.ct_eq(&expected)), "{scalar:?}");
+
+            let product = mul_halve_and_add::<C>(C::Point::neutral(), &scalar);
+            assert!(bool::from(product.is_neutral()));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn check_msm<C: Curve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let mut buf = [0u8; 64];
+        let mut pairs = Vec::new();
+        let mut expected = *C::Point::neutral();
+        for i in 0..20 {
+            rng.fill(&mut buf);
+            let point = C::Point::mulgen(&ModScalar::<C>::from_bytes_mod_order(&buf).to_scalar());
+            rng.fill(&mut buf);
+            let scalar = match i {
+                0 => ModScalar::ZERO,
+                1 => -ModScalar::<C>::ONE,
+                _ => ModScalar::from_bytes_mod_order(&buf),
+            };
+
+            let mut product = C::Point::default();
+            product.mul(&point, &scalar.to_scalar());
+            expected.add_assign(&product);
+            pairs.push((point, scalar));
+        }
+        assert!(bool::from(msm::<C>(&pairs).ct_eq(&expected)));
+        assert!(bool::from(msm::<C>(&[]).is_neutral()));
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn halve_and_add() { check_halve_and_add::<C>(seed) }
+        #[cfg(feature = "alloc")]
+        fn multi_scalar_mul() { check_msm::<C>(seed) }
+    }
+}
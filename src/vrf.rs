@@ -0,0 +1,519 @@
+//! Verifiable random functions, following the ECVRF construction of RFC 9381.
+//!
+//! The holder of a [`SecretKey`] `x` can compute a [`Proof`] for an input `alpha`,
+//! from which anyone derives the 64-byte output `beta` with [`Proof::to_hash`]. With
+//! the [`PublicKey`] `Y = x*B`, anyone can check with [`PublicKey::verify`] that
+//! `beta` is the unique output for `alpha`, without learning anything about the
+//! outputs for other inputs.
+//!
+//! The steps are those of RFC 9381 §5, instantiated for the xs233 groups:
+//!
+//! - `H` is [`encode_to_curve`] of `Y || alpha`, with the domain separation tag
+//!   `"ECVRF_" || C::NAME || "_XMD:SHA-256_ICART_NU_" || suite_string`. The map is
+//!   constant-time, so the secret key does not leak through the time taken to hash
+//!   the input.
+//! - The nonce is derived from the secret key and `H` as in §5.4.2.2, with SHA-512.
+//! - The proof is `Gamma || c || s`, where `Gamma = x*H`, `c` is the 16-byte
+//!   challenge of §5.4.3 and `s = k + c*x` is a 30-byte canonical scalar. Integers
+//!   are encoded as little-endian, like everywhere else in this crate.
+//! - `beta` is the SHA-512 hash of `Gamma`, as in §5.2. The xs233 groups have prime
+//!   order, so there is no cofactor to clear.
+//!
+//! RFC 9381 does not define suites for binary curves, so the suite strings of
+//! [`VrfCurve`] are not registered, and the outputs are not interoperable with other
+//! implementations.
+
+use crate::curve::Curve;
+use crate::hash_to_curve::{encode_to_curve, HashToCurve};
+use crate::scalar::ModScalar;
+use crate::xsb233::Xsb233;
+use crate::xsk233::Xsk233;
+use crate::{Error, Point};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha512};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// The length of an encoded proof in bytes.
+pub const PROOF_LEN: usize = 30 + CHALLENGE_LEN + 30;
+
+/// The length of the output `beta` in bytes.
+pub const OUTPUT_LEN: usize = 64;
+
+const CHALLENGE_LEN: usize = 16;
+
+const CHALLENGE_GENERATION_DOMAIN_SEPARATOR_FRONT: u8 = 0x02;
+const PROOF_TO_HASH_DOMAIN_SEPARATOR_FRONT: u8 = 0x03;
+const DOMAIN_SEPARATOR_BACK: u8 = 0x00;
+
+/// The groups that the ECVRF is implemented for, [`Xsk233`] and [`Xsb233`].
+pub trait VrfCurve: HashToCurve {
+    /// The suite string, which is bound into all hashes. The suites of the xs233
+    /// groups are not registered with IANA.
+    const SUITE_STRING: u8;
+}
+
+impl VrfCurve for Xsk233 {
+    const SUITE_STRING: u8 = 0xf1;
+}
+
+impl VrfCurve for Xsb233 {
+    const SUITE_STRING: u8 = 0xf2;
+}
+
+// ECVRF_encode_to_curve, with the h2c suite of the curve.
+fn encode_input<C: VrfCurve>(public: &[u8; 30], alpha: &[u8]) -> C::Point {
+    const PREFIX: &[u8] = b"ECVRF_";
+    const SUFFIX: &[u8] = b"_XMD:SHA-256_ICART_NU_";
+    let mut dst = [0u8; 64];
+    let mut len = 0;
+    for part in [PREFIX, C::NAME.as_bytes(), SUFFIX, &[C::SUITE_STRING]] {
+        dst[len..len + part.len()].copy_from_slice(part);
+        len += part.len();
+    }
+    encode_to_curve::<C>(&[public, alpha], &dst[..len])
+}
+
+// ECVRF_nonce_generation of RFC 9381 §5.4.2.2, with the encoding of the secret scalar
+// in place of the EdDSA secret key.
+fn nonce<C: Curve>(secret: &ModScalar<C>, h: &[u8; 30]) -> ModScalar<C> {
+    let mut secret_bytes = secret.to_bytes();
+    let mut hashed = Sha512::digest(secret_bytes);
+    secret_bytes.zeroize();
+    let k = Sha512::new()
+        .chain_update(&hashed[32..])
+        .chain_update(h)
+        .finalize();
+    hashed.zeroize();
+    ModScalar::from_bytes_mod_order(&k)
+}
+
+// ECVRF_challenge_generation of RFC 9381 §5.4.3, which returns the truncated hash.
+fn challenge<C: VrfCurve>(points: [&[u8; 30]; 5]) -> [u8; CHALLENGE_LEN] {
+    let mut hasher = Sha512::new();
+    hasher.update([C::SUITE_STRING, CHALLENGE_GENERATION_DOMAIN_SEPARATOR_FRONT]);
+    for point in points {
+        hasher.update(point);
+    }
+    hasher.update([DOMAIN_SEPARATOR_BACK]);
+    hasher.finalize()[..CHALLENGE_LEN].try_into().unwrap()
+}
+
+/// A secret key for computing proofs.
+#[derive(Clone)]
+pub struct SecretKey<C: VrfCurve> {
+    secret: ModScalar<C>,
+    public_key: PublicKey<C>,
+}
+
+/// A public key for verifying proofs, which is never the neutral.
+#[derive(Clone, Copy, Debug)]
+pub struct PublicKey<C: VrfCurve> {
+    point: C::Point,
+    bytes: [u8; 30],
+}
+
+/// A decoded proof. Only the encoding of `s` is checked, so the output of
+/// [`Proof::to_hash`] is meaningless unless the proof was verified.
+#[derive(Clone, Copy, Debug)]
+pub struct Proof<C: VrfCurve> {
+    gamma: C::Point,
+    gamma_bytes: [u8; 30],
+    c: [u8; CHALLENGE_LEN],
+    s: ModScalar<C>,
+}
+
+impl<C: VrfCurve> SecretKey<C> {
+    pub fn random_from_rng(rng: &mut impl CryptoRngCore) -> Self {
+        Self::from_scalar(ModScalar::random(rng))
+    }
+
+    /// Decodes a secret key from its canonical 30-byte encoding. Zero is rejected.
+    pub fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+        let secret = ModScalar::from_bytes_non_zero(bytes)?;
+        Ok(Self::from_scalar(secret))
+    }
+
+    fn from_scalar(secret: ModScalar<C>) -> Self {
+        let mut scalar = secret.to_scalar();
+        let point = C::Point::mulgen(&scalar);
+        scalar.zeroize();
+        SecretKey {
+            secret,
+            public_key: PublicKey {
+                point,
+                bytes: point.to_encoded(),
+            },
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        self.secret.to_bytes()
+    }
+
+    pub fn public_key(&self) -> &PublicKey<C> {
+        &self.public_key
+    }
+
+    /// Computes the proof for `alpha`, as ECVRF_prove of RFC 9381 §5.1. The output is
+    /// [`Proof::to_hash`] of the proof.
+    pub fn prove(&self, alpha: &[u8]) -> Proof<C> {
+        let h = encode_input::<C>(&self.public_key.bytes, alpha);
+        let h_bytes = h.to_encoded();
+
+        let mut secret_scalar = self.secret.to_scalar();
+        let mut gamma = C::Point::default();
+        gamma.mul(&h, &secret_scalar);
+        secret_scalar.zeroize();
+        let gamma_bytes = gamma.to_encoded();
+
+        let mut k = nonce(&self.secret, &h_bytes);
+        let mut k_scalar = k.to_scalar();
+        let u = C::Point::mulgen(&k_scalar);
+        let mut v = C::Point::default();
+        v.mul(&h, &k_scalar);
+        k_scalar.zeroize();
+
+        let c = challenge::<C>([
+            &self.public_key.bytes,
+            &h_bytes,
+            &gamma_bytes,
+            &u.to_encoded(),
+            &v.to_encoded(),
+        ]);
+        let s = k + ModScalar::from_bytes_mod_order(&c) * self.secret;
+        k.zeroize();
+
+        Proof {
+            gamma,
+            gamma_bytes,
+            c,
+            s,
+        }
+    }
+}
+
+impl<C: VrfCurve> PublicKey<C> {
+    /// Decodes a public key, rejecting invalid encodings and the neutral, which is the
+    /// key validation of RFC 9381 §5.4.5.
+    pub fn from_bytes(bytes: &[u8; 30]) -> Result<Self, Error> {
+        let point = C::Point::try_decode_non_neutral(bytes)?;
+        Ok(PublicKey {
+            point,
+            bytes: *bytes,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 30] {
+        self.bytes
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 30] {
+        &self.bytes
+    }
+
+    pub fn as_point(&self) -> &C::Point {
+        &self.point
+    }
+
+    /// Verifies the proof for `alpha` as ECVRF_verify of RFC 9381 §5.3, and returns
+    /// the output.
+    pub fn verify(&self, alpha: &[u8], proof: &Proof<C>) -> Result<[u8; OUTPUT_LEN], Error> {
+        let h = encode_input::<C>(&self.bytes, alpha);
+        let c = ModScalar::<C>::from_bytes_mod_order(&proof.c);
+        let s = proof.s.to_scalar();
+        let minus_c = (-c).to_scalar();
+
+        // U = s*B - c*Y
+        let mut u = C::Point::default();
+        u.mul(&self.point, &minus_c);
+        u.add_assign(&C::Point::mulgen(&s));
+
+        // V = s*H - c*Gamma
+        let mut v = C::Point::default();
+        v.mul(&h, &s);
+        let mut c_gamma = C::Point::default();
+        c_gamma.mul(&proof.gamma, &minus_c);
+        v.add_assign(&c_gamma);
+
+        let expected = challenge::<C>([
+            &self.bytes,
+            &h.to_encoded(),
+            &proof.gamma_bytes,
+            &u.to_encoded(),
+            &v.to_encoded(),
+        ]);
+        if expected == proof.c {
+            Ok(proof.to_hash())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
+
+impl<C: VrfCurve> Proof<C> {
+    /// Decodes a proof, rejecting invalid encodings of `Gamma` and non-canonical `s`.
+    pub fn from_bytes(bytes: &[u8; PROOF_LEN]) -> Result<Self, Error> {
+        let gamma_bytes: [u8; 30] = bytes[..30].try_into().unwrap();
+        let c: [u8; CHALLENGE_LEN] = bytes[30..30 + CHALLENGE_LEN].try_into().unwrap();
+        let s_bytes: &[u8; 30] = bytes[30 + CHALLENGE_LEN..].try_into().unwrap();
+
+        let gamma = C::Point::try_decode(&gamma_bytes)?;
+        let s = Option::from(ModScalar::from_canonical_bytes(s_bytes))
+            .ok_or(Error::NonCanonicalScalar)?;
+        Ok(Proof {
+            gamma,
+            gamma_bytes,
+            c,
+            s,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; PROOF_LEN] {
+        let mut out = [0u8; PROOF_LEN];
+        out[..30].copy_from_slice(&self.gamma_bytes);
+        out[30..30 + CHALLENGE_LEN].copy_from_slice(&self.c);
+        out[30 + CHALLENGE_LEN..].copy_from_slice(&self.s.to_bytes());
+        out
+    }
+
+    /// Computes the output `beta` from the proof, as ECVRF_proof_to_hash of RFC 9381
+    /// §5.2. This does not verify the proof, see [`PublicKey::verify`].
+    pub fn to_hash(&self) -> [u8; OUTPUT_LEN] {
+        Sha512::new()
+            .chain_update([C::SUITE_STRING, PROOF_TO_HASH_DOMAIN_SEPARATOR_FRONT])
+            .chain_update(self.gamma_bytes)
+            .chain_update([DOMAIN_SEPARATOR_BACK])
+            .finalize()
+            .into()
+    }
+}
+
+impl<C: VrfCurve> TryFrom<&[u8]> for PublicKey<C> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: &[u8; 30] = bytes.try_into().map_err(|_| Error::InvalidLength {
+            expected: 30,
+            actual: bytes.len(),
+        })?;
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<C: VrfCurve> TryFrom<&[u8]> for Proof<C> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: &[u8; PROOF_LEN] = bytes.try_into().map_err(|_| Error::InvalidLength {
+            expected: PROOF_LEN,
+            actual: bytes.len(),
+        })?;
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<C: VrfCurve> PartialEq for PublicKey<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<C: VrfCurve> Eq for PublicKey<C> {}
+
+// encodings are unique, so comparing them is the same as comparing the values
+impl<C: VrfCurve> PartialEq for Proof<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl<C: VrfCurve> Eq for Proof<C> {}
+
+impl<C: VrfCurve> From<Proof<C>> for [u8; PROOF_LEN] {
+    fn from(proof: Proof<C>) -> Self {
+        proof.to_bytes()
+    }
+}
+
+impl<C: VrfCurve> Drop for SecretKey<C> {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl<C: VrfCurve> ZeroizeOnDrop for SecretKey<C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::curve_tests;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // (secret key, alpha, proof, beta), generated with this implementation
+    const XSK233_VECTORS: &[(&str, &[u8], &str, &str)] = &[
+        (
+            "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d00",
+            b"",
+            "6bc02b16ddc3a9bb841b3e4a5bbe9be532e9bf1013ffe9d249cb4dda1701f6961f54d92376fb4ef3d8dbd4316b09ff9892127dfb31c0176dba50f27d495fff41721ea088b47009d0d5826b00",
+            "d51d0cf7ace6b0823fd5c58a6c7f1c03ff8093ca24140ce4a1500d39f77cc897f39751036581f65c3bfa5912f4289d57a0b918be368366934fd1d8e6396d5ba6",
+        ),
+        (
+            "010000000000000000000000000000000000000000000000000000000000",
+            b"abc",
+            "fa98ca35a925df602738f5baf718e7e08cf943471133b6c4bdcde0a918005563e5343756cfa4f45d6dafa1f123b21df3c3827fe7dfd027e96a327f495f3933dd28c62fc8fb8a5581178f3c00",
+            "75777e561da782b90f5ba81dfb863ff6f7a28f19030fed7f1d3c5eeba2f509516f5c827528cee3a656645cf43df7281ab8a7301676c13693ba65a3d56ef8ff7a",
+        ),
+        (
+            "ffeeddccbbaa99887766554433221100ffeeddccbbaa9988776655443300",
+            b"sample",
+            "10472732dd421ee72641dca264c5167db91243f597470c2eccccbea9b30010f39557c296265979091ded1c62855016bf1dd5151675db8d30215f5381506c827cb309c27d13c0298ed2152c00",
+            "aace89d8e49c4e6ac0f34e7473872e183d96f6871c175b6943d10dd53e1556283396da817aa43ae15edd35b69a44b62a3450fdfc1ce0ce4877979a1deacbf06a",
+        ),
+    ];
+    const XSB233_VECTORS: &[(&str, &[u8], &str, &str)] = &[
+        (
+            "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d00",
+            b"",
+            "9188ccf33f3b9b68c235377441b06ad574f81cc030a9425472622465d5017069c3b39c9d143fe5af2cb2f194612f0c94ea5d87e2005a432d547456c17fd91d75c552a8caf4c9006c49092d00",
+            "a30fd38df4f0f341f7b21eb09efb151f3ee8e5df759b4c7f9d265f9a42c0563464e374afb25bd5394cc20af69f940249f0dbfa30e1240f0df8ccbd738d5f1517",
+        ),
+        (
+            "010000000000000000000000000000000000000000000000000000000000",
+            b"abc",
+            "1e42a518709c4376a8096d67873d4bd57ecdc2efa4c40d82de400c02280037cb35704604d4f233a93110d82ec14fbfff302e36cf512bac52c8276b6ef9f8882b92bef1354fed9b650a2bac00",
+            "8b19cbf2383ab3b552fed05abf2244c0a686460cf12affc32c0ffbd17047a9a8673d61d4008dbcb2fb08bb07aa691796751ba65da6aff8c7e7e19f7be091b0ba",
+        ),
+        (
+            "ffeeddccbbaa99887766554433221100ffeeddccbbaa9988776655443300",
+            b"sample",
+            "510496767be473394f7857eba22bd72b86917c5d898c846d861771aac0019bc7c66ffd68c9a5f7929ad749511c17c6eb79e34fb8527d958ed9547f7aabf22184e6735ae3e960c5a407d59700",
+            "87cc9d6f5bf832545cc801859e104d60b737d67dee6b35037d7fbe15f01814b68c44231277646d01789e2042e8f8215f0739c24f9c4d1ca667926b55651d3b00",
+        ),
+    ];
+
+    fn check_vectors<C: VrfCurve>(vectors: &[(&str, &[u8], &str, &str)]) {
+        for (secret, alpha, proof, beta) in vectors {
+            let key = SecretKey::<C>::from_bytes(&hex::decode(secret).unwrap().try_into().unwrap())
+                .unwrap();
+            let computed = key.prove(alpha);
+            assert_eq!(hex::encode(computed.to_bytes()), *proof);
+            assert_eq!(hex::encode(computed.to_hash()), *beta);
+
+            let decoded = Proof::<C>::try_from(&hex::decode(proof).unwrap()[..]).unwrap();
+            assert_eq!(decoded, computed);
+            let output = key.public_key().verify(alpha, &decoded).unwrap();
+            assert_eq!(hex::encode(output), *beta);
+        }
+    }
+
+    fn check_prove_verify<C: VrfCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SecretKey::<C>::random_from_rng(&mut rng);
+        let public = *key.public_key();
+        let alpha = b"xs233 vrf";
+
+        // proving is deterministic, and the verified output is the hash of the proof
+        let proof = key.prove(alpha);
+        assert_eq!(key.prove(alpha), proof);
+        assert_eq!(public.verify(alpha, &proof), Ok(proof.to_hash()));
+        let other_proof = key.prove(b"other");
+        assert_ne!(other_proof.to_hash(), proof.to_hash());
+
+        // the output depends on the key
+        let other = SecretKey::<C>::random_from_rng(&mut rng);
+        assert_ne!(other.prove(alpha).to_hash(), proof.to_hash());
+
+        // wrong input, key or proof
+        assert_eq!(
+            public.verify(b"xs233 vrF", &proof),
+            Err(Error::VerificationFailed)
+        );
+        assert_eq!(
+            other.public_key().verify(alpha, &proof),
+            Err(Error::VerificationFailed)
+        );
+        assert_eq!(
+            public.verify(alpha, &other_proof),
+            Err(Error::VerificationFailed)
+        );
+        for index in [0, 29, 30, 45, 46, 74] {
+            let mut bytes = proof.to_bytes();
+            bytes[index] ^= 1;
+            if let Ok(tampered) = Proof::<C>::from_bytes(&bytes) {
+                assert_eq!(
+                    public.verify(alpha, &tampered),
+                    Err(Error::VerificationFailed)
+                );
+            }
+        }
+
+        // a proof with the gamma of another key does not verify
+        let mut bytes = proof.to_bytes();
+        bytes[..30].copy_from_slice(&other.prove(alpha).to_bytes()[..30]);
+        let swapped = Proof::<C>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            public.verify(alpha, &swapped),
+            Err(Error::VerificationFailed)
+        );
+    }
+
+    fn check_roundtrip<C: VrfCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SecretKey::<C>::random_from_rng(&mut rng);
+        let public = *key.public_key();
+        let alpha = b"xs233 vrf";
+        let proof = key.prove(alpha);
+
+        // round trips
+        let decoded = SecretKey::<C>::from_bytes(&key.to_bytes()).unwrap();
+        assert_eq!(*decoded.public_key(), public);
+        assert_eq!(PublicKey::<C>::from_bytes(&public.to_bytes()), Ok(public));
+        assert_eq!(Proof::<C>::from_bytes(&proof.to_bytes()), Ok(proof));
+    }
+
+    fn check_strict_decoding<C: VrfCurve>(seed: u8) {
+        let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+        let key = SecretKey::<C>::random_from_rng(&mut rng);
+        let proof = key.prove(b"xs233 vrf");
+
+        // strict decoding
+        let mut bytes = proof.to_bytes();
+        bytes[46..].copy_from_slice(&[0xff; 30]);
+        assert_eq!(
+            Proof::<C>::from_bytes(&bytes),
+            Err(Error::NonCanonicalScalar)
+        );
+        let mut bytes = proof.to_bytes();
+        bytes[..30].copy_from_slice(&[0xff; 30]);
+        assert_eq!(Proof::<C>::from_bytes(&bytes), Err(Error::InvalidEncoding));
+        assert_eq!(
+            Proof::<C>::try_from(&bytes[..75]),
+            Err(Error::InvalidLength {
+                expected: PROOF_LEN,
+                actual: 75
+            })
+        );
+        assert_eq!(
+            PublicKey::<C>::from_bytes(&[0; 30]),
+            Err(Error::IdentityPoint)
+        );
+        assert!(matches!(
+            SecretKey::<C>::from_bytes(&[0; 30]),
+            Err(Error::ZeroScalar)
+        ));
+    }
+
+    #[test]
+    fn vectors() {
+        check_vectors::<Xsk233>(XSK233_VECTORS);
+        check_vectors::<Xsb233>(XSB233_VECTORS);
+    }
+
+    curve_tests! {
+        |C, seed|
+        fn prove_verify() { check_prove_verify::<C>(seed) }
+        fn roundtrip() { check_roundtrip::<C>(seed) }
+        fn strict_decoding() { check_strict_decoding::<C>(seed) }
+    }
+}
@@ -2,96 +2,204 @@
 // - we call the xsb233_* functions instead of xsk233_* functions
 // - we don't use the frobenius endomorphism for multiplication
 
+use crate::curve::Curve;
+#[cfg(feature = "ffi")]
+use crate::pure::{assert_opaque_layout, Extended};
+use crate::scalar::ModScalar;
+#[cfg(feature = "ffi")]
 use crate::{from_choice, scalar::Scalar, to_choice, Point};
-use subtle::{Choice, ConditionallyNegatable, ConditionallySelectable, ConstantTimeEq};
+#[cfg(feature = "ffi")]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "ffi")]
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+#[cfg(feature = "ffi")]
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Xsb233Point(xs233_sys::xsb233_point);
+
+// The wrapper is repr(transparent), so a pointer to it is a valid pointer to the
+// wrapped c-xs233 point, and vice versa. Creating the pointers is safe; the unsafe
+// blocks below only pass them to c-xs233 while the borrows they come from are alive.
+#[cfg(feature = "ffi")]
+impl Xsb233Point {
+    fn as_xsbpoint(&self) -> *const xs233_sys::xsb233_point {
+        &self.0
+    }
+
+    fn as_mut_xsbpoint(&mut self) -> *mut xs233_sys::xsb233_point {
+        &mut self.0
+    }
 
-#[derive(Clone, Copy, Debug)]
-pub struct Xsb233Point([u64; 16]);
+    // Reads the coordinates of the point, see Extended::from_opaque.
+    pub(crate) fn to_extended(self) -> Extended {
+        Self::check_opaque_layout();
+        Extended::from_opaque(&self.0.opaque)
+    }
 
-impl Xsb233Point {
-    unsafe fn as_xsbpoint(&self) -> *const xs233_sys::xsb233_point {
-        &*self as *const Xsb233Point as *const xs233_sys::xsb233_point
+    pub(crate) fn from_extended(point: &Extended) -> Self {
+        Self::check_opaque_layout();
+        Xsb233Point(xs233_sys::xsb233_point {
+            opaque: point.to_opaque(),
+        })
+    }
+
+    // Checks the layout of c-xs233 points once, see assert_opaque_layout.
+    fn check_opaque_layout() {
+        static CHECKED: AtomicBool = AtomicBool::new(false);
+        if CHECKED.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let generator = Self::generator();
+        let doubled = generator + generator;
+        let mut doubled_encoding = [0u8; 30];
+        doubled.encode(&mut doubled_encoding);
+        assert_opaque_layout::<crate::pure::xsb233::Params>(
+            &generator.0.opaque,
+            &doubled.0.opaque,
+            &doubled_encoding,
+        );
+        CHECKED.store(true, Ordering::Relaxed);
+    }
+
+    // Casts one of the statics of c-xs233 to a point.
+    fn from_static(point: &'static xs233_sys::xsb233_point) -> &'static Self {
+        let ptr: *const xs233_sys::xsb233_point = point;
+        // SAFETY: Xsb233Point is repr(transparent) over xsb233_point, so the
+        // pointer is valid for reads of an Xsb233Point and has the right alignment.
+        // The static lives forever and is never written to.
+        unsafe { &*ptr.cast::<Xsb233Point>() }
     }
+}
 
-    unsafe fn as_mut_xsbpoint(&mut self) -> *mut xs233_sys::xsb233_point {
-        &mut *self as *mut Xsb233Point as *mut xs233_sys::xsb233_point
+// The neutral, as for the pure Rust backend. The all-zero representation is not
+// a valid point.
+#[cfg(feature = "ffi")]
+impl Default for Xsb233Point {
+    fn default() -> Self {
+        *Self::neutral()
     }
 }
 
+#[cfg(feature = "ffi")]
 crate::impl_ops!(Xsb233Point);
 
+// In the impls below, c-xs233 only reads from the `*const` arguments and only
+// writes to the `*mut` one. It loads all inputs before storing the output, so the
+// output may alias an input.
+// All pointers come from live references to points, or are derived from the same
+// `&mut self` for in-place operations, so they are valid, aligned and not
+// aliased by a Rust reference during the call.
+#[cfg(feature = "ffi")]
 impl Point for Xsb233Point {
     type EncodedPoint = [u8; 30];
 
     fn add(&mut self, lhs: &Self, rhs: &Self) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsb233_add(self.as_mut_xsbpoint(), lhs.as_xsbpoint(), rhs.as_xsbpoint());
         }
     }
 
     fn add_assign(&mut self, rhs: &Self) {
+        let ptr = self.as_mut_xsbpoint();
+        // SAFETY: see above.
         unsafe {
-            xs233_sys::xsb233_add(
-                self.as_mut_xsbpoint(),
-                self.as_xsbpoint(),
-                rhs.as_xsbpoint(),
-            );
+            xs233_sys::xsb233_add(ptr, ptr, rhs.as_xsbpoint());
         }
     }
 
     fn sub(&mut self, lhs: &Self, rhs: &Self) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsb233_sub(self.as_mut_xsbpoint(), lhs.as_xsbpoint(), rhs.as_xsbpoint());
         }
     }
 
     fn sub_assign(&mut self, rhs: &Self) {
+        let ptr = self.as_mut_xsbpoint();
+        // SAFETY: see above.
         unsafe {
-            xs233_sys::xsb233_sub(
-                self.as_mut_xsbpoint(),
-                self.as_xsbpoint(),
-                rhs.as_xsbpoint(),
-            );
+            xs233_sys::xsb233_sub(ptr, ptr, rhs.as_xsbpoint());
         }
     }
 
     fn neg(&mut self, point: &Self) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsb233_neg(self.as_mut_xsbpoint(), point.as_xsbpoint());
         }
     }
 
     fn neg_inplace(&mut self) {
+        let ptr = self.as_mut_xsbpoint();
+        // SAFETY: see above.
+        unsafe {
+            xs233_sys::xsb233_neg(ptr, ptr);
+        }
+    }
+
+    fn condneg(&mut self, point: &Self, choice: Choice) {
+        // SAFETY: see above. from_choice returns 0 or 0xffffffff, as c-xs233
+        // requires.
+        unsafe {
+            xs233_sys::xsb233_condneg(
+                self.as_mut_xsbpoint(),
+                point.as_xsbpoint(),
+                from_choice(choice),
+            );
+        }
+    }
+
+    fn condneg_inplace(&mut self, choice: Choice) {
+        let ptr = self.as_mut_xsbpoint();
+        // SAFETY: as in condneg.
         unsafe {
-            xs233_sys::xsb233_neg(self.as_mut_xsbpoint(), self.as_xsbpoint());
+            xs233_sys::xsb233_condneg(ptr, ptr, from_choice(choice));
         }
     }
 
     fn double(&mut self, point: &Self) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsb233_double(self.as_mut_xsbpoint(), point.as_xsbpoint());
         }
     }
 
     fn double_inplace(&mut self) {
+        let ptr = self.as_mut_xsbpoint();
+        // SAFETY: see above.
         unsafe {
-            xs233_sys::xsb233_double(self.as_mut_xsbpoint(), self.as_xsbpoint());
+            xs233_sys::xsb233_double(ptr, ptr);
         }
     }
 
+    // c-xs233 does not implement halving, so we use the pure Rust formulas on
+    // the coordinates of the point.
+    fn halve(&mut self, point: &Self) {
+        let half = point.to_extended().halve::<crate::pure::xsb233::Params>();
+        *self = Self::from_extended(&half);
+    }
+
     fn xdouble(&mut self, point: &Self, n: u32) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsb233_xdouble(self.as_mut_xsbpoint(), point.as_xsbpoint(), n);
         }
     }
 
     fn xdouble_inplace(&mut self, n: u32) {
+        let ptr = self.as_mut_xsbpoint();
+        // SAFETY: see above.
         unsafe {
-            xs233_sys::xsb233_xdouble(self.as_mut_xsbpoint(), self.as_xsbpoint(), n);
+            xs233_sys::xsb233_xdouble(ptr, ptr, n);
         }
     }
 
     fn mul<const N: usize>(&mut self, point: &Self, scalar: &Scalar<N>) {
+        // SAFETY: see above. as_void_ptr points to N bytes, and checks at compile
+        // time that N is at most 30, as c-xs233 requires.
         unsafe {
             xs233_sys::xsb233_mul_ladder(
                 self.as_mut_xsbpoint(),
@@ -103,35 +211,27 @@ impl Point for Xsb233Point {
     }
 
     fn mul_inplace<const N: usize>(&mut self, scalar: &Scalar<N>) {
+        let ptr = self.as_mut_xsbpoint();
+        // SAFETY: as in mul.
         unsafe {
-            xs233_sys::xsb233_mul_ladder(
-                self.as_mut_xsbpoint(),
-                self.as_xsbpoint(),
-                scalar.as_void_ptr(),
-                scalar.len(),
-            );
+            xs233_sys::xsb233_mul_ladder(ptr, ptr, scalar.as_void_ptr(), scalar.len());
         }
     }
 
     fn neutral() -> &'static Self {
-        unsafe {
-            let sys_ptr: *const xs233_sys::xsb233_point = &xs233_sys::xsb233_neutral;
-            let pt_ptr: *const Xsb233Point = sys_ptr.cast();
-            &*pt_ptr
-        }
+        // SAFETY: the static is initialized by c-xs233 and never written to.
+        Self::from_static(unsafe { &xs233_sys::xsb233_neutral })
     }
 
     fn generator() -> &'static Self {
-        unsafe {
-            let sys_ptr: *const xs233_sys::xsb233_point = &xs233_sys::xsb233_generator;
-            let pt_ptr: *const Xsb233Point = sys_ptr.cast();
-            &*pt_ptr
-        }
+        // SAFETY: the static is initialized by c-xs233 and never written to.
+        Self::from_static(unsafe { &xs233_sys::xsb233_generator })
     }
 
     fn mulgen<const N: usize>(scalar: &Scalar<N>) -> Self {
-        let mut out = Self::neutral().clone();
+        let mut out = *Self::neutral();
 
+        // SAFETY: as in mul.
         unsafe {
             xs233_sys::xsb233_mulgen(out.as_mut_xsbpoint(), scalar.as_void_ptr(), scalar.len())
         }
@@ -140,6 +240,7 @@ impl Point for Xsb233Point {
     }
 
     fn decode(&mut self, repr: &Self::EncodedPoint) -> Choice {
+        // SAFETY: see above. c-xs233 reads exactly 30 bytes from repr.
         let is_valid =
             unsafe { xs233_sys::xsb233_decode(self.as_mut_xsbpoint(), repr.as_ptr().cast()) };
 
@@ -147,31 +248,32 @@ impl Point for Xsb233Point {
     }
 
     fn encode(&self, dst: &mut Self::EncodedPoint) {
+        // SAFETY: see above. c-xs233 writes exactly 30 bytes to dst.
         unsafe { xs233_sys::xsb233_encode(dst.as_mut_ptr().cast(), self.as_xsbpoint()) };
     }
 
     fn is_neutral(&self) -> Choice {
+        // SAFETY: see above.
         let is_neutral = unsafe { xs233_sys::xsb233_is_neutral(self.as_xsbpoint()) };
         to_choice(is_neutral)
     }
 }
 
-impl Default for Xsb233Point {
-    fn default() -> Self {
-        Xsb233Point([0u64; 16])
-    }
-}
-
+#[cfg(feature = "ffi")]
 impl ConstantTimeEq for Xsb233Point {
     fn ct_eq(&self, other: &Self) -> Choice {
+        // SAFETY: both pointers come from live references and are only read.
         let is_eq = unsafe { xs233_sys::xsb233_equals(self.as_xsbpoint(), other.as_xsbpoint()) };
         to_choice(is_eq)
     }
 }
 
+#[cfg(feature = "ffi")]
 impl ConditionallySelectable for Xsb233Point {
     fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
         let mut out = Self::default();
+        // SAFETY: out is a local and the inputs are live references. from_choice
+        // returns 0 or 0xffffffff, as c-xs233 requires.
         unsafe {
             xs233_sys::xsb233_select(
                 out.as_mut_xsbpoint(),
@@ -184,21 +286,35 @@ impl ConditionallySelectable for Xsb233Point {
     }
 }
 
-impl ConditionallyNegatable for Xsb233Point {
-    fn conditional_negate(&mut self, choice: Choice) {
-        unsafe {
-            xs233_sys::xsb233_condneg(
-                self.as_mut_xsbpoint(),
-                self.as_xsbpoint(),
-                from_choice(choice),
-            )
-        }
-    }
+#[cfg(not(feature = "ffi"))]
+pub use crate::pure::Xsb233Point;
+
+/// A point in affine coordinates, for compact storage.
+pub type Xsb233AffinePoint = crate::affine::AffinePoint<Xsb233Point>;
+
+/// The xs233 group derived from B-233, for use with generic code over [`Curve`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Xsb233;
+
+impl Curve for Xsb233 {
+    const NAME: &'static str = "xsb233";
+    const ORDER: [u64; 4] = [
+        0x22031D2603CFE0D7,
+        0x0013E974E72F8A69,
+        0x0000000000000000,
+        0x0000010000000000,
+    ];
+    const COFACTOR: u32 = 2;
+    const ENCODED_LEN: usize = 30;
+
+    type Point = Xsb233Point;
+    type Scalar = ModScalar<Xsb233>;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scalar::Scalar;
     use crate::Point;
 
     const DECODE_EXPECT_OK: [&str; 20] = [
@@ -224,6 +340,30 @@ mod tests {
         "50c08e4f6ec9f4feb32152c19809879983422eb54200eb50792600d73901",
     ];
 
+    // the halves of the points in DECODE_EXPECT_OK
+    const HALVE_EXPECT: [&str; 20] = [
+        "000000000000000000000000000000000000000000000000000000000000",
+        "96157a6a58cb25da432af930f0d0826ff54564875b705790d3697f01f100",
+        "970e0397e7d3a72874dd7abca0de954f94be5ed121056fe3cd0405771701",
+        "40040cf7a1cf55ede9d2b22495da43656f753e23a24c0eb64232f8679b00",
+        "0aecc43a803cb193b2da4845b322cb6e5f44af91b7e3fb9b5a9b2fc96500",
+        "0ea38a6757ac120bebdef7316f122549bc4c6baeff693a01e3f3856bd900",
+        "788c6296110dff22da2b8d3d2d6b35ec19f93f7bd06311c79e7883726400",
+        "e85cfd5ad9f1c9b0fd2bca6b16ca1c4f5126b1ff93b78a558e80ae1d6201",
+        "40bdcee42f364d054e71add7ac1d609794443257269fbfe102b52e068201",
+        "00d010a3712c4c35e4a4b959dfb659dff5cab2ee1de6aea96b7a60ba0a00",
+        "8970bdef55cb784d7122ec0dd43d892b86ac56c863d09d3478e29f3cd001",
+        "07c4944917d67f87eeb8e885cad74004d052cb5c7cb3242b0558e1007300",
+        "013430d80c3cdad4af4e10780df7a635b35cfbf06508e294fd36e6459b00",
+        "d443765b431029b7d07c6a6aedac655bdee0b402413578aa002ec5274d00",
+        "ed234cd70b697a6cbe4db23323ba74fba19d7dc785171213905bd3546201",
+        "93c21de721a197ba27c477d43ecf301262ecb5721ce8d14b26cf001baf00",
+        "ba1e866544c6f8c2445211cdd1c111236332e031383cf93f5ad9c8d4be01",
+        "52ada9bf618474c4b7fe392ed295420a03372eaae1f52dc6cc69ff2cbb00",
+        "e55a2932c69b33ec59c3e32664e6519391ce1cb69e07dc8a07d673635d00",
+        "1bf4be2ab14a4d589791bfdbaee20d97cc9c0c8740d9096ae63cfa67c801",
+    ];
+
     const DECODE_EXPECT_ERR: [&str; 20] = [
         "801fb075ad4b6708d7ca674fd29f45118a1e98f34672e6f416db57ba5a01",
         "3c098046550a679c35d1aedb20a4a5d52f4342ba1b79f0a7d2c27a77b600",
@@ -251,13 +391,25 @@ mod tests {
     fn decode() {
         let mut point = Xsb233Point::default();
 
-        for hexstr in DECODE_EXPECT_OK {
+        for (hexstr, half_hexstr) in DECODE_EXPECT_OK.iter().zip(HALVE_EXPECT) {
             let buf: [u8; 30] = hex::decode(hexstr)
                 .expect("error decoding hex string")
                 .try_into()
                 .expect("parsed bytes of wrong length (!= 30)");
             let is_ok: bool = point.decode(&buf).into();
             assert!(is_ok);
+            assert_eq!(Xsb233Point::try_decode(&buf), Ok(point));
+            assert_eq!(Xsb233Point::try_from(&buf[..]), Ok(point));
+
+            // every valid point can be halved, and doubling undoes it
+            let mut half = Xsb233Point::default();
+            half.halve(&point);
+            assert_eq!(half.to_string(), half_hexstr);
+            assert_eq!(half + half, point);
+            let mut doubled = Xsb233Point::default();
+            doubled.double(&point);
+            doubled.halve_inplace();
+            assert_eq!(doubled, point);
         }
 
         for hexstr in DECODE_EXPECT_ERR {
@@ -267,6 +419,120 @@ mod tests {
                 .expect("parsed bytes of wrong length (!= 30)");
             let is_ok: bool = point.decode(&buf).into();
             assert!(!is_ok);
+            assert_eq!(
+                Xsb233Point::try_decode(&buf),
+                Err(crate::Error::InvalidEncoding)
+            );
+            assert_eq!(
+                Xsb233Point::try_from(&buf[..]),
+                Err(crate::Error::InvalidEncoding)
+            );
+        }
+
+        let neutral = [0u8; 30];
+        assert_eq!(
+            Xsb233Point::try_decode(&neutral),
+            Ok(*Xsb233Point::neutral())
+        );
+        assert_eq!(
+            Xsb233Point::try_decode_non_neutral(&neutral),
+            Err(crate::Error::IdentityPoint)
+        );
+        assert_eq!(
+            Xsb233Point::try_from(&neutral[..29]),
+            Err(crate::Error::InvalidLength {
+                expected: 30,
+                actual: 29
+            })
+        );
+    }
+
+    #[test]
+    fn fmt_and_parse() {
+        for hexstr in DECODE_EXPECT_OK {
+            let point: Xsb233Point = hexstr.parse().expect("error parsing point");
+            assert_eq!(format!("{}", point), hexstr);
+            assert_eq!(format!("{:x}", point), hexstr);
+            assert_eq!(format!("{:?}", point), format!("Xsb233Point({})", hexstr));
+        }
+
+        for hexstr in DECODE_EXPECT_ERR {
+            let res = hexstr.parse::<Xsb233Point>();
+            assert_eq!(res, Err(crate::ParsePointError::InvalidEncoding));
+        }
+
+        let res = DECODE_EXPECT_OK[1][2..].parse::<Xsb233Point>();
+        assert_eq!(res, Err(crate::ParsePointError::InvalidLength));
+        let res = format!("g{}", &DECODE_EXPECT_OK[1][1..]).parse::<Xsb233Point>();
+        assert_eq!(res, Err(crate::ParsePointError::InvalidHexDigit));
+        assert_eq!(
+            res.map_err(crate::Error::from),
+            Err(crate::Error::InvalidHex)
+        );
+
+        // the formatter flags apply to the whole hex string
+        let point: Xsb233Point = DECODE_EXPECT_OK[1].parse().unwrap();
+        assert_eq!(
+            format!("{:#x}", point),
+            format!("0x{}", DECODE_EXPECT_OK[1])
+        );
+        assert_eq!(
+            format!("{:>62}", point),
+            format!("  {}", DECODE_EXPECT_OK[1])
+        );
+        assert_eq!(
+            format!("{:-<61x}", point),
+            format!("{}-", DECODE_EXPECT_OK[1])
+        );
+        assert_eq!(
+            format!("{:#064x}", point),
+            format!("0x00{}", DECODE_EXPECT_OK[1])
+        );
+
+        // the debug output must not depend on the internal representation
+        let mut doubled = Xsb233Point::default();
+        doubled.double(Xsb233Point::generator());
+        let added = Xsb233Point::generator() + Xsb233Point::generator();
+        assert_eq!(format!("{:?}", doubled), format!("{:?}", added));
+    }
+
+    #[test]
+    fn hash_and_ord() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let points: Vec<Xsb233Point> = DECODE_EXPECT_OK
+            .iter()
+            .map(|hexstr| hexstr.parse().expect("error parsing point"))
+            .collect();
+
+        // the same points, but with a different internal representation
+        let mut others = Vec::new();
+        for point in &points {
+            let mut other = *point + *Xsb233Point::generator();
+            other.sub_assign(Xsb233Point::generator());
+            others.push(other);
+        }
+
+        let hash_set: HashSet<_> = points.iter().chain(others.iter()).collect();
+        assert_eq!(hash_set.len(), points.len());
+
+        let btree_set: BTreeSet<_> = points.iter().chain(others.iter()).collect();
+        assert_eq!(btree_set.len(), points.len());
+
+        let mut encodings: Vec<[u8; 30]> = points
+            .iter()
+            .map(|point| {
+                let mut buf = [0u8; 30];
+                point.encode(&mut buf);
+                buf
+            })
+            .collect();
+        encodings.sort();
+
+        for (point, encoding) in btree_set.iter().zip(encodings.iter()) {
+            let mut buf = [0u8; 30];
+            point.encode(&mut buf);
+            assert_eq!(&buf, encoding);
         }
     }
 
@@ -584,15 +850,15 @@ mod tests {
 
             // this is a lot less cumbersome in pornin's c libarary since he doesn't
             // treat these as distinct types, but such is the life of a rust hacker.
-            let scalar01: Scalar<01> = Scalar::new(buf[..01].try_into().unwrap());
-            let scalar02: Scalar<02> = Scalar::new(buf[..02].try_into().unwrap());
-            let scalar03: Scalar<03> = Scalar::new(buf[..03].try_into().unwrap());
-            let scalar04: Scalar<04> = Scalar::new(buf[..04].try_into().unwrap());
-            let scalar05: Scalar<05> = Scalar::new(buf[..05].try_into().unwrap());
-            let scalar06: Scalar<06> = Scalar::new(buf[..06].try_into().unwrap());
-            let scalar07: Scalar<07> = Scalar::new(buf[..07].try_into().unwrap());
-            let scalar08: Scalar<08> = Scalar::new(buf[..08].try_into().unwrap());
-            let scalar09: Scalar<09> = Scalar::new(buf[..09].try_into().unwrap());
+            let scalar01: Scalar<1> = Scalar::new(buf[..1].try_into().unwrap());
+            let scalar02: Scalar<2> = Scalar::new(buf[..2].try_into().unwrap());
+            let scalar03: Scalar<3> = Scalar::new(buf[..3].try_into().unwrap());
+            let scalar04: Scalar<4> = Scalar::new(buf[..4].try_into().unwrap());
+            let scalar05: Scalar<5> = Scalar::new(buf[..5].try_into().unwrap());
+            let scalar06: Scalar<6> = Scalar::new(buf[..6].try_into().unwrap());
+            let scalar07: Scalar<7> = Scalar::new(buf[..7].try_into().unwrap());
+            let scalar08: Scalar<8> = Scalar::new(buf[..8].try_into().unwrap());
+            let scalar09: Scalar<9> = Scalar::new(buf[..9].try_into().unwrap());
             let scalar10: Scalar<10> = Scalar::new(buf[..10].try_into().unwrap());
             let scalar12: Scalar<11> = Scalar::new(buf[..11].try_into().unwrap());
             let scalar11: Scalar<12> = Scalar::new(buf[..12].try_into().unwrap());
@@ -648,16 +914,16 @@ mod tests {
 
             let mut mul_points = vec![Xsb233Point::default(); 29];
 
-            Xsb233Point::mul(&mut mul_points[00], Xsb233Point::generator(), &scalar01);
-            Xsb233Point::mul(&mut mul_points[01], Xsb233Point::generator(), &scalar02);
-            Xsb233Point::mul(&mut mul_points[02], Xsb233Point::generator(), &scalar03);
-            Xsb233Point::mul(&mut mul_points[03], Xsb233Point::generator(), &scalar04);
-            Xsb233Point::mul(&mut mul_points[04], Xsb233Point::generator(), &scalar05);
-            Xsb233Point::mul(&mut mul_points[05], Xsb233Point::generator(), &scalar06);
-            Xsb233Point::mul(&mut mul_points[06], Xsb233Point::generator(), &scalar07);
-            Xsb233Point::mul(&mut mul_points[07], Xsb233Point::generator(), &scalar08);
-            Xsb233Point::mul(&mut mul_points[08], Xsb233Point::generator(), &scalar09);
-            Xsb233Point::mul(&mut mul_points[09], Xsb233Point::generator(), &scalar10);
+            Xsb233Point::mul(&mut mul_points[0], Xsb233Point::generator(), &scalar01);
+            Xsb233Point::mul(&mut mul_points[1], Xsb233Point::generator(), &scalar02);
+            Xsb233Point::mul(&mut mul_points[2], Xsb233Point::generator(), &scalar03);
+            Xsb233Point::mul(&mut mul_points[3], Xsb233Point::generator(), &scalar04);
+            Xsb233Point::mul(&mut mul_points[4], Xsb233Point::generator(), &scalar05);
+            Xsb233Point::mul(&mut mul_points[5], Xsb233Point::generator(), &scalar06);
+            Xsb233Point::mul(&mut mul_points[6], Xsb233Point::generator(), &scalar07);
+            Xsb233Point::mul(&mut mul_points[7], Xsb233Point::generator(), &scalar08);
+            Xsb233Point::mul(&mut mul_points[8], Xsb233Point::generator(), &scalar09);
+            Xsb233Point::mul(&mut mul_points[9], Xsb233Point::generator(), &scalar10);
             Xsb233Point::mul(&mut mul_points[10], Xsb233Point::generator(), &scalar11);
             Xsb233Point::mul(&mut mul_points[11], Xsb233Point::generator(), &scalar12);
             Xsb233Point::mul(&mut mul_points[12], Xsb233Point::generator(), &scalar13);
@@ -683,4 +949,52 @@ mod tests {
             }
         }
     }
+
+    // the coordinates read from c-xs233 points must match those of the pure Rust
+    // backend, see Extended::from_opaque
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn extended_coordinates() {
+        use crate::pure::xsb233::Params;
+        use crate::pure::CurveParams;
+
+        let generator = Xsb233Point::generator().to_extended();
+        let expected = Params::GENERATOR;
+        assert_eq!(
+            (generator.x, generator.s, generator.z, generator.t),
+            (expected.x, expected.s, expected.z, expected.t)
+        );
+
+        for hexstr in DECODE_EXPECT_OK {
+            let point: Xsb233Point = hexstr.parse().unwrap();
+            // the limbs of computed points are only partially reduced
+            let doubled = point + point;
+            let extended = doubled.to_extended();
+            assert_eq!(hex::encode(extended.encode()), doubled.to_string());
+            assert!(bool::from(
+                extended.ct_eq(&(point.to_extended().double::<Params>()))
+            ));
+            assert_eq!(Xsb233Point::from_extended(&extended), doubled);
+        }
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    #[should_panic(expected = "the point layout of c-xs233")]
+    fn opaque_layout_mismatch() {
+        let generator = Xsb233Point::generator();
+        let doubled = generator + generator;
+        let mut doubled_encoding = [0u8; 30];
+        doubled.encode(&mut doubled_encoding);
+
+        // X and S swapped
+        let mut swapped = doubled.0.opaque;
+        let (x, s) = swapped.split_at_mut(4);
+        x.swap_with_slice(&mut s[..4]);
+        crate::pure::assert_opaque_layout::<crate::pure::xsb233::Params>(
+            &generator.0.opaque,
+            &swapped,
+            &doubled_encoding,
+        );
+    }
 }
@@ -1,19 +1,84 @@
+use crate::curve::Curve;
+#[cfg(feature = "ffi")]
+use crate::pure::{assert_opaque_layout, Extended};
+use crate::scalar::ModScalar;
+#[cfg(feature = "ffi")]
 use crate::{from_choice, scalar::Scalar, to_choice, Point};
-use subtle::{Choice, ConditionallyNegatable, ConditionallySelectable, ConstantTimeEq};
+#[cfg(feature = "ffi")]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "ffi")]
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+#[cfg(feature = "ffi")]
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Xsk233Point(xs233_sys::xsk233_point);
+
+// The wrapper is repr(transparent), so a pointer to it is a valid pointer to the
+// wrapped c-xs233 point, and vice versa. Creating the pointers is safe; the unsafe
+// blocks below only pass them to c-xs233 while the borrows they come from are alive.
+#[cfg(feature = "ffi")]
+impl Xsk233Point {
+    fn as_xskpoint(&self) -> *const xs233_sys::xsk233_point {
+        &self.0
+    }
+
+    fn as_mut_xskpoint(&mut self) -> *mut xs233_sys::xsk233_point {
+        &mut self.0
+    }
 
-#[derive(Clone, Copy, Debug)]
-pub struct Xsk233Point([u64; 16]);
+    // Reads the coordinates of the point, see Extended::from_opaque.
+    pub(crate) fn to_extended(self) -> Extended {
+        Self::check_opaque_layout();
+        Extended::from_opaque(&self.0.opaque)
+    }
 
-impl Xsk233Point {
-    unsafe fn as_xskpoint(&self) -> *const xs233_sys::xsk233_point {
-        &*self as *const Xsk233Point as *const xs233_sys::xsk233_point
+    pub(crate) fn from_extended(point: &Extended) -> Self {
+        Self::check_opaque_layout();
+        Xsk233Point(xs233_sys::xsk233_point {
+            opaque: point.to_opaque(),
+        })
+    }
+
+    // Checks the layout of c-xs233 points once, see assert_opaque_layout.
+    fn check_opaque_layout() {
+        static CHECKED: AtomicBool = AtomicBool::new(false);
+        if CHECKED.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let generator = Self::generator();
+        let doubled = generator + generator;
+        let mut doubled_encoding = [0u8; 30];
+        doubled.encode(&mut doubled_encoding);
+        assert_opaque_layout::<crate::pure::xsk233::Params>(
+            &generator.0.opaque,
+            &doubled.0.opaque,
+            &doubled_encoding,
+        );
+        CHECKED.store(true, Ordering::Relaxed);
+    }
+
+    // Casts one of the statics of c-xs233 to a point.
+    fn from_static(point: &'static xs233_sys::xsk233_point) -> &'static Self {
+        let ptr: *const xs233_sys::xsk233_point = point;
+        // SAFETY: Xsk233Point is repr(transparent) over xsk233_point, so the
+        // pointer is valid for reads of an Xsk233Point and has the right alignment.
+        // The static lives forever and is never written to.
+        unsafe { &*ptr.cast::<Xsk233Point>() }
     }
+}
 
-    unsafe fn as_mut_xskpoint(&mut self) -> *mut xs233_sys::xsk233_point {
-        &mut *self as *mut Xsk233Point as *mut xs233_sys::xsk233_point
+// The neutral, as for the pure Rust backend. The all-zero representation is not
+// a valid point.
+#[cfg(feature = "ffi")]
+impl Default for Xsk233Point {
+    fn default() -> Self {
+        *Self::neutral()
     }
 }
 
+#[cfg(feature = "ffi")]
 crate::impl_ops!(Xsk233Point);
 
 // impl Xsk233Point {
@@ -42,78 +107,121 @@ crate::impl_ops!(Xsk233Point);
 //     }
 // }
 
+// In the impls below, c-xs233 only reads from the `*const` arguments and only
+// writes to the `*mut` one. It loads all inputs before storing the output, so the
+// output may alias an input.
+// All pointers come from live references to points, or are derived from the same
+// `&mut self` for in-place operations, so they are valid, aligned and not
+// aliased by a Rust reference during the call.
+#[cfg(feature = "ffi")]
 impl Point for Xsk233Point {
     type EncodedPoint = [u8; 30];
 
     fn add(&mut self, lhs: &Self, rhs: &Self) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsk233_add(self.as_mut_xskpoint(), lhs.as_xskpoint(), rhs.as_xskpoint());
         }
     }
 
     fn add_assign(&mut self, rhs: &Self) {
+        let ptr = self.as_mut_xskpoint();
+        // SAFETY: see above.
         unsafe {
-            xs233_sys::xsk233_add(
-                self.as_mut_xskpoint(),
-                self.as_xskpoint(),
-                rhs.as_xskpoint(),
-            );
+            xs233_sys::xsk233_add(ptr, ptr, rhs.as_xskpoint());
         }
     }
 
     fn sub(&mut self, lhs: &Self, rhs: &Self) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsk233_sub(self.as_mut_xskpoint(), lhs.as_xskpoint(), rhs.as_xskpoint());
         }
     }
 
     fn sub_assign(&mut self, rhs: &Self) {
+        let ptr = self.as_mut_xskpoint();
+        // SAFETY: see above.
         unsafe {
-            xs233_sys::xsk233_sub(
-                self.as_mut_xskpoint(),
-                self.as_xskpoint(),
-                rhs.as_xskpoint(),
-            );
+            xs233_sys::xsk233_sub(ptr, ptr, rhs.as_xskpoint());
         }
     }
 
     fn neg(&mut self, point: &Self) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsk233_neg(self.as_mut_xskpoint(), point.as_xskpoint());
         }
     }
 
     fn neg_inplace(&mut self) {
+        let ptr = self.as_mut_xskpoint();
+        // SAFETY: see above.
+        unsafe {
+            xs233_sys::xsk233_neg(ptr, ptr);
+        }
+    }
+
+    fn condneg(&mut self, point: &Self, choice: Choice) {
+        // SAFETY: see above. from_choice returns 0 or 0xffffffff, as c-xs233
+        // requires.
+        unsafe {
+            xs233_sys::xsk233_condneg(
+                self.as_mut_xskpoint(),
+                point.as_xskpoint(),
+                from_choice(choice),
+            );
+        }
+    }
+
+    fn condneg_inplace(&mut self, choice: Choice) {
+        let ptr = self.as_mut_xskpoint();
+        // SAFETY: as in condneg.
         unsafe {
-            xs233_sys::xsk233_neg(self.as_mut_xskpoint(), self.as_xskpoint());
+            xs233_sys::xsk233_condneg(ptr, ptr, from_choice(choice));
         }
     }
 
     fn double(&mut self, point: &Self) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsk233_double(self.as_mut_xskpoint(), point.as_xskpoint());
         }
     }
 
     fn double_inplace(&mut self) {
+        let ptr = self.as_mut_xskpoint();
+        // SAFETY: see above.
         unsafe {
-            xs233_sys::xsk233_double(self.as_mut_xskpoint(), self.as_xskpoint());
+            xs233_sys::xsk233_double(ptr, ptr);
         }
     }
 
+    // c-xs233 does not implement halving, so we use the pure Rust formulas on
+    // the coordinates of the point.
+    fn halve(&mut self, point: &Self) {
+        let half = point.to_extended().halve::<crate::pure::xsk233::Params>();
+        *self = Self::from_extended(&half);
+    }
+
     fn xdouble(&mut self, point: &Self, n: u32) {
+        // SAFETY: see above.
         unsafe {
             xs233_sys::xsk233_xdouble(self.as_mut_xskpoint(), point.as_xskpoint(), n);
         }
     }
 
     fn xdouble_inplace(&mut self, n: u32) {
+        let ptr = self.as_mut_xskpoint();
+        // SAFETY: see above.
         unsafe {
-            xs233_sys::xsk233_xdouble(self.as_mut_xskpoint(), self.as_xskpoint(), n);
+            xs233_sys::xsk233_xdouble(ptr, ptr, n);
         }
     }
 
-    fn mul<const N: usize>(&mut self, point: &Self, scalar: &crate::scalar::Scalar<N>) {
+    fn mul<const N: usize>(&mut self, point: &Self, scalar: &Scalar<N>) {
+        // SAFETY: see above. as_void_ptr points to N bytes, and checks at compile
+        // time that N is at most 30, as c-xs233 requires.
         unsafe {
             xs233_sys::xsk233_mul_frob(
                 self.as_mut_xskpoint(),
@@ -124,36 +232,28 @@ impl Point for Xsk233Point {
         }
     }
 
-    fn mul_inplace<const N: usize>(&mut self, scalar: &crate::scalar::Scalar<N>) {
+    fn mul_inplace<const N: usize>(&mut self, scalar: &Scalar<N>) {
+        let ptr = self.as_mut_xskpoint();
+        // SAFETY: as in mul.
         unsafe {
-            xs233_sys::xsk233_mul_frob(
-                self.as_mut_xskpoint(),
-                self.as_xskpoint(),
-                scalar.as_void_ptr(),
-                scalar.len(),
-            );
+            xs233_sys::xsk233_mul_frob(ptr, ptr, scalar.as_void_ptr(), scalar.len());
         }
     }
 
     fn neutral() -> &'static Self {
-        unsafe {
-            let sys_ptr: *const xs233_sys::xsk233_point = &xs233_sys::xsk233_neutral;
-            let pt_ptr: *const Xsk233Point = sys_ptr.cast();
-            &*pt_ptr
-        }
+        // SAFETY: the static is initialized by c-xs233 and never written to.
+        Self::from_static(unsafe { &xs233_sys::xsk233_neutral })
     }
 
     fn generator() -> &'static Self {
-        unsafe {
-            let sys_ptr: *const xs233_sys::xsk233_point = &xs233_sys::xsk233_generator;
-            let pt_ptr: *const Xsk233Point = sys_ptr.cast();
-            &*pt_ptr
-        }
+        // SAFETY: the static is initialized by c-xs233 and never written to.
+        Self::from_static(unsafe { &xs233_sys::xsk233_generator })
     }
 
     fn mulgen<const N: usize>(scalar: &Scalar<N>) -> Self {
-        let mut out = Self::neutral().clone();
+        let mut out = *Self::neutral();
 
+        // SAFETY: as in mul.
         unsafe {
             xs233_sys::xsk233_mulgen_frob(out.as_mut_xskpoint(), scalar.as_void_ptr(), scalar.len())
         }
@@ -161,39 +261,41 @@ impl Point for Xsk233Point {
         out
     }
 
-    fn decode(&mut self, repr: &[u8; 30]) -> Choice {
+    fn decode(&mut self, repr: &Self::EncodedPoint) -> Choice {
+        // SAFETY: see above. c-xs233 reads exactly 30 bytes from repr.
         let is_valid =
             unsafe { xs233_sys::xsk233_decode(self.as_mut_xskpoint(), repr.as_ptr().cast()) };
 
         to_choice(is_valid)
     }
 
-    fn encode(&self, dst: &mut [u8; 30]) {
+    fn encode(&self, dst: &mut Self::EncodedPoint) {
+        // SAFETY: see above. c-xs233 writes exactly 30 bytes to dst.
         unsafe { xs233_sys::xsk233_encode(dst.as_mut_ptr().cast(), self.as_xskpoint()) };
     }
 
     fn is_neutral(&self) -> Choice {
+        // SAFETY: see above.
         let is_neutral = unsafe { xs233_sys::xsk233_is_neutral(self.as_xskpoint()) };
         to_choice(is_neutral)
     }
 }
 
-impl Default for Xsk233Point {
-    fn default() -> Self {
-        Xsk233Point([0u64; 16])
-    }
-}
-
+#[cfg(feature = "ffi")]
 impl ConstantTimeEq for Xsk233Point {
     fn ct_eq(&self, other: &Self) -> Choice {
+        // SAFETY: both pointers come from live references and are only read.
         let is_eq = unsafe { xs233_sys::xsk233_equals(self.as_xskpoint(), other.as_xskpoint()) };
         to_choice(is_eq)
     }
 }
 
+#[cfg(feature = "ffi")]
 impl ConditionallySelectable for Xsk233Point {
     fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
         let mut out = Self::default();
+        // SAFETY: out is a local and the inputs are live references. from_choice
+        // returns 0 or 0xffffffff, as c-xs233 requires.
         unsafe {
             xs233_sys::xsk233_select(
                 out.as_mut_xskpoint(),
@@ -206,21 +308,35 @@ impl ConditionallySelectable for Xsk233Point {
     }
 }
 
-impl ConditionallyNegatable for Xsk233Point {
-    fn conditional_negate(&mut self, choice: Choice) {
-        unsafe {
-            xs233_sys::xsk233_condneg(
-                self.as_mut_xskpoint(),
-                self.as_xskpoint(),
-                from_choice(choice),
-            )
-        }
-    }
+#[cfg(not(feature = "ffi"))]
+pub use crate::pure::Xsk233Point;
+
+/// A point in affine coordinates, for compact storage.
+pub type Xsk233AffinePoint = crate::affine::AffinePoint<Xsk233Point>;
+
+/// The xs233 group derived from K-233, for use with generic code over [`Curve`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Xsk233;
+
+impl Curve for Xsk233 {
+    const NAME: &'static str = "xsk233";
+    const ORDER: [u64; 4] = [
+        0x6EFB1AD5F173ABDF,
+        0x00069D5BB915BCD4,
+        0x0000000000000000,
+        0x0000008000000000,
+    ];
+    const COFACTOR: u32 = 4;
+    const ENCODED_LEN: usize = 30;
+
+    type Point = Xsk233Point;
+    type Scalar = ModScalar<Xsk233>;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scalar::Scalar;
     use crate::Point;
 
     const DECODE_EXPECT_OK: [&str; 20] = [
@@ -246,6 +362,30 @@ mod tests {
         "23b6566c74a3de5cef968835f901b7c218108ec6106096ac8c07afadda00",
     ];
 
+    // the halves of the points in DECODE_EXPECT_OK
+    const HALVE_EXPECT: [&str; 20] = [
+        "000000000000000000000000000000000000000000000000000000000000",
+        "2cab32198c3042826788d4515cca4741dfec178f1163b6d7a79cf62b4c01",
+        "0b83a057c33a76b4efd9f7ea15a3d31ce2815a0f9e1e853b28c218b20b01",
+        "e778cbee84d0a3c6f362dcb5dd73610b152e0a8239459681c797715fa101",
+        "1563423d156167d16c22b72f7c2a3945b51dd3bbfb8e9c46508f3a02c900",
+        "28e417dba574e3fbb47f13646a32eb26ab09be2b72304858ae81c589a201",
+        "5b94bd7a50a87ab5204d9b7a7b7cf56757c931574135f66ff5af3f0f3b01",
+        "fb03f95d53e07aea5e1921aaaf38c02d09f0844576d51d1ad3d435109800",
+        "3c21dcd6ea43ef6e69f3546853acffbb1e7487b581bf1b981f824e81df00",
+        "80cd88776298e593abf81635d0051bbda15ef1e11b6b4185e12d32a68c01",
+        "7012d84b7835622be037b7d5cbd953844943662fc8e07b937c8e36b1c600",
+        "8e98bf7130ae521c3e996d1beb9ee6838d6be6865c65c4a55d06e8716f01",
+        "c0fa18ba054ca7b8ce9b326209100106ffd43c386818e23521ff26498f01",
+        "3fcf0594f1192a0266ae3edb2e961b5945ed6bcaa24f838bb9d451ec8801",
+        "c8b4a3465f58749fc611a05226fe8c42a54ecea3f830e6b340c602af7600",
+        "70f2f138c7e26ba6f58ad76431b37f0fc62c449edf98e1d74fa69dab1f01",
+        "bb5f8a2b80eb84208fb9485ad4d97a7bac16a8c1de5895b915f3fb88eb01",
+        "8b08dd9f8022b481cc0cf4eea326cd259f1116e869445493a88c7ba11001",
+        "d1c51b76a9f2850598d171f58d8a9d4221372bf6aaf162be56d7db418401",
+        "2183c28a1e913bbd8f6ff2ac8362fd43afc235cb215cadc52e0964561300",
+    ];
+
     const DECODE_EXPECT_ERR: [&str; 20] = [
         /* w = 1 yields w^2 + w = 0, which is invalid */
         "010000000000000000000000000000000000000000000000000000000000",
@@ -276,13 +416,25 @@ mod tests {
     fn decode() {
         let mut point = Xsk233Point::default();
 
-        for hexstr in DECODE_EXPECT_OK {
+        for (hexstr, half_hexstr) in DECODE_EXPECT_OK.iter().zip(HALVE_EXPECT) {
             let buf: [u8; 30] = hex::decode(hexstr)
                 .expect("error decoding hex string")
                 .try_into()
                 .expect("parsed bytes of wrong length (!= 30)");
             let is_ok: bool = point.decode(&buf).into();
             assert!(is_ok);
+            assert_eq!(Xsk233Point::try_decode(&buf), Ok(point));
+            assert_eq!(Xsk233Point::try_from(&buf[..]), Ok(point));
+
+            // every valid point can be halved, and doubling undoes it
+            let mut half = Xsk233Point::default();
+            half.halve(&point);
+            assert_eq!(half.to_string(), half_hexstr);
+            assert_eq!(half + half, point);
+            let mut doubled = Xsk233Point::default();
+            doubled.double(&point);
+            doubled.halve_inplace();
+            assert_eq!(doubled, point);
         }
 
         for hexstr in DECODE_EXPECT_ERR {
@@ -292,6 +444,120 @@ mod tests {
                 .expect("parsed bytes of wrong length (!= 30)");
             let is_ok: bool = point.decode(&buf).into();
             assert!(!is_ok);
+            assert_eq!(
+                Xsk233Point::try_decode(&buf),
+                Err(crate::Error::InvalidEncoding)
+            );
+            assert_eq!(
+                Xsk233Point::try_from(&buf[..]),
+                Err(crate::Error::InvalidEncoding)
+            );
+        }
+
+        let neutral = [0u8; 30];
+        assert_eq!(
+            Xsk233Point::try_decode(&neutral),
+            Ok(*Xsk233Point::neutral())
+        );
+        assert_eq!(
+            Xsk233Point::try_decode_non_neutral(&neutral),
+            Err(crate::Error::IdentityPoint)
+        );
+        assert_eq!(
+            Xsk233Point::try_from(&neutral[..29]),
+            Err(crate::Error::InvalidLength {
+                expected: 30,
+                actual: 29
+            })
+        );
+    }
+
+    #[test]
+    fn fmt_and_parse() {
+        for hexstr in DECODE_EXPECT_OK {
+            let point: Xsk233Point = hexstr.parse().expect("error parsing point");
+            assert_eq!(format!("{}", point), hexstr);
+            assert_eq!(format!("{:x}", point), hexstr);
+            assert_eq!(format!("{:?}", point), format!("Xsk233Point({})", hexstr));
+        }
+
+        for hexstr in DECODE_EXPECT_ERR {
+            let res = hexstr.parse::<Xsk233Point>();
+            assert_eq!(res, Err(crate::ParsePointError::InvalidEncoding));
+        }
+
+        let res = DECODE_EXPECT_OK[1][2..].parse::<Xsk233Point>();
+        assert_eq!(res, Err(crate::ParsePointError::InvalidLength));
+        let res = format!("g{}", &DECODE_EXPECT_OK[1][1..]).parse::<Xsk233Point>();
+        assert_eq!(res, Err(crate::ParsePointError::InvalidHexDigit));
+        assert_eq!(
+            res.map_err(crate::Error::from),
+            Err(crate::Error::InvalidHex)
+        );
+
+        // the formatter flags apply to the whole hex string
+        let point: Xsk233Point = DECODE_EXPECT_OK[1].parse().unwrap();
+        assert_eq!(
+            format!("{:#x}", point),
+            format!("0x{}", DECODE_EXPECT_OK[1])
+        );
+        assert_eq!(
+            format!("{:>62}", point),
+            format!("  {}", DECODE_EXPECT_OK[1])
+        );
+        assert_eq!(
+            format!("{:-<61x}", point),
+            format!("{}-", DECODE_EXPECT_OK[1])
+        );
+        assert_eq!(
+            format!("{:#064x}", point),
+            format!("0x00{}", DECODE_EXPECT_OK[1])
+        );
+
+        // the debug output must not depend on the internal representation
+        let mut doubled = Xsk233Point::default();
+        doubled.double(Xsk233Point::generator());
+        let added = Xsk233Point::generator() + Xsk233Point::generator();
+        assert_eq!(format!("{:?}", doubled), format!("{:?}", added));
+    }
+
+    #[test]
+    fn hash_and_ord() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let points: Vec<Xsk233Point> = DECODE_EXPECT_OK
+            .iter()
+            .map(|hexstr| hexstr.parse().expect("error parsing point"))
+            .collect();
+
+        // the same points, but with a different internal representation
+        let mut others = Vec::new();
+        for point in &points {
+            let mut other = *point + *Xsk233Point::generator();
+            other.sub_assign(Xsk233Point::generator());
+            others.push(other);
+        }
+
+        let hash_set: HashSet<_> = points.iter().chain(others.iter()).collect();
+        assert_eq!(hash_set.len(), points.len());
+
+        let btree_set: BTreeSet<_> = points.iter().chain(others.iter()).collect();
+        assert_eq!(btree_set.len(), points.len());
+
+        let mut encodings: Vec<[u8; 30]> = points
+            .iter()
+            .map(|point| {
+                let mut buf = [0u8; 30];
+                point.encode(&mut buf);
+                buf
+            })
+            .collect();
+        encodings.sort();
+
+        for (point, encoding) in btree_set.iter().zip(encodings.iter()) {
+            let mut buf = [0u8; 30];
+            point.encode(&mut buf);
+            assert_eq!(&buf, encoding);
         }
     }
 
@@ -609,15 +875,15 @@ mod tests {
 
             // this is a lot less cumbersome in pornin's c libarary since he doesn't
             // treat these as distinct types, but such is the life of a rust hacker.
-            let scalar01: Scalar<01> = Scalar::new(buf[..01].try_into().unwrap());
-            let scalar02: Scalar<02> = Scalar::new(buf[..02].try_into().unwrap());
-            let scalar03: Scalar<03> = Scalar::new(buf[..03].try_into().unwrap());
-            let scalar04: Scalar<04> = Scalar::new(buf[..04].try_into().unwrap());
-            let scalar05: Scalar<05> = Scalar::new(buf[..05].try_into().unwrap());
-            let scalar06: Scalar<06> = Scalar::new(buf[..06].try_into().unwrap());
-            let scalar07: Scalar<07> = Scalar::new(buf[..07].try_into().unwrap());
-            let scalar08: Scalar<08> = Scalar::new(buf[..08].try_into().unwrap());
-            let scalar09: Scalar<09> = Scalar::new(buf[..09].try_into().unwrap());
+            let scalar01: Scalar<1> = Scalar::new(buf[..1].try_into().unwrap());
+            let scalar02: Scalar<2> = Scalar::new(buf[..2].try_into().unwrap());
+            let scalar03: Scalar<3> = Scalar::new(buf[..3].try_into().unwrap());
+            let scalar04: Scalar<4> = Scalar::new(buf[..4].try_into().unwrap());
+            let scalar05: Scalar<5> = Scalar::new(buf[..5].try_into().unwrap());
+            let scalar06: Scalar<6> = Scalar::new(buf[..6].try_into().unwrap());
+            let scalar07: Scalar<7> = Scalar::new(buf[..7].try_into().unwrap());
+            let scalar08: Scalar<8> = Scalar::new(buf[..8].try_into().unwrap());
+            let scalar09: Scalar<9> = Scalar::new(buf[..9].try_into().unwrap());
             let scalar10: Scalar<10> = Scalar::new(buf[..10].try_into().unwrap());
             let scalar12: Scalar<11> = Scalar::new(buf[..11].try_into().unwrap());
             let scalar11: Scalar<12> = Scalar::new(buf[..12].try_into().unwrap());
@@ -708,4 +974,52 @@ mod tests {
             }
         }
     }
+
+    // the coordinates read from c-xs233 points must match those of the pure Rust
+    // backend, see Extended::from_opaque
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn extended_coordinates() {
+        use crate::pure::xsk233::Params;
+        use crate::pure::CurveParams;
+
+        let generator = Xsk233Point::generator().to_extended();
+        let expected = Params::GENERATOR;
+        assert_eq!(
+            (generator.x, generator.s, generator.z, generator.t),
+            (expected.x, expected.s, expected.z, expected.t)
+        );
+
+        for hexstr in DECODE_EXPECT_OK {
+            let point: Xsk233Point = hexstr.parse().unwrap();
+            // the limbs of computed points are only partially reduced
+            let doubled = point + point;
+            let extended = doubled.to_extended();
+            assert_eq!(hex::encode(extended.encode()), doubled.to_string());
+            assert!(bool::from(
+                extended.ct_eq(&(point.to_extended().double::<Params>()))
+            ));
+            assert_eq!(Xsk233Point::from_extended(&extended), doubled);
+        }
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    #[should_panic(expected = "the point layout of c-xs233")]
+    fn opaque_layout_mismatch() {
+        let generator = Xsk233Point::generator();
+        let doubled = generator + generator;
+        let mut doubled_encoding = [0u8; 30];
+        doubled.encode(&mut doubled_encoding);
+
+        // X and S swapped
+        let mut swapped = doubled.0.opaque;
+        let (x, s) = swapped.split_at_mut(4);
+        x.swap_with_slice(&mut s[..4]);
+        crate::pure::assert_opaque_layout::<crate::pure::xsk233::Params>(
+            &generator.0.opaque,
+            &swapped,
+            &doubled_encoding,
+        );
+    }
 }